@@ -18,31 +18,382 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use clap::Parser;
-use olscorer_core::audio_utils::AudioData;
-use olscorer_core::transcription::Transcriber;
+use clap::{Parser, ValueEnum};
+use olscorer_core::audio_utils::{AudioData, AudioFormat};
+use olscorer_core::midi_export;
+use olscorer_core::onset_detection::SpectralFluxDetector;
+use olscorer_core::transcription::{DisplayStyle, Note, Transcriber, TranscriptionConfig};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// Highest frequency (in Hz) which can be given as a frequency limit, which is
+/// the Nyquist frequency of audio sampled at 44100 Hz
+const MAX_FREQUENCY_LIMIT: f64 = 22050.0;
+
+/// Formats in which the detected notes can be printed
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum OutputFormat {
+    /// Comma-separated list of note names
+    Text,
+    /// Array of objects with the name and timing of each note
+    Json,
+}
+
+impl OutputFormat {
+    /// Returns the extension of the output files written in this format
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "txt",
+            OutputFormat::Json => "json",
+        }
+    }
+}
 
 #[derive(Debug, Parser)]
 #[clap(name = "Olscorer", author, version, about)]
 struct OlscorerArgs {
     /// Path to input audio file
-    input_file: std::path::PathBuf,
+    #[clap(required_unless_present = "batch")]
+    input_file: Option<PathBuf>,
+
+    /// Transcribe every audio file in a directory instead of a single file,
+    /// writing the notes of each file to a separate output file
+    #[clap(long, conflicts_with_all = ["input_file", "output_midi"])]
+    batch: Option<PathBuf>,
+
+    /// Directory to write the output files to in batch mode, instead of
+    /// alongside the audio files
+    #[clap(long, requires = "batch")]
+    output_dir: Option<PathBuf>,
+
+    /// Transcribe the files in batch mode in parallel
+    #[clap(long, requires = "batch")]
+    parallel: bool,
+
+    /// Spell note names with flats instead of sharps, for example Bb4
+    /// instead of A#4
+    #[clap(long)]
+    flats: bool,
+
+    /// Path to write the notes to as a MIDI file
+    #[clap(long)]
+    output_midi: Option<PathBuf>,
+
+    /// Format of the printed notes
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Lowest pitch (in Hz) which can be detected
+    #[clap(long)]
+    min_frequency: Option<f64>,
+
+    /// Highest pitch (in Hz) which can be detected, at most 22050 Hz
+    #[clap(long)]
+    max_frequency: Option<f64>,
+
+    /// Threshold of the spectral flux onset detector, which is used to find
+    /// the onsets of notes. This is the minimum strength of a note onset, as
+    /// a fraction of the strongest onset in the audio, between 0 (exclusive)
+    /// and 1 (inclusive). Lower values detect softer notes. The default is
+    /// the threshold of the default spectral flux detector.
+    #[clap(
+        long,
+        value_parser = parse_onset_threshold,
+        default_value_t = SpectralFluxDetector::default().threshold
+    )]
+    onset_threshold: f64,
+}
+
+/// Parses an onset threshold, which must be in the range (0, 1]
+fn parse_onset_threshold(value: &str) -> Result<f64, String> {
+    let threshold: f64 = value
+        .parse()
+        .map_err(|_| format!("`{}` is not a number", value))?;
+
+    if threshold > 0.0 && threshold <= 1.0 {
+        Ok(threshold)
+    } else {
+        Err("onset threshold must be greater than 0 and at most 1".to_string())
+    }
+}
+
+/// Checks that the frequency limits form a valid range
+///
+/// Returns an error message if either limit is not positive, if the maximum
+/// is greater than 22050 Hz, or if the minimum is not less than the maximum.
+fn validate_frequency_range(
+    min_frequency: Option<f64>,
+    max_frequency: Option<f64>,
+) -> Result<(), String> {
+    for (name, limit) in [("minimum", min_frequency), ("maximum", max_frequency)] {
+        if let Some(limit) = limit {
+            if !(limit > 0.0 && limit <= MAX_FREQUENCY_LIMIT) {
+                return Err(format!(
+                    "{} frequency must be greater than 0 Hz and at most {} Hz",
+                    name, MAX_FREQUENCY_LIMIT
+                ));
+            }
+        }
+    }
+
+    if let (Some(min_frequency), Some(max_frequency)) = (min_frequency, max_frequency) {
+        if min_frequency >= max_frequency {
+            return Err("minimum frequency must be less than maximum frequency".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// A detected note, as printed in the JSON output
+#[derive(Debug, Serialize)]
+struct JsonNote {
+    note: String,
+    start_sample: usize,
+    duration_samples: usize,
+    start_seconds: f64,
+}
+
+/// Formats the notes as a JSON array of objects
+fn format_json(notes: &[Note], sample_rate: u32, display_style: DisplayStyle) -> String {
+    let json_notes: Vec<JsonNote> = notes
+        .iter()
+        .map(|note| JsonNote {
+            note: note.name.display_with_style(display_style),
+            start_sample: note.start,
+            duration_samples: note.duration,
+            start_seconds: note.start_seconds(sample_rate),
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&json_notes).expect("Error formatting notes as JSON")
+}
+
+/// Formats the notes as a comma-separated list of note names
+fn format_text(notes: &[Note], display_style: DisplayStyle) -> String {
+    notes
+        .iter()
+        .map(|note| note.name.display_with_style(display_style))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl OlscorerArgs {
+    /// Creates the transcription configuration described by the arguments
+    ///
+    /// The onset detector is a `SpectralFluxDetector` with the given onset
+    /// threshold, which is the same detector as in the default configuration
+    /// when the threshold is not given.
+    fn transcription_config(&self) -> TranscriptionConfig {
+        TranscriptionConfig {
+            min_frequency: self.min_frequency,
+            max_frequency: self.max_frequency,
+            onset_detector: Box::new(SpectralFluxDetector {
+                threshold: self.onset_threshold,
+                ..SpectralFluxDetector::default()
+            }),
+            ..TranscriptionConfig::default()
+        }
+    }
+
+    /// Formats the notes in the output format given by the arguments
+    fn format_notes(&self, notes: &[Note], sample_rate: u32) -> String {
+        let display_style = if self.flats {
+            DisplayStyle::Flat
+        } else {
+            DisplayStyle::Sharp
+        };
+
+        match self.format {
+            OutputFormat::Text => format_text(notes, display_style),
+            OutputFormat::Json => format_json(notes, sample_rate, display_style),
+        }
+    }
+}
+
+/// Returns the paths of the supported audio files in a directory, sorted by
+/// name
+///
+/// Subdirectories are not searched.
+fn find_audio_files(directory: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut audio_files = vec![];
+
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        let is_audio_file = path
+            .extension()
+            .map(|ext| AudioFormat::from_extension(&ext.to_string_lossy().to_lowercase()))
+            .is_some_and(|format| format.is_some());
+
+        if path.is_file() && is_audio_file {
+            audio_files.push(path);
+        }
+    }
+
+    audio_files.sort();
+
+    Ok(audio_files)
+}
+
+/// Transcribes an audio file and writes the formatted notes to an output file
+///
+/// The output file has the name of the audio file with the extension of the
+/// output format, and is written to the output directory if one is given, or
+/// alongside the audio file otherwise.
+///
+/// Returns the path of the output file.
+fn transcribe_to_file(
+    input_file: &Path,
+    output_dir: Option<&Path>,
+    args: &OlscorerArgs,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let audio_data = AudioData::read_audio_file(input_file.to_path_buf())?;
+    let sample_rate = audio_data.sample_rate;
+
+    let notes = Transcriber::get_notes_with_config(audio_data, args.transcription_config());
+
+    let output_file = match output_dir {
+        Some(output_dir) => output_dir.join(input_file.file_name().unwrap_or_default()),
+        None => input_file.to_path_buf(),
+    }
+    .with_extension(args.format.extension());
+
+    std::fs::write(&output_file, args.format_notes(&notes, sample_rate) + "\n")?;
+
+    Ok(output_file)
+}
+
+/// Transcribes every audio file in the batch directory
+///
+/// Errors on individual files are printed and do not stop the remaining files
+/// from being transcribed.
+///
+/// Returns the number of files which could not be transcribed.
+fn run_batch(batch_dir: &Path, args: &OlscorerArgs) -> Result<usize, Box<dyn Error>> {
+    let audio_files = find_audio_files(batch_dir)?;
+
+    if let Some(output_dir) = &args.output_dir {
+        std::fs::create_dir_all(output_dir)?;
+    }
+
+    let transcribe = |input_file: &PathBuf| match transcribe_to_file(
+        input_file,
+        args.output_dir.as_deref(),
+        args,
+    ) {
+        Ok(output_file) => {
+            eprintln!("{} -> {}", input_file.display(), output_file.display());
+            true
+        }
+        Err(e) => {
+            eprintln!("Error transcribing {}: {}", input_file.display(), e);
+            false
+        }
+    };
+
+    let failures = if args.parallel {
+        audio_files.par_iter().filter(|f| !transcribe(f)).count()
+    } else {
+        audio_files.iter().filter(|f| !transcribe(f)).count()
+    };
+
+    Ok(failures)
 }
 
 fn main() {
     let args = OlscorerArgs::parse();
 
-    let audio_data = AudioData::read_wav_file(args.input_file).expect("Error reading wav file");
+    if let Err(message) = validate_frequency_range(args.min_frequency, args.max_frequency) {
+        eprintln!("Error: {}", message);
+        std::process::exit(1);
+    }
+
+    if let Some(batch_dir) = &args.batch {
+        match run_batch(batch_dir, &args) {
+            Ok(0) => {}
+            Ok(_) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let input_file = args.input_file.clone().expect("Input file is required");
+    let audio_data = AudioData::read_audio_file(input_file).expect("Error reading audio file");
+
+    let sample_rate = audio_data.sample_rate;
 
     // Get all the notes in the audio
-    let all_notes = Transcriber::get_notes(audio_data);
+    let all_notes = Transcriber::get_notes_with_config(audio_data, args.transcription_config());
 
-    // Format and print notes as a comma-separated list
-    let output_notes = all_notes
-        .iter()
-        .map(|note| note.name.to_string())
-        .collect::<Vec<String>>()
-        .join(", ");
+    if let Some(midi_path) = &args.output_midi {
+        let midi_file = std::fs::File::create(midi_path).expect("Error creating MIDI file");
+        midi_export::write_midi(&all_notes, sample_rate, std::io::BufWriter::new(midi_file))
+            .expect("Error writing MIDI file");
+    }
+
+    // Format and print notes
+    println!("{}", args.format_notes(&all_notes, sample_rate));
+}
+
+#[cfg(test)]
+mod tests {
+    mod format_tests {
+        use crate::{format_json, format_text};
+        use olscorer_core::transcription::{DisplayStyle, Note, NoteName};
+
+        fn notes() -> Vec<Note> {
+            vec![
+                Note {
+                    name: NoteName::C(4),
+                    start: 0,
+                    duration: 22050,
+                    confidence: 1.0,
+                    cents_deviation: 0.0,
+                },
+                Note {
+                    name: NoteName::ASharp(4),
+                    start: 22050,
+                    duration: 44100,
+                    confidence: 1.0,
+                    cents_deviation: 0.0,
+                },
+            ]
+        }
+
+        #[test]
+        fn json_output_parses_correctly() {
+            let json = format_json(&notes(), 44100, DisplayStyle::Sharp);
+
+            let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(
+                serde_json::json!([
+                    {
+                        "note": "C4",
+                        "start_sample": 0,
+                        "duration_samples": 22050,
+                        "start_seconds": 0.0
+                    },
+                    {
+                        "note": "A#4",
+                        "start_sample": 22050,
+                        "duration_samples": 44100,
+                        "start_seconds": 0.5
+                    }
+                ]),
+                parsed
+            );
+        }
 
-    println!("{}", output_notes);
+        #[test]
+        fn text_output_is_comma_separated() {
+            assert_eq!("C4, Bb4", format_text(&notes(), DisplayStyle::Flat));
+        }
+    }
 }
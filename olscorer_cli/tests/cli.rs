@@ -0,0 +1,219 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use assert_cmd::Command;
+use olscorer_core::audio_utils::AudioData;
+use olscorer_core::transcription::Transcriber;
+
+fn olscorer_cli() -> Command {
+    Command::cargo_bin("olscorer_cli").unwrap()
+}
+
+fn example_audio() -> String {
+    format!(
+        "{}/../resources/test/example_audio.wav",
+        env!("CARGO_MANIFEST_DIR")
+    )
+}
+
+#[test]
+fn minimum_above_maximum_is_rejected() {
+    olscorer_cli()
+        .args([
+            &example_audio(),
+            "--min-frequency",
+            "1200",
+            "--max-frequency",
+            "80",
+        ])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("Error: minimum frequency must be less than maximum frequency\n");
+}
+
+#[test]
+fn maximum_above_nyquist_frequency_is_rejected() {
+    olscorer_cli()
+        .args([&example_audio(), "--max-frequency", "30000"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("Error: maximum frequency must be greater than 0 Hz and at most 22050 Hz\n");
+}
+
+#[test]
+fn non_positive_minimum_is_rejected() {
+    olscorer_cli()
+        .args([&example_audio(), "--min-frequency", "0"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("Error: minimum frequency must be greater than 0 Hz and at most 22050 Hz\n");
+}
+
+#[test]
+fn valid_range_transcribes_notes() {
+    olscorer_cli()
+        .args([
+            &example_audio(),
+            "--min-frequency",
+            "80",
+            "--max-frequency",
+            "1200",
+        ])
+        .assert()
+        .success()
+        .stdout("C5, E5, G5\n");
+}
+
+#[test]
+fn high_onset_threshold_detects_fewer_notes() {
+    let twinkle = format!(
+        "{}/../resources/test/twinkle_twinkle_little_star.wav",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let count_notes = |output: Vec<u8>| String::from_utf8(output).unwrap().split(", ").count();
+
+    let default_output = olscorer_cli().arg(&twinkle).assert().success();
+    let high_threshold_output = olscorer_cli()
+        .args([&twinkle, "--onset-threshold", "0.9"])
+        .assert()
+        .success();
+
+    assert!(
+        count_notes(high_threshold_output.get_output().stdout.clone())
+            < count_notes(default_output.get_output().stdout.clone())
+    );
+}
+
+#[test]
+fn default_onset_threshold_matches_default_transcription() {
+    let twinkle = format!(
+        "{}/../resources/test/twinkle_twinkle_little_star.wav",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let notes = Transcriber::get_notes(AudioData::read_wav_file(twinkle.clone().into()).unwrap());
+
+    let output = olscorer_cli()
+        .args([&twinkle, "--format", "json"])
+        .assert()
+        .success();
+    let printed: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+
+    let printed_notes: Vec<(String, u64, u64)> = printed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|note| {
+            (
+                note["note"].as_str().unwrap().to_string(),
+                note["start_sample"].as_u64().unwrap(),
+                note["duration_samples"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    let expected_notes: Vec<(String, u64, u64)> = notes
+        .iter()
+        .map(|note| {
+            (
+                note.name.to_string(),
+                note.start as u64,
+                note.duration as u64,
+            )
+        })
+        .collect();
+
+    assert!(!expected_notes.is_empty());
+    assert_eq!(expected_notes, printed_notes);
+}
+
+#[test]
+fn onset_threshold_out_of_range_is_rejected() {
+    olscorer_cli()
+        .args([&example_audio(), "--onset-threshold", "1.5"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "onset threshold must be greater than 0 and at most 1",
+        ));
+}
+
+/// Writes a one second WAV file containing silence followed by a sine tone
+fn write_tone(path: &std::path::Path, frequency: f64) {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+
+    for i in 0..44100 {
+        let sample = if i < 11025 {
+            0.0
+        } else {
+            (2.0 * std::f64::consts::PI * frequency * i as f64 / 44100.0).sin()
+        };
+        writer.write_sample((sample * 16000.0) as i16).unwrap();
+    }
+
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn batch_writes_one_output_file_per_input() {
+    let input_dir = tempfile::tempdir().unwrap();
+    let output_dir = tempfile::tempdir().unwrap();
+    write_tone(&input_dir.path().join("a4.wav"), 440.0);
+    write_tone(&input_dir.path().join("c5.wav"), 523.25);
+
+    olscorer_cli()
+        .arg("--batch")
+        .arg(input_dir.path())
+        .arg("--output-dir")
+        .arg(output_dir.path())
+        .assert()
+        .success();
+
+    let read_output = |name: &str| std::fs::read_to_string(output_dir.path().join(name)).unwrap();
+    assert_eq!(2, std::fs::read_dir(output_dir.path()).unwrap().count());
+    assert_eq!("A4\n", read_output("a4.txt"));
+    assert_eq!("C5\n", read_output("c5.txt"));
+}
+
+#[test]
+fn batch_continues_after_invalid_file() {
+    let input_dir = tempfile::tempdir().unwrap();
+    write_tone(&input_dir.path().join("a4.wav"), 440.0);
+    std::fs::write(input_dir.path().join("broken.wav"), "not audio").unwrap();
+
+    olscorer_cli()
+        .arg("--batch")
+        .arg(input_dir.path())
+        .args(["--parallel", "--format", "json"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicates::str::contains("Error transcribing"));
+
+    assert!(input_dir.path().join("a4.json").exists());
+    assert!(!input_dir.path().join("broken.json").exists());
+}
@@ -0,0 +1,57 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use olscorer_core::{audio_utils::AudioData, transcription::Transcriber};
+use std::path::PathBuf;
+
+/// Compares the time taken to find the notes in a recording with many onsets
+/// when its frames are processed on a single thread and on all available cores
+///
+/// The speedup is only visible on a machine with several cores.
+fn get_notes(c: &mut Criterion) {
+    let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    filepath.push("../resources/test/twinkle_twinkle_little_star.wav");
+    let audio_data = AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+
+    let mut group = c.benchmark_group("get_notes");
+    group.sample_size(10);
+
+    let mut thread_counts = vec![1, rayon::current_num_threads()];
+    thread_counts.dedup();
+
+    for threads in thread_counts {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+
+        group.bench_with_input(
+            BenchmarkId::new("threads", threads),
+            &audio_data,
+            |b, audio_data| b.iter(|| pool.install(|| Transcriber::get_notes(audio_data.clone()))),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, get_notes);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use olscorer_core::pitch_detection::Mpm;
+
+/// Calculates the square sums of the normalized square difference function
+/// directly from their definition, in O(n²) time
+fn brute_force_square_sums(samples: &[f64]) -> Vec<f64> {
+    let n = samples.len();
+
+    (0..n)
+        .map(|tau| {
+            (0..n - tau)
+                .map(|j| samples[j] * samples[j] + samples[j + tau] * samples[j + tau])
+                .sum()
+        })
+        .collect()
+}
+
+/// Compares the brute-force square sums with the whole normalized square
+/// difference function, which uses the O(n) running sum
+///
+/// The running sum should make `get_nsdf` at least 10 times faster than the
+/// brute-force square sums alone.
+fn square_sums(c: &mut Criterion) {
+    let mut group = c.benchmark_group("square_sums");
+
+    for n in [1024, 4096] {
+        let samples: Vec<f64> = (0..n)
+            .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / 44100.0).sin())
+            .collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("brute_force", n),
+            &samples,
+            |b, samples| b.iter(|| brute_force_square_sums(black_box(samples))),
+        );
+        group.bench_with_input(BenchmarkId::new("get_nsdf", n), &samples, |b, samples| {
+            b.iter(|| Mpm::get_nsdf(black_box(samples.clone())))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, square_sums);
+criterion_main!(benches);
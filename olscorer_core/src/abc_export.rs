@@ -0,0 +1,238 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::score::{QuantisationConfig, Score};
+use crate::transcription::Note;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Letter and whether it is sharpened, for each pitch class, where C is 0
+/// and B is 11
+const PITCH_SPELLINGS: [(char, bool); 12] = [
+    ('C', false),
+    ('C', true),
+    ('D', false),
+    ('D', true),
+    ('E', false),
+    ('F', false),
+    ('F', true),
+    ('G', false),
+    ('G', true),
+    ('A', false),
+    ('A', true),
+    ('B', false),
+];
+
+/// Converts the notes to ABC notation in 4/4 time, quantising positions and
+/// durations to semiquavers
+///
+/// # Arguments
+///
+/// * `notes` - Notes to convert, with start positions and durations in samples
+/// * `sample_rate` - Sample rate (in Hz) of the audio the notes were
+///   transcribed from
+/// * `bpm` - Tempo of the audio (in quarter notes per minute)
+pub fn to_abc(notes: &[Note], sample_rate: u32, bpm: f64) -> String {
+    to_abc_with_config(
+        notes,
+        sample_rate,
+        (4, 4),
+        bpm,
+        QuantisationConfig::default(),
+    )
+}
+
+/// Converts the notes to ABC notation, using the given time signature and
+/// quantisation
+///
+/// The notes are quantised and arranged into measures in the same way as
+/// for `musicxml_export::to_musicxml_with_config`. The tune is written in C
+/// major with a unit note length of a quarter note and one measure per line.
+/// Accidentals last until the end of the measure in ABC, so a natural sign
+/// is written on notes which follow a sharpened note of the same pitch. The
+/// tempo is written to the nearest whole BPM, so it must be at least 0.5 BPM.
+pub fn to_abc_with_config(
+    notes: &[Note],
+    sample_rate: u32,
+    time_signature: (u8, u8),
+    bpm: f64,
+    config: QuantisationConfig,
+) -> String {
+    assert!(bpm >= 0.5, "tempo must round to at least 1 BPM");
+    let score = Score::new(notes, sample_rate, time_signature, bpm, &config);
+
+    let mut abc = String::new();
+    abc.push_str("X:1\n");
+    abc.push_str("T:Transcription\n");
+    let _ = writeln!(abc, "M:{}/{}", time_signature.0, time_signature.1);
+    abc.push_str("L:1/4\n");
+    let _ = writeln!(abc, "Q:1/4={}", bpm.round());
+    abc.push_str("K:C\n");
+
+    let num_measures = score.num_measures();
+    let mut events = score.events.iter().peekable();
+
+    for measure in 0..num_measures {
+        let measure_end = (measure + 1) * score.measure_length;
+        let mut words = vec![];
+
+        // Letters and octaves of the notes which have been sharpened so far
+        // in the measure
+        let mut sharpened = HashSet::new();
+
+        while let Some(event) = events.next_if(|e| e.start < measure_end) {
+            let length = if event.note_value <= 4 {
+                match 4 / event.note_value {
+                    1 => String::new(),
+                    quarters => quarters.to_string(),
+                }
+            } else {
+                format!("/{}", event.note_value / 4)
+            };
+
+            let word = match event.name {
+                Some(name) => {
+                    let (letter, is_sharp) = PITCH_SPELLINGS[name.pitch_class() as usize];
+                    let octave = name.octave();
+
+                    let accidental = if is_sharp {
+                        sharpened.insert((letter, octave));
+                        "^"
+                    } else if sharpened.remove(&(letter, octave)) {
+                        "="
+                    } else {
+                        ""
+                    };
+
+                    // Uppercase letters are in octave 4 and lowercase letters
+                    // in octave 5, with marks for each octave beyond them
+                    let pitch = if octave >= 5 {
+                        format!(
+                            "{}{}",
+                            letter.to_ascii_lowercase(),
+                            "'".repeat(octave as usize - 5)
+                        )
+                    } else {
+                        format!("{}{}", letter, ",".repeat(4 - octave as usize))
+                    };
+
+                    let tie = if event.tied_to_next { "-" } else { "" };
+                    format!("{}{}{}{}", accidental, pitch, length, tie)
+                }
+                None => format!("z{}", length),
+            };
+            words.push(word);
+        }
+
+        let barline = if measure + 1 == num_measures {
+            "|]"
+        } else {
+            "|"
+        };
+        let _ = writeln!(abc, "{} {}", words.join(" "), barline);
+    }
+
+    abc
+}
+
+#[cfg(test)]
+mod tests {
+    mod to_abc_tests {
+        use crate::abc_export::{to_abc, to_abc_with_config};
+        use crate::test_utils::note;
+        use crate::transcription::NoteName;
+        use crate::QuantisationConfig;
+
+        #[test]
+        fn simple_melody_matches_expected_source() {
+            // At 120 BPM, a quarter note is 22050 samples at 44100 Hz
+            let notes = [
+                note(NoteName::C(4), 0, 22050),
+                note(NoteName::E(4), 22050, 22050),
+                note(NoteName::G(4), 44100, 44100),
+            ];
+
+            let expected = "X:1
+T:Transcription
+M:4/4
+L:1/4
+Q:1/4=120
+K:C
+C E G2 |]
+";
+
+            assert_eq!(expected, to_abc(&notes, 44100, 120.0));
+        }
+
+        #[test]
+        fn octaves_are_written_with_case_and_marks() {
+            let notes = [
+                note(NoteName::C(3), 0, 22050),
+                note(NoteName::C(4), 22050, 22050),
+                note(NoteName::C(5), 44100, 22050),
+                note(NoteName::C(7), 66150, 22050),
+            ];
+
+            let abc = to_abc(&notes, 44100, 120.0);
+
+            assert!(abc.contains("\nC, C c c'' |]\n"), "{abc}");
+        }
+
+        #[test]
+        fn naturals_cancel_sharps_until_the_barline() {
+            let notes = [
+                note(NoteName::FSharp(4), 0, 22050),
+                note(NoteName::F(4), 22050, 22050),
+                note(NoteName::F(5), 44100, 22050),
+                note(NoteName::FSharp(4), 66150, 22050),
+                note(NoteName::F(4), 88200, 22050),
+            ];
+
+            let abc = to_abc(&notes, 44100, 120.0);
+
+            assert!(abc.contains("\n^F =F f ^F |\nF z2 z |]\n"), "{abc}");
+        }
+
+        #[test]
+        fn rests_ties_and_short_notes_are_written() {
+            // A rest, then a half note starting on the fourth beat, which is
+            // tied across the barline, and a semiquaver
+            let notes = [
+                note(NoteName::A(4), 66150, 44100),
+                note(NoteName::B(4), 110250, 5512),
+            ];
+
+            let abc = to_abc(&notes, 44100, 120.0);
+
+            assert!(abc.contains("\nz2 z A- |\nA B/4 z2 z/2 z/4 |]\n"), "{abc}");
+        }
+
+        #[test]
+        fn time_signature_is_written() {
+            let notes = [note(NoteName::C(4), 0, 22050)];
+
+            let abc =
+                to_abc_with_config(&notes, 44100, (3, 4), 120.0, QuantisationConfig::default());
+
+            assert!(abc.contains("\nM:3/4\n"), "{abc}");
+            assert!(abc.contains("\nC z2 |]\n"), "{abc}");
+        }
+    }
+}
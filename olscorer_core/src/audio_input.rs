@@ -0,0 +1,193 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::audio_utils::Frame;
+use crate::errors::AudioInputError;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SampleFormat, SampleRate, SizedSample, StreamConfig, StreamError};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Samples sent from the input stream callback, or the error which stopped
+/// the stream
+type InputChunk = Result<Vec<f64>, StreamError>;
+
+/// Struct for reading overlapping frames of audio from the default input
+/// device as it is recorded
+///
+/// Consecutive frames overlap by half of the frame width. Multichannel input
+/// is converted to mono by averaging the channels. The stream stops
+/// recording when it is dropped.
+pub struct MicrophoneStream {
+    // The stream is only held so that it keeps recording
+    _stream: cpal::Stream,
+    receiver: Receiver<InputChunk>,
+    frame_width: usize,
+    hop_size: usize,
+    buffer: VecDeque<f64>,
+    next_start: usize,
+}
+
+impl MicrophoneStream {
+    /// Starts recording from the default input device
+    ///
+    /// Returns the stream wrapped in Ok if recording started, and an Error
+    /// otherwise, including an `UnsupportedSampleRate` error if the device
+    /// cannot record at the given sample rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate` - Sample rate (in Hz) to record at
+    /// * `frame_width` - Number of samples each frame should contain
+    pub fn new(sample_rate: u32, frame_width: usize) -> Result<MicrophoneStream, AudioInputError> {
+        assert_ne!(0, frame_width, "frame width must be non-negative");
+
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or(AudioInputError::NoInputDevice)?;
+
+        let supported_config = device
+            .supported_input_configs()?
+            .find(|range| {
+                range.min_sample_rate().0 <= sample_rate && sample_rate <= range.max_sample_rate().0
+            })
+            .ok_or(AudioInputError::UnsupportedSampleRate(sample_rate))?
+            .with_sample_rate(SampleRate(sample_rate));
+
+        let config = supported_config.config();
+        let (sender, receiver) = mpsc::channel();
+
+        let stream = match supported_config.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, sender)?,
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, sender)?,
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, sender)?,
+            sample_format => {
+                return Err(AudioInputError::UnsupportedSampleFormat(
+                    sample_format.to_string(),
+                ))
+            }
+        };
+        stream.play()?;
+
+        Ok(MicrophoneStream {
+            _stream: stream,
+            receiver,
+            frame_width,
+            hop_size: std::cmp::max(frame_width / 2, 1),
+            buffer: VecDeque::with_capacity(frame_width),
+            next_start: 0,
+        })
+    }
+}
+
+impl Iterator for MicrophoneStream {
+    type Item = Frame;
+
+    /// Waits until enough samples have been recorded for the next frame
+    ///
+    /// Returns None once the input stream stops because of an error.
+    fn next(&mut self) -> Option<Frame> {
+        while self.buffer.len() < self.frame_width {
+            match self.receiver.recv() {
+                Ok(Ok(samples)) => self.buffer.extend(samples),
+                Ok(Err(_)) | Err(_) => return None,
+            }
+        }
+
+        let frame = Frame {
+            start_pos: self.next_start,
+            samples: self.buffer.iter().take(self.frame_width).copied().collect(),
+        };
+
+        self.buffer.drain(..self.hop_size);
+        self.next_start += self.hop_size;
+
+        Some(frame)
+    }
+}
+
+/// Builds an input stream which sends each chunk of recorded samples,
+/// converted to mono, through the channel
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    sender: Sender<InputChunk>,
+) -> Result<cpal::Stream, AudioInputError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let num_channels = config.channels as usize;
+    let error_sender = sender.clone();
+
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            // The receiver is only dropped along with the stream
+            let _ = sender.send(Ok(mix_to_mono(data, num_channels)));
+        },
+        move |error| {
+            let _ = error_sender.send(Err(error));
+        },
+        None,
+    )?;
+
+    Ok(stream)
+}
+
+/// Converts interleaved samples with the given number of channels to mono by
+/// averaging the channels
+fn mix_to_mono<T>(data: &[T], num_channels: usize) -> Vec<f64>
+where
+    T: Sample,
+    f32: FromSample<T>,
+{
+    data.chunks_exact(num_channels)
+        .map(|frame| {
+            frame
+                .iter()
+                .map(|sample| sample.to_sample::<f32>() as f64)
+                .sum::<f64>()
+                / num_channels as f64
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    mod mix_to_mono_tests {
+        use crate::audio_input::mix_to_mono;
+
+        #[test]
+        fn mono_samples_are_unchanged() {
+            assert_eq!(vec![0.5, -0.25], mix_to_mono(&[0.5f32, -0.25], 1));
+        }
+
+        #[test]
+        fn channels_are_averaged() {
+            assert_eq!(vec![0.25, 0.0], mix_to_mono(&[0.5f32, 0.0, 0.5, -0.5], 2));
+        }
+
+        #[test]
+        fn integer_samples_are_normalised() {
+            assert_eq!(vec![0.0, -1.0], mix_to_mono(&[0i16, i16::MIN], 1));
+        }
+    }
+}
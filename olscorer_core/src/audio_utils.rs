@@ -19,13 +19,62 @@
  */
 
 use crate::errors::{
+    AudioFileError::{
+        EmptyAudio, MalformedWav, UnsupportedBitDepth, UnsupportedChannelCount, UnsupportedFormat,
+    },
+    FilterError::{CutoffAboveNyquist, InvalidPassBand, InvalidPreEmphasisCoefficient},
     FrameError::{DuplicateFrameIndices, FrameIndexOutOfBounds, FrameIndicesNotSorted},
-    WavFileError::{UnsupportedBitDepth, UnsupportedChannelCount},
+    IncompatibleSampleRateError, ZeroSampleRateError,
 };
-use hound::{SampleFormat, WavReader};
+use crate::onset_detection::OnsetDetector;
+use crate::pitch_detection::{forward_fft, inverse_fft};
+use crate::transcription::{Mode, NoteName, Transcriber, TranscriptionConfig};
+use crate::wavelets::stationary_haar_details;
+use claxon::FlacReader;
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::collections::VecDeque;
 use std::error::Error;
+use std::fs::File;
+use std::io::{Cursor, Read};
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::DecoderOptions,
+    errors::Error as SymphoniaError,
+    formats::FormatOptions,
+    io::{MediaSource, MediaSourceStream},
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+const MAX_24BIT: i32 = 8388607;
+
+/// Format tag of wav files with floating point samples
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// Format tag of wav files whose format is given by the subformat in the
+/// extended part of the fmt chunk
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+/// Seed for the random number generator used for dithering, so that dithered
+/// audio is reproducible
+const DITHER_SEED: u64 = 0x6f6c73636f726572;
+
+/// Number of zero crossings of the sinc function on each side of the
+/// interpolation point used for resampling
+const SINC_HALF_WIDTH: f64 = 16.0;
+
+/// Lowest frequency (in Hz) included in chromagrams, the frequency of A0
+const MIN_CHROMA_FREQUENCY: f64 = 27.5;
+
+const MIN_BPM: f64 = 40.0;
+const MAX_BPM: f64 = 250.0;
 
-const MAX_24BIT: i32 = 16777215;
+/// Standard deviation (in seconds) of the Gaussian which each onset is
+/// smoothed with when estimating the tempo, since onset detectors only find
+/// onsets to within a frame or hop of their true position
+const ONSET_TIMING_DEVIATION: f64 = 0.03;
 
 #[derive(Debug, PartialEq)]
 pub struct Frame {
@@ -35,7 +84,330 @@ pub struct Frame {
     pub samples: Vec<f64>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+impl Frame {
+    /// Finds the sample with the largest absolute value in the frame
+    ///
+    /// Returns the index of the sample within the frame and its value wrapped
+    /// in Some, or None if the frame is empty.
+    pub fn peak_sample(&self) -> Option<(usize, f64)> {
+        self.samples
+            .iter()
+            .copied()
+            .enumerate()
+            .reduce(|peak, sample| {
+                if sample.1.abs() > peak.1.abs() {
+                    sample
+                } else {
+                    peak
+                }
+            })
+    }
+
+    /// Returns the frequency (in Hz) of each bin of the discrete Fourier
+    /// transform of the frame, from 0 Hz up to the Nyquist frequency
+    ///
+    /// The returned vector has `N / 2 + 1` entries, where N is the number of
+    /// samples in the frame, so the Nyquist frequency is at index `N / 2`.
+    /// An empty frame has no frequency bins.
+    pub fn frequency_bins(&self, sample_rate: u32) -> Vec<f64> {
+        let n = self.samples.len();
+
+        if n == 0 {
+            return vec![];
+        }
+
+        (0..=n / 2)
+            .map(|k| k as f64 * sample_rate as f64 / n as f64)
+            .collect()
+    }
+
+    /// Calculates the root mean square of the samples in the frame
+    ///
+    /// Returns the root mean square wrapped in Some if the frame is non-empty,
+    /// otherwise returns None.
+    pub fn rms(&self) -> Option<f64> {
+        AudioData::slice_root_mean_square(&self.samples)
+    }
+
+    /// Returns a copy of the frame with each sample multiplied by the
+    /// corresponding coefficient of the window function
+    pub fn apply_window(&self, window: &WindowFunction) -> Frame {
+        Frame {
+            start_pos: self.start_pos,
+            samples: self
+                .samples
+                .iter()
+                .zip(window.coefficients(self.samples.len()))
+                .map(|(sample, coefficient)| sample * coefficient)
+                .collect(),
+        }
+    }
+}
+
+/// Iterator over the frames of audio data, created by `AudioData::frame_iter`
+pub struct FrameIter<'a> {
+    samples: &'a [f64],
+    frame_width: usize,
+    step_size: usize,
+    next_start: usize,
+    last_start: Option<usize>,
+}
+
+impl Iterator for FrameIter<'_> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        let start = self.next_start;
+
+        if self.last_start.is_none_or(|last_start| start > last_start) {
+            return None;
+        }
+
+        self.next_start = start.saturating_add(self.step_size);
+
+        Some(Frame {
+            start_pos: start,
+            samples: self.samples[start..start + self.frame_width].to_vec(),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = match self.last_start {
+            Some(last_start) if self.next_start <= last_start => {
+                (last_start - self.next_start) / self.step_size + 1
+            }
+            _ => 0,
+        };
+
+        (remaining, Some(remaining))
+    }
+}
+
+/// Iterator over the frames of a wav file which reads the samples of each
+/// frame only when it is reached, created by `AudioData::stream_frames`
+pub struct WavFrameStream {
+    samples: Box<dyn Iterator<Item = Result<f64, Box<dyn Error>>>>,
+    frame_width: usize,
+    hop_size: usize,
+    /// Samples of the previous frame which are also in the next frame
+    buffer: VecDeque<f64>,
+    /// Position in the audio of the first sample in the buffer
+    buffer_start: usize,
+    next_start: usize,
+    finished: bool,
+}
+
+impl Iterator for WavFrameStream {
+    type Item = Result<Frame, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Result<Frame, Box<dyn Error>>> {
+        if self.finished {
+            return None;
+        }
+
+        // Discard the samples before the start of the next frame, which may
+        // not have been read yet if the hop size is larger than the frame
+        while self.buffer_start < self.next_start {
+            if self.buffer.pop_front().is_none() {
+                match self.samples.next() {
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        self.finished = true;
+                        return Some(Err(e));
+                    }
+                    None => {
+                        self.finished = true;
+                        return None;
+                    }
+                }
+            }
+            self.buffer_start += 1;
+        }
+
+        while self.buffer.len() < self.frame_width {
+            match self.samples.next() {
+                Some(Ok(sample)) => self.buffer.push_back(sample),
+                Some(Err(e)) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+                // The final partial frame is not returned
+                None => {
+                    self.finished = true;
+                    return None;
+                }
+            }
+        }
+
+        let frame = Frame {
+            start_pos: self.next_start,
+            samples: self.buffer.iter().copied().collect(),
+        };
+        self.next_start += self.hop_size;
+
+        Some(Ok(frame))
+    }
+}
+
+/// Enum representing the supported audio file formats
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AudioFormat {
+    Wav,
+    Flac,
+    Mp3,
+    Ogg,
+}
+
+impl AudioFormat {
+    /// Returns the audio format with the given (lowercase) file extension,
+    /// or None if the extension is not supported
+    pub fn from_extension(extension: &str) -> Option<AudioFormat> {
+        match extension {
+            "wav" => Some(AudioFormat::Wav),
+            "flac" => Some(AudioFormat::Flac),
+            "mp3" => Some(AudioFormat::Mp3),
+            "ogg" => Some(AudioFormat::Ogg),
+            _ => None,
+        }
+    }
+
+    /// Returns the file extension of the audio format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Ogg => "ogg",
+        }
+    }
+}
+
+/// Enum representing the types of sample which can be written to a wav file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WavSampleFormat {
+    /// Signed integer samples, with 8, 16, 24, or 32 bits
+    Int,
+
+    /// Floating point samples, with 32 bits
+    Float,
+}
+
+/// Enum representing the methods for combining the channels of multichannel
+/// audio into a single channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChannelMixStrategy {
+    /// Only the first (left) channel is used
+    LeftOnly,
+
+    /// All channels are averaged with equal weight
+    Average,
+
+    /// Channels are averaged with weights following the usual conventions
+    /// for mixing surround sound down to mono. For 5.1 and 7.1 audio, in the
+    /// standard wav channel order, the low-frequency effects channel is left
+    /// out and the surround channels are attenuated by 3 dB. Audio with any
+    /// other number of channels is averaged with equal weight.
+    Downmix,
+
+    /// The left and right channels are treated as a mid-side stereo pair,
+    /// and the mid signal, `(L + R) / 2`, is used. Only mono and stereo
+    /// audio is supported.
+    MidSide,
+}
+
+impl ChannelMixStrategy {
+    /// Returns the mix strategy used when none is given, which keeps only the
+    /// left channel of stereo audio and averages audio with more channels
+    fn default_for(num_channels: u16) -> ChannelMixStrategy {
+        if num_channels > 2 {
+            ChannelMixStrategy::Average
+        } else {
+            ChannelMixStrategy::LeftOnly
+        }
+    }
+
+    /// Returns the weight of each channel in the mix, or an error if the
+    /// strategy does not support the number of channels
+    fn channel_weights(&self, num_channels: u16) -> Result<Vec<f64>, Box<dyn Error>> {
+        if num_channels == 0 || (*self == ChannelMixStrategy::MidSide && num_channels > 2) {
+            return Err(Box::new(UnsupportedChannelCount(num_channels)));
+        }
+
+        let num_channels = num_channels as usize;
+
+        let weights = match self {
+            ChannelMixStrategy::LeftOnly => {
+                let mut weights = vec![0.0; num_channels];
+                weights[0] = 1.0;
+                weights
+            }
+            ChannelMixStrategy::Average => vec![1.0; num_channels],
+            ChannelMixStrategy::Downmix => {
+                let surround = std::f64::consts::FRAC_1_SQRT_2;
+                match num_channels {
+                    // L, R, C, LFE, Ls, Rs
+                    6 => vec![1.0, 1.0, 1.0, 0.0, surround, surround],
+                    // L, R, C, LFE, Lb, Rb, Ls, Rs
+                    8 => vec![1.0, 1.0, 1.0, 0.0, surround, surround, surround, surround],
+                    _ => vec![1.0; num_channels],
+                }
+            }
+            // The side signal, (L - R) / 2, is discarded
+            ChannelMixStrategy::MidSide => vec![1.0; num_channels],
+        };
+
+        Ok(weights)
+    }
+}
+
+/// Enum representing the window functions which can be applied to frames
+/// to reduce spectral leakage
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowFunction {
+    /// All samples are weighted equally, which leaves the frame unchanged
+    Rectangular,
+
+    /// Hann window, which tapers to zero at both ends of the frame
+    Hann,
+
+    /// Hamming window, which tapers to 0.08 at both ends of the frame
+    Hamming,
+
+    /// Blackman window, which tapers to zero at both ends of the frame and
+    /// has lower side lobes than the Hann window
+    Blackman,
+}
+
+impl WindowFunction {
+    /// Returns the window coefficients for a frame with the given number of
+    /// samples
+    ///
+    /// The windows are symmetric, so the first and last coefficients are
+    /// equal. A window with a single sample has the coefficient 1.
+    pub fn coefficients(&self, width: usize) -> Vec<f64> {
+        if width == 1 {
+            return vec![1.0];
+        }
+
+        (0..width)
+            .map(|n| {
+                let phase = 2.0 * std::f64::consts::PI * n as f64 / (width - 1) as f64;
+                match self {
+                    WindowFunction::Rectangular => 1.0,
+                    WindowFunction::Hann => 0.5 * (1.0 - phase.cos()),
+                    WindowFunction::Hamming => 0.54 - 0.46 * phase.cos(),
+                    WindowFunction::Blackman => {
+                        0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos()
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AudioData {
     /// Sample rate (in Hz)
     pub sample_rate: u32,
@@ -45,6 +417,101 @@ pub struct AudioData {
 }
 
 impl AudioData {
+    /// Creates audio data from mono samples with the given sample rate
+    ///
+    /// The duration is the number of samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - Audio samples, normally in the range -1.0 to 1.0
+    /// * `sample_rate` - Sample rate (in Hz) of the samples. Must be non-zero.
+    pub fn from_raw_samples(samples: Vec<f64>, sample_rate: u32) -> AudioData {
+        assert_ne!(0, sample_rate, "sample rate must be non-zero");
+
+        AudioData {
+            sample_rate,
+            duration: samples.len() as u32,
+            samples,
+        }
+    }
+
+    /// Returns a new AudioData containing the samples from `start` up to, but
+    /// not including, `end`
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - First sample of the segment
+    /// * `end` - Segment ends at, but does not include, this sample. Must not
+    ///   be less than `start` or greater than the number of samples.
+    pub fn slice(&self, start: usize, end: usize) -> AudioData {
+        assert!(
+            start <= end,
+            "slice start must not be greater than slice end"
+        );
+        assert!(
+            end <= self.samples.len(),
+            "slice end must not be greater than the number of samples"
+        );
+
+        AudioData {
+            sample_rate: self.sample_rate,
+            duration: (end - start) as u32,
+            samples: self.samples[start..end].to_vec(),
+        }
+    }
+
+    /// Returns a new AudioData containing the samples of this audio followed
+    /// by the samples of the other audio
+    ///
+    /// Returns an IncompatibleSampleRateError if the sample rates of the two
+    /// audio segments are different.
+    pub fn concat(&self, other: &AudioData) -> Result<AudioData, IncompatibleSampleRateError> {
+        if self.sample_rate != other.sample_rate {
+            return Err(IncompatibleSampleRateError {
+                expected: self.sample_rate,
+                found: other.sample_rate,
+            });
+        }
+
+        let mut samples = self.samples.clone();
+        samples.extend_from_slice(&other.samples);
+
+        Ok(AudioData {
+            sample_rate: self.sample_rate,
+            duration: self.duration + other.duration,
+            samples,
+        })
+    }
+
+    /// Returns a new AudioData containing the sum of the samples of this audio
+    /// and the other audio, clipped to the range -1.0 to 1.0
+    ///
+    /// The shorter audio is padded with silence, so the mixed audio is as
+    /// long as the longer audio. Returns an IncompatibleSampleRateError if the
+    /// sample rates of the two audio segments are different.
+    pub fn mix(&self, other: &AudioData) -> Result<AudioData, IncompatibleSampleRateError> {
+        if self.sample_rate != other.sample_rate {
+            return Err(IncompatibleSampleRateError {
+                expected: self.sample_rate,
+                found: other.sample_rate,
+            });
+        }
+
+        let length = std::cmp::max(self.samples.len(), other.samples.len());
+        let samples: Vec<f64> = (0..length)
+            .map(|i| {
+                let x = self.samples.get(i).unwrap_or(&0.0) + other.samples.get(i).unwrap_or(&0.0);
+                x.clamp(-1.0, 1.0)
+            })
+            .collect();
+
+        Ok(AudioData {
+            sample_rate: self.sample_rate,
+            duration: samples.len() as u32,
+            samples,
+        })
+    }
+
     /// Returns a vector of audio frames from the samples in the AudioData struct
     ///
     /// # Arguments
@@ -60,25 +527,68 @@ impl AudioData {
         start: Option<usize>,
         end: Option<usize>,
     ) -> Vec<Frame> {
+        self.frame_iter(frame_width, step_size, start, end)
+            .collect()
+    }
+
+    /// Returns an iterator over the same audio frames as `get_frames`, which
+    /// copies the samples of each frame only when it is reached
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_width` - Number of samples each frame should contain
+    /// * `step_size` - Interval between starting position of consecutive frames
+    /// * `start` - (Optional) First frame should start at this sample
+    /// * `end` - (Optional) Final frame should end at, but not include, this sample
+    pub fn frame_iter(
+        &self,
+        frame_width: usize,
+        step_size: usize,
+        start: Option<usize>,
+        end: Option<usize>,
+    ) -> FrameIter<'_> {
         assert_ne!(0, frame_width, "frame width must be non-negative");
+        assert_ne!(0, step_size, "step size must be positive");
 
         let start = start.unwrap_or(0);
         let end = std::cmp::min(end.unwrap_or(self.samples.len()), self.samples.len());
 
-        let mut frames = Vec::new();
-
-        for i in (start..=(end - frame_width)).step_by(step_size) {
-            let mut frame_samples = vec![0.0; frame_width as usize];
-            frame_samples.clone_from_slice(&self.samples[i..i + frame_width]);
-            let frame = Frame {
-                start_pos: i,
-                samples: frame_samples.into_iter().map(|s| s as f64).collect(),
-            };
+        // There are no frames if there is no room for a full frame between the
+        // start and end points
+        let last_start = end
+            .checked_sub(frame_width)
+            .filter(|last_start| *last_start >= start);
 
-            frames.push(frame);
+        FrameIter {
+            samples: &self.samples,
+            frame_width,
+            step_size,
+            next_start: start,
+            last_start,
         }
+    }
 
-        frames
+    /// Returns a vector of audio frames from the samples in the AudioData
+    /// struct, with the window function applied to each frame
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_width` - Number of samples each frame should contain
+    /// * `step_size` - Interval between starting position of consecutive frames
+    /// * `window` - Window function to multiply each frame by
+    /// * `start` - (Optional) First frame should start at this sample
+    /// * `end` - (Optional) Final frame should end at, but not include, this sample
+    pub fn get_windowed_frames(
+        &self,
+        frame_width: usize,
+        step_size: usize,
+        window: WindowFunction,
+        start: Option<usize>,
+        end: Option<usize>,
+    ) -> Vec<Frame> {
+        self.frame_iter(frame_width, step_size, start, end)
+            .map(|frame| frame.apply_window(&window))
+            .collect()
     }
 
     /// Gets the audio frames which start at the given indices
@@ -111,7 +621,7 @@ impl AudioData {
                     return Err(Box::new(DuplicateFrameIndices(indices[i], i, i + 1)));
                 }
 
-                let mut frame_samples = vec![0.0; indices[i + 1] - indices[i] as usize];
+                let mut frame_samples = vec![0.0; indices[i + 1] - indices[i]];
                 frame_samples.clone_from_slice(&self.samples[indices[i]..indices[i + 1]]);
 
                 frames.push(Frame {
@@ -130,484 +640,4460 @@ impl AudioData {
         Ok(frames)
     }
 
-    /// Gets the audio data from a wav file
+    /// Returns an iterator over non-overlapping chunks of `chunk_size`
+    /// samples
     ///
-    /// Returns an AudioData struct wrapped in Ok if there are no errors, and
-    /// an Error otherwise.
-    pub fn read_wav_file(filepath: std::path::PathBuf) -> Result<AudioData, Box<dyn Error>> {
-        let reader = WavReader::open(filepath)?;
+    /// The chunks borrow from the samples in the AudioData struct, so no
+    /// samples are copied. The last chunk is shorter than `chunk_size` if the
+    /// number of samples is not a multiple of `chunk_size`.
+    pub fn chunk_iter(&self, chunk_size: usize) -> impl Iterator<Item = &[f64]> {
+        assert_ne!(0, chunk_size, "chunk size must be non-zero");
 
-        let sample_rate = reader.spec().sample_rate;
-        let duration = reader.duration();
-        let bit_depth = reader.spec().bits_per_sample;
-        let num_channels = reader.spec().channels;
+        self.samples.chunks(chunk_size)
+    }
 
-        // Read samples as floats
-        let samples: Vec<f32> = match reader.spec().sample_format {
-            SampleFormat::Float => reader
-                .into_samples::<f32>()
-                .map(|sample| sample.unwrap())
-                .collect(),
-            SampleFormat::Int => {
-                let max: i32 = match bit_depth {
-                    16 => i16::MAX as i32,
-                    24 => MAX_24BIT,
-                    32 => i32::MAX,
-                    _ => return Err(Box::new(UnsupportedBitDepth(bit_depth))),
-                };
-                reader
-                    .into_samples::<i32>()
-                    .map(|sample| sample.unwrap() as f32 / max as f32)
-                    .collect()
-            }
-        };
+    /// Splits the audio into `n` non-overlapping parts of roughly equal length
+    ///
+    /// The lengths of any two parts differ by at most one sample, with the
+    /// longer parts coming first. If `n` is greater than the number of
+    /// samples, each sample is placed in its own part and the remaining parts
+    /// are empty.
+    pub fn split_into_n_equal_parts(&self, n: usize) -> Vec<AudioData> {
+        assert_ne!(0, n, "number of parts must be non-zero");
 
-        // For stereo audio, only take samples from the left channel
-        // TODO: Implement other methods to combine multiple channels
-        // into one
-        let samples = match num_channels {
-            1 => samples,
-            2 => {
-                let mut left_samples = Vec::with_capacity(samples.len() / 2);
-
-                // Samples are interleaved, samples from the left channel
-                // are at even indices
-                for sample in samples.iter().step_by(2) {
-                    left_samples.push(*sample);
-                }
-                left_samples
-            }
-            _ => return Err(Box::new(UnsupportedChannelCount(num_channels))),
+        let part_length = self.samples.len() / n;
+        let remainder = self.samples.len() % n;
+
+        let mut parts = Vec::with_capacity(n);
+        let mut start = 0;
+
+        for i in 0..n {
+            // The first `remainder` parts each take one of the leftover samples
+            let end = start + part_length + usize::from(i < remainder);
+            let samples = self.samples[start..end].to_vec();
+
+            parts.push(AudioData {
+                sample_rate: self.sample_rate,
+                duration: samples.len() as u32,
+                samples,
+            });
+            start = end;
         }
-        .into_iter()
-        .map(|s| s as f64)
-        .collect();
 
-        let audio_data = AudioData {
-            sample_rate,
-            duration,
-            samples,
-        };
-        Ok(audio_data)
+        parts
     }
 
-    /// Calculates the root mean square of the input samples
+    /// Estimates the tempo of the audio (in beats per minute) from the note
+    /// onsets found by the given detector
     ///
-    /// Returns the root mean square wrapped in Some if the samples vector is
-    /// non-empty, otherwise returns None.
-    pub fn root_mean_square(samples: Vec<f64>) -> Option<f64> {
-        if samples.len() == 0 {
+    /// The inter-onset intervals between every pair of onsets, not only
+    /// consecutive ones, are collected, so that beats on which no note starts
+    /// still contribute. The tempo is taken to be the period between 40 and
+    /// 250 BPM which the most intervals are close to, where each interval is
+    /// weighted by a Gaussian of its distance from the period. This is the
+    /// autocorrelation of the onsets treated as a train of smoothed pulses.
+    ///
+    /// Returns the tempo wrapped in Some, or None if fewer than 4 onsets were
+    /// detected.
+    pub fn detect_bpm_from_onsets(&self, detector: &dyn OnsetDetector) -> Option<f64> {
+        let onsets = detector.get_onsets(&Transcriber::scale_samples(self));
+
+        if onsets.len() < 4 {
             return None;
         }
-        Some((samples.iter().map(|x| x * x).sum::<f64>() / samples.len() as f64).sqrt())
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    /// Tests for wav file reader
-    mod read_wav_file_tests {
-        use crate::audio_utils::AudioData;
+        let onset_times: Vec<f64> = onsets
+            .iter()
+            .map(|onset| onset.sample as f64 / self.sample_rate as f64)
+            .collect();
 
-        #[test]
-        fn sample_rate_read_correctly() {
-            let mut filepath = std::path::PathBuf::new();
-            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+        // Only the intervals between pairs of onsets which are at most one
+        // beat apart at the slowest tempo contribute to the autocorrelation
+        let max_period = 60.0 / MIN_BPM;
+        let mut intervals = vec![];
 
-            let audio_data =
-                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
-            assert_eq!(44100, audio_data.sample_rate);
+        for (i, start) in onset_times.iter().enumerate() {
+            for end in onset_times[i + 1..].iter() {
+                if end - start > max_period + 0.1 {
+                    break;
+                }
+                intervals.push(end - start);
+            }
         }
 
-        #[test]
-        fn duration_read_correctly() {
-            let mut filepath = std::path::PathBuf::new();
-            filepath.push("../resources/test/sine_660Hz_22050samples_s32bit_44100Hz_mono.wav");
-
-            let audio_data =
-                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
-            assert_eq!(22050, audio_data.duration);
-        }
+        let sigma = ONSET_TIMING_DEVIATION;
 
-        #[test]
-        fn reading_stereo_audio_works_correctly() {
-            let mut filepath = std::path::PathBuf::new();
-            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_stereo.wav");
+        // Evaluate the autocorrelation for tempos at 0.1 BPM intervals
+        let num_candidates = ((MAX_BPM - MIN_BPM) * 10.0) as usize + 1;
 
-            let audio_data =
-                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
-            assert_eq!(44100, audio_data.sample_rate);
-            assert_eq!(44100, audio_data.duration);
-        }
+        (0..num_candidates)
+            .map(|i| MIN_BPM + i as f64 / 10.0)
+            .map(|bpm| {
+                let period = 60.0 / bpm;
+                let autoc: f64 = intervals
+                    .iter()
+                    .map(|interval| (-(interval - period).powi(2) / (2.0 * sigma * sigma)).exp())
+                    .sum();
+                (bpm, autoc)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(bpm, _)| bpm)
+    }
 
-        #[test]
-        fn different_bit_depths_read_correctly() {
-            let mut filepath_signed_16 = std::path::PathBuf::new();
-            filepath_signed_16
-                .push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+    /// Estimates the key of the music in the audio
+    ///
+    /// The audio is transcribed using the given parameters, and the total
+    /// duration of the detected notes in each pitch class is compared with
+    /// the major and minor key profiles using the Krumhansl-Schmuckler
+    /// algorithm. The octave of the returned tonic is always 0. Returns None
+    /// if no notes are detected.
+    pub fn detect_key(&self, config: &TranscriptionConfig) -> Option<(NoteName, Mode)> {
+        Transcriber::estimate_key(&Transcriber::pitch_class_histogram(self, config))
+    }
 
-            let audio_data_signed_16 =
+    /// Calculates the chromagram of the audio, the energy in each of the 12
+    /// pitch classes over time
+    ///
+    /// The short-time Fourier transform is calculated using Hann windowed
+    /// frames, and the energy of each frequency bin is added to the pitch
+    /// class nearest to the bin's frequency in equal temperament with A4 at
+    /// 440 Hz. Bins below 27.5 Hz (A0) are ignored. Each frame of the
+    /// chromagram is normalised to unit L2 norm, except for silent frames,
+    /// which are all zeros. Bin 0 is C and bin 11 is B.
+    ///
+    /// # Arguments
+    ///
+    /// * `hop_size` - Interval (in samples) between the starts of consecutive
+    ///   frames
+    /// * `n_fft` - Number of samples in each frame
+    pub fn to_chromagram(&self, hop_size: usize, n_fft: usize) -> Vec<[f64; 12]> {
+        let window = WindowFunction::Hann.coefficients(n_fft);
+
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(n_fft);
+
+        self.get_frames(n_fft, hop_size, None, None)
+            .into_iter()
+            .map(|frame| {
+                let mut spectrum: Vec<Complex<f64>> = frame
+                    .samples
+                    .iter()
+                    .zip(&window)
+                    .map(|(x, w)| Complex { re: x * w, im: 0.0 })
+                    .collect();
+                fft.process(&mut spectrum);
+
+                let mut chroma = [0.0; 12];
+
+                for (bin, frequency) in frame.frequency_bins(self.sample_rate).iter().enumerate() {
+                    if *frequency < MIN_CHROMA_FREQUENCY {
+                        continue;
+                    }
+
+                    // A is pitch class 9
+                    let semitones_from_a = (12.0 * (frequency / 440.0).log2()).round() as i64;
+                    let pitch_class = (semitones_from_a + 9).rem_euclid(12) as usize;
+
+                    chroma[pitch_class] += spectrum[bin].norm_sqr();
+                }
+
+                let norm = chroma.iter().map(|x| x * x).sum::<f64>().sqrt();
+                if norm > 0.0 {
+                    chroma.iter_mut().for_each(|x| *x /= norm);
+                }
+
+                chroma
+            })
+            .collect()
+    }
+
+    /// Calculates the zero-crossing rate (in crossings per second) for each
+    /// one-second window of the audio
+    ///
+    /// A zero crossing is counted at each sample whose sign differs from the
+    /// sign of the preceding sample. The final window may be shorter than one
+    /// second, in which case its rate is calculated from the available
+    /// samples only.
+    pub fn zero_crossing_rate_per_second(&self) -> Vec<f64> {
+        assert_ne!(0, self.sample_rate, "sample rate must be non-zero");
+
+        let window_width = self.sample_rate as usize;
+
+        (0..self.samples.len())
+            .step_by(window_width)
+            .map(|start| {
+                let end = std::cmp::min(start + window_width, self.samples.len());
+                // Include the preceding sample, so that a sign change between
+                // two windows is counted in the second window
+                let crossings =
+                    AudioData::count_zero_crossings(&self.samples[start.saturating_sub(1)..end]);
+
+                crossings as f64 * self.sample_rate as f64 / (end - start) as f64
+            })
+            .collect()
+    }
+
+    /// Calculates the phase difference (in degrees) between the left and
+    /// right channels of a stereo recording for each frequency bin
+    ///
+    /// The phase difference is taken from the cross-spectrum of the two
+    /// channels, and is in the range (-180, 180]. One value is returned for
+    /// each bin from DC up to and including the Nyquist frequency, so the
+    /// bin at index `k` corresponds to `k * sample_rate / left.len()` Hz.
+    ///
+    /// A bin in which only the left channel has energy has a phase
+    /// difference of 0, and a bin in which only the right channel has energy
+    /// has a phase difference of 180, so a sound panned hard left is at 0
+    /// degrees and a sound panned hard right is at 180 degrees. Bins in which
+    /// neither channel has energy have a phase difference of 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Samples of the left channel
+    /// * `right` - Samples of the right channel
+    /// * `sample_rate` - Sample rate (in Hz) of both channels
+    pub fn channel_phase_difference(left: &[f64], right: &[f64], sample_rate: u32) -> Vec<f64> {
+        assert_ne!(0, sample_rate, "sample rate must be non-zero");
+        assert_eq!(
+            left.len(),
+            right.len(),
+            "left and right channels must have the same length"
+        );
+
+        if left.is_empty() {
+            return vec![];
+        }
+
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(left.len());
+
+        let mut left_spectrum: Vec<Complex<f64>> =
+            left.iter().map(|x| Complex { re: *x, im: 0.0 }).collect();
+        let mut right_spectrum: Vec<Complex<f64>> =
+            right.iter().map(|x| Complex { re: *x, im: 0.0 }).collect();
+
+        fft.process(&mut left_spectrum);
+        fft.process(&mut right_spectrum);
+
+        left_spectrum
+            .iter()
+            .zip(right_spectrum.iter())
+            .take(left.len() / 2 + 1)
+            .map(|(l, r)| {
+                if r.norm() == 0.0 {
+                    return 0.0;
+                }
+                if l.norm() == 0.0 {
+                    return 180.0;
+                }
+
+                let phase = (l * r.conj()).arg().to_degrees();
+                // Map -180 degrees to 180 degrees so that opposite phases are
+                // always reported the same way
+                if phase <= -180.0 {
+                    phase + 360.0
+                } else {
+                    phase
+                }
+            })
+            .collect()
+    }
+
+    /// Calculates the discrete Fourier transform of the samples
+    ///
+    /// The samples are zero-padded to the next power of two before the
+    /// transform is calculated, and the full complex spectrum (including the
+    /// bins above the Nyquist frequency) is returned.
+    pub fn to_frequency_domain(&self) -> Vec<Complex<f64>> {
+        let fft_length = self.samples.len().next_power_of_two();
+
+        let mut spectrum: Vec<Complex<f64>> = self
+            .samples
+            .iter()
+            .map(|x| Complex { re: *x, im: 0.0 })
+            .collect();
+        spectrum.resize(fft_length, Complex { re: 0.0, im: 0.0 });
+
+        let mut planner = FftPlanner::new();
+        planner.plan_fft_forward(fft_length).process(&mut spectrum);
+
+        spectrum
+    }
+
+    /// Creates audio data from a full complex spectrum using the inverse
+    /// discrete Fourier transform
+    ///
+    /// This is the inverse of `to_frequency_domain`. The returned audio has
+    /// one sample per frequency bin, so audio which was zero-padded by
+    /// `to_frequency_domain` keeps its padding. The imaginary parts of the
+    /// inverse transform are discarded.
+    pub fn from_frequency_domain(spectrum: Vec<Complex<f64>>, sample_rate: u32) -> AudioData {
+        let fft_length = spectrum.len();
+        let mut buffer = spectrum;
+
+        let mut planner = FftPlanner::new();
+        planner.plan_fft_inverse(fft_length).process(&mut buffer);
+
+        let samples: Vec<f64> = buffer.iter().map(|x| x.re / fft_length as f64).collect();
+
+        AudioData {
+            sample_rate,
+            duration: samples.len() as u32,
+            samples,
+        }
+    }
+
+    /// Finds the positions of sharp transients, such as the attacks of
+    /// percussive notes, using a multi-level Haar wavelet transform
+    ///
+    /// The detail coefficients at each level of the transform measure how
+    /// abruptly the mean amplitude changes over blocks of `2^(level - 1)`
+    /// samples. Every sample where the magnitude of a detail coefficient at
+    /// any level exceeds `threshold` is a transient candidate. Candidates
+    /// closer together than the widest wavelet are grouped, and the position
+    /// of the strongest candidate in each group is returned.
+    ///
+    /// Steady tones whose period is shorter than `2^decomposition_levels`
+    /// samples can also produce large coefficients, so the number of levels
+    /// should be kept small when the audio contains low-pitched notes.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - Minimum magnitude of a detail coefficient for a
+    ///   transient to be detected
+    /// * `decomposition_levels` - Number of levels of the wavelet transform
+    pub fn detect_transients(&self, threshold: f64, decomposition_levels: u8) -> Vec<usize> {
+        assert!(
+            (1..=16).contains(&decomposition_levels),
+            "number of decomposition levels must be between 1 and 16"
+        );
+
+        let details = stationary_haar_details(&self.samples, decomposition_levels);
+
+        // The transient strength at each sample is the largest detail
+        // coefficient magnitude across all levels
+        let strengths: Vec<f64> = (0..self.samples.len())
+            .map(|n| {
+                details
+                    .iter()
+                    .fold(0.0, |max: f64, level| max.max(level[n].abs()))
+            })
+            .collect();
+
+        let group_width = 1usize << decomposition_levels;
+
+        let mut transients = vec![];
+        // Position and strength of the strongest candidate in the current group
+        let mut strongest: Option<(usize, f64)> = None;
+        let mut last_candidate = 0;
+
+        for (n, strength) in strengths.into_iter().enumerate() {
+            if strength <= threshold {
+                continue;
+            }
+
+            strongest = match strongest {
+                Some(candidate) if n - last_candidate < group_width => {
+                    if strength > candidate.1 {
+                        Some((n, strength))
+                    } else {
+                        Some(candidate)
+                    }
+                }
+                Some(candidate) => {
+                    transients.push(candidate.0);
+                    Some((n, strength))
+                }
+                None => Some((n, strength)),
+            };
+            last_candidate = n;
+        }
+
+        if let Some(candidate) = strongest {
+            transients.push(candidate.0);
+        }
+
+        transients
+    }
+
+    /// Adds triangular probability density function (TPDF) dither noise to
+    /// the samples
+    ///
+    /// Dithering should be applied before the samples are quantised to
+    /// integers with the given bit depth, to avoid distortion caused by
+    /// quantisation error. The noise is the sum of two independent uniformly
+    /// distributed values, each spanning one least significant bit (LSB) at
+    /// the given bit depth, where one LSB is `1 / 2^(bit_depth - 1)`. The
+    /// noise is generated with a fixed seed, so dithering the same audio
+    /// always gives the same result.
+    pub fn dither(&self, bit_depth: u16) -> AudioData {
+        assert!(
+            (1..=32).contains(&bit_depth),
+            "bit depth must be between 1 and 32"
+        );
+
+        let half_lsb = 0.5 / 2.0f64.powi(bit_depth as i32 - 1);
+        let mut rng = StdRng::seed_from_u64(DITHER_SEED);
+
+        let samples = self
+            .samples
+            .iter()
+            .map(|x| x + rng.gen_range(-half_lsb..half_lsb) + rng.gen_range(-half_lsb..half_lsb))
+            .collect();
+
+        AudioData {
+            sample_rate: self.sample_rate,
+            duration: self.duration,
+            samples,
+        }
+    }
+
+    /// Applies a first-order pre-emphasis filter to the samples, which boosts
+    /// high frequencies relative to low frequencies
+    ///
+    /// The filter is `y[n] = x[n] - alpha * x[n - 1]`, where the sample
+    /// before the first sample is taken to be zero. Returns the filtered
+    /// audio wrapped in Ok, or an Error if `alpha` is not in the range
+    /// 0.0 (inclusive) to 1.0 (exclusive).
+    ///
+    /// # Arguments
+    ///
+    /// * `alpha` - Filter coefficient, typically 0.97
+    pub fn apply_pre_emphasis(&self, alpha: f64) -> Result<AudioData, Box<dyn Error>> {
+        if !(0.0..1.0).contains(&alpha) {
+            return Err(Box::new(InvalidPreEmphasisCoefficient(alpha)));
+        }
+
+        let mut samples = self.samples.clone();
+        for i in (1..samples.len()).rev() {
+            samples[i] -= alpha * samples[i - 1];
+        }
+
+        Ok(AudioData {
+            sample_rate: self.sample_rate,
+            duration: self.duration,
+            samples,
+        })
+    }
+
+    /// Removes the frequencies outside the given range from the audio
+    ///
+    /// The filter sets the frequency bins of the discrete Fourier transform of
+    /// the whole audio which are outside the range to zero, then takes the
+    /// inverse transform. Returns the filtered audio wrapped in Ok, or an Error
+    /// if the range is empty or negative, or if `high_hz` is above the Nyquist
+    /// frequency.
+    ///
+    /// # Arguments
+    ///
+    /// * `low_hz` - Lowest frequency (in Hz) to keep
+    /// * `high_hz` - Highest frequency (in Hz) to keep
+    pub fn band_pass_filter(&self, low_hz: f64, high_hz: f64) -> Result<AudioData, Box<dyn Error>> {
+        if !(low_hz >= 0.0 && low_hz < high_hz) {
+            return Err(Box::new(InvalidPassBand(low_hz, high_hz)));
+        }
+
+        let nyquist = self.sample_rate as f64 / 2.0;
+        if high_hz > nyquist {
+            return Err(Box::new(CutoffAboveNyquist(high_hz, nyquist)));
+        }
+
+        // The transform is not zero-padded, so that the frequencies of audio
+        // with a whole number of periods fall exactly on bins
+        let fft_length = self.samples.len();
+        let mut spectrum = forward_fft(&self.samples, fft_length);
+
+        for (k, bin) in spectrum.iter_mut().enumerate() {
+            // Bins above the Nyquist frequency are the negative frequencies
+            let frequency = std::cmp::min(k, fft_length - k) as f64 * self.sample_rate as f64
+                / fft_length as f64;
+            if frequency < low_hz || frequency > high_hz {
+                *bin = Complex { re: 0.0, im: 0.0 };
+            }
+        }
+
+        let samples = if fft_length == 0 {
+            vec![]
+        } else {
+            inverse_fft(spectrum)
+        };
+
+        Ok(AudioData {
+            sample_rate: self.sample_rate,
+            duration: self.duration,
+            samples,
+        })
+    }
+
+    /// Removes any DC offset from the samples by subtracting their mean, so
+    /// that the waveform is centred around zero
+    ///
+    /// If the magnitude of the mean is less than `f64::EPSILON`, the samples
+    /// are returned unchanged.
+    pub fn remove_dc_offset(&self) -> AudioData {
+        let mean = if self.samples.is_empty() {
+            0.0
+        } else {
+            self.samples.iter().sum::<f64>() / self.samples.len() as f64
+        };
+
+        let samples = if mean.abs() < f64::EPSILON {
+            self.samples.clone()
+        } else {
+            self.samples.iter().map(|x| x - mean).collect()
+        };
+
+        AudioData {
+            sample_rate: self.sample_rate,
+            duration: self.duration,
+            samples,
+        }
+    }
+
+    /// Scales the amplitude of the samples by the given gain (in decibels)
+    ///
+    /// The samples are multiplied by `10^(gain_db / 20)`, so a gain of 0 dB
+    /// leaves them unchanged and a gain of negative infinity silences them.
+    ///
+    /// # Arguments
+    ///
+    /// * `gain_db` - Gain (in dB) to apply to the samples
+    /// * `clip` - Whether to clip the scaled samples to the range -1.0 to 1.0
+    pub fn apply_gain(&self, gain_db: f64, clip: bool) -> AudioData {
+        let gain = 10.0f64.powf(gain_db / 20.0);
+
+        let samples = self
+            .samples
+            .iter()
+            .map(|x| {
+                let scaled = x * gain;
+                if clip {
+                    scaled.clamp(-1.0, 1.0)
+                } else {
+                    scaled
+                }
+            })
+            .collect();
+
+        AudioData {
+            sample_rate: self.sample_rate,
+            duration: self.duration,
+            samples,
+        }
+    }
+
+    /// Scales the amplitude of the samples so that the sample with the
+    /// largest absolute value is exactly 1.0 or -1.0
+    ///
+    /// Silent audio is returned unchanged.
+    pub fn peak_normalization(&self) -> AudioData {
+        let peak = self
+            .samples
+            .iter()
+            .fold(0.0, |max: f64, x| max.max(x.abs()));
+
+        if peak == 0.0 {
+            return self.clone();
+        }
+
+        AudioData {
+            sample_rate: self.sample_rate,
+            duration: self.duration,
+            samples: self.samples.iter().map(|x| x / peak).collect(),
+        }
+    }
+
+    /// Applies a linear fade in to the start of the audio
+    ///
+    /// Sample `i` of the first `duration_samples` samples is multiplied by
+    /// `i / duration_samples`, so the first sample becomes zero. Panics if
+    /// `duration_samples` is greater than the number of samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration_samples` - Number of samples the fade lasts for
+    pub fn fade_in(&self, duration_samples: usize) -> AudioData {
+        assert!(
+            duration_samples <= self.samples.len(),
+            "fade duration must not exceed the number of samples"
+        );
+
+        let mut samples = self.samples.clone();
+        for (i, sample) in samples.iter_mut().take(duration_samples).enumerate() {
+            *sample *= i as f64 / duration_samples as f64;
+        }
+
+        AudioData {
+            sample_rate: self.sample_rate,
+            duration: self.duration,
+            samples,
+        }
+    }
+
+    /// Applies a linear fade out to the end of the audio
+    ///
+    /// The fade is the reverse of the fade applied by `fade_in`, so the last
+    /// sample becomes zero. Panics if `duration_samples` is greater than the
+    /// number of samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration_samples` - Number of samples the fade lasts for
+    pub fn fade_out(&self, duration_samples: usize) -> AudioData {
+        assert!(
+            duration_samples <= self.samples.len(),
+            "fade duration must not exceed the number of samples"
+        );
+
+        let mut samples = self.samples.clone();
+        for (i, sample) in samples.iter_mut().rev().take(duration_samples).enumerate() {
+            *sample *= i as f64 / duration_samples as f64;
+        }
+
+        AudioData {
+            sample_rate: self.sample_rate,
+            duration: self.duration,
+            samples,
+        }
+    }
+
+    /// Converts the samples to 32-bit floating point PCM samples
+    ///
+    /// Samples outside the range -1.0 to 1.0 are clipped.
+    pub fn to_pcm_f32(&self) -> Vec<f32> {
+        self.samples
+            .iter()
+            .map(|x| x.clamp(-1.0, 1.0) as f32)
+            .collect()
+    }
+
+    /// Converts the samples to 16-bit integer PCM samples
+    ///
+    /// Samples are scaled so that 1.0 maps to `i16::MAX`, the same scale used
+    /// when reading 16-bit wav files, and rounded to the nearest integer.
+    /// Samples outside the range -1.0 to 1.0 are clipped.
+    pub fn to_pcm_i16(&self) -> Vec<i16> {
+        self.samples
+            .iter()
+            .map(|x| (x.clamp(-1.0, 1.0) * i16::MAX as f64).round() as i16)
+            .collect()
+    }
+
+    /// Returns the audio data at the same sample rate as `other`
+    ///
+    /// The audio is resampled with `resample`, which uses windowed sinc
+    /// interpolation. This is used by `cross_correlation`, but not by `mix`,
+    /// which returns an error for audio at different sample rates instead.
+    pub fn resample_to_match(&self, other: &AudioData) -> AudioData {
+        self.resample(other.sample_rate)
+    }
+
+    /// Calculates the cross-correlation of the audio with the other audio
+    /// for each lag by which the other audio can be delayed within this audio
+    ///
+    /// The other audio is first brought to the sample rate of this audio with
+    /// `resample_to_match`. The value at lag `k` is the sum of
+    /// `self.samples[n + k] * other.samples[n]` over all `n`, so the lag of
+    /// the largest value is the position (in samples) at which the other
+    /// audio best lines up with this audio. Returns one value for each sample
+    /// of this audio.
+    pub fn cross_correlation(&self, other: &AudioData) -> Vec<f64> {
+        let other = other.resample_to_match(self);
+        let num_lags = self.samples.len();
+
+        if num_lags == 0 || other.samples.is_empty() {
+            return vec![0.0; num_lags];
+        }
+
+        // Zero-pad the samples to avoid circular correlation
+        let fft_length = (self.samples.len() + other.samples.len()).next_power_of_two();
+
+        let spectrum: Vec<Complex<f64>> = forward_fft(&self.samples, fft_length)
+            .iter()
+            .zip(forward_fft(&other.samples, fft_length))
+            .map(|(x, y)| x * y.conj())
+            .collect();
+
+        let mut correlation = inverse_fft(spectrum);
+        correlation.truncate(num_lags);
+        correlation
+    }
+
+    /// Converts the audio to the target sample rate using windowed sinc
+    /// interpolation
+    ///
+    /// The duration of the returned audio data is its number of samples at
+    /// the new sample rate. If the audio is already at the target sample
+    /// rate, a copy of the audio data is returned without resampling.
+    pub fn resample(&self, target_sample_rate: u32) -> AudioData {
+        if self.sample_rate == target_sample_rate {
+            self.clone()
+        } else {
+            self.resample_sinc(target_sample_rate)
+        }
+    }
+
+    /// Resamples the audio to the target sample rate using sinc
+    /// interpolation with a Hann window
+    ///
+    /// When downsampling, the cutoff frequency of the interpolation filter is
+    /// lowered to the new Nyquist frequency to prevent aliasing.
+    fn resample_sinc(&self, target_sample_rate: u32) -> AudioData {
+        assert_ne!(target_sample_rate, 0, "sample rate must be non-zero");
+
+        let ratio = target_sample_rate as f64 / self.sample_rate as f64;
+        let cutoff = ratio.min(1.0);
+        let half_width = SINC_HALF_WIDTH / cutoff;
+        let num_samples = (self.samples.len() as f64 * ratio).round() as usize;
+
+        let samples: Vec<f64> = (0..num_samples)
+            .map(|i| {
+                // Position of the output sample in the input samples
+                let t = i as f64 / ratio;
+                let first = (t - half_width).ceil().max(0.0) as usize;
+                let last = ((t + half_width).floor() as usize).min(self.samples.len() - 1);
+
+                (first..=last)
+                    .map(|j| {
+                        let x = (t - j as f64) * cutoff;
+                        let sinc = if x == 0.0 {
+                            1.0
+                        } else {
+                            (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x)
+                        };
+                        let window =
+                            0.5 * (1.0 + (std::f64::consts::PI * x / SINC_HALF_WIDTH).cos());
+
+                        self.samples[j] * cutoff * sinc * window
+                    })
+                    .sum()
+            })
+            .collect();
+
+        AudioData {
+            sample_rate: target_sample_rate,
+            duration: samples.len() as u32,
+            samples,
+        }
+    }
+
+    /// Gets the audio data from a wav file
+    ///
+    /// Integer samples with 8, 16, 24, or 32 bits and float samples with 32 or
+    /// 64 bits are supported. For stereo audio, only the samples from the left
+    /// channel are used.
+    /// Audio with more than two channels is converted to mono by averaging
+    /// all channels.
+    ///
+    /// Returns an AudioData struct wrapped in Ok if there are no errors, and
+    /// an Error otherwise, including an `EmptyAudio` error if the file
+    /// contains no samples.
+    pub fn read_wav_file(filepath: std::path::PathBuf) -> Result<AudioData, Box<dyn Error>> {
+        AudioData::decode_wav_bytes(&std::fs::read(filepath)?, None)
+    }
+
+    /// Gets the audio data from a wav file, converting multichannel audio to
+    /// mono using the given strategy
+    ///
+    /// Returns an AudioData struct wrapped in Ok if there are no errors, and
+    /// an Error otherwise.
+    pub fn read_wav_file_with_options(
+        filepath: std::path::PathBuf,
+        mix_strategy: ChannelMixStrategy,
+    ) -> Result<AudioData, Box<dyn Error>> {
+        AudioData::decode_wav_bytes(&std::fs::read(filepath)?, Some(mix_strategy))
+    }
+
+    /// Writes the audio to a mono wav file with integer samples
+    ///
+    /// The samples are dithered before they are quantised, and samples
+    /// outside the range -1.0 to 1.0 are clipped. Returns an
+    /// `UnsupportedBitDepth` error if the bit depth is not 8, 16, 24, or 32.
+    ///
+    /// # Arguments
+    ///
+    /// * `filepath` - Path of the wav file to create
+    /// * `bit_depth` - Number of bits in each sample
+    pub fn write_wav_file(
+        &self,
+        filepath: std::path::PathBuf,
+        bit_depth: u16,
+    ) -> Result<(), Box<dyn Error>> {
+        self.write_wav_file_with_options(filepath, bit_depth, WavSampleFormat::Int)
+    }
+
+    /// Writes the audio to a mono wav file with samples of the given format
+    ///
+    /// Integer samples are dithered with `dither` and then scaled in the same
+    /// way as when reading a wav file, so writing and then reading the audio
+    /// gives the same samples to within two least significant bits at the bit
+    /// depth. Integer samples outside the range -1.0 to 1.0 are clipped.
+    /// Returns an `UnsupportedBitDepth` error if the bit depth is not 8, 16,
+    /// 24, or 32 for integer samples, or 32 for float samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `filepath` - Path of the wav file to create
+    /// * `bit_depth` - Number of bits in each sample
+    /// * `sample_format` - Whether the samples are integers or floats
+    pub fn write_wav_file_with_options(
+        &self,
+        filepath: std::path::PathBuf,
+        bit_depth: u16,
+        sample_format: WavSampleFormat,
+    ) -> Result<(), Box<dyn Error>> {
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: self.sample_rate,
+            bits_per_sample: bit_depth,
+            sample_format: match sample_format {
+                WavSampleFormat::Int => SampleFormat::Int,
+                WavSampleFormat::Float => SampleFormat::Float,
+            },
+        };
+
+        match sample_format {
+            WavSampleFormat::Int => {
+                let max = AudioData::max_int_sample(bit_depth)? as f64;
+                // Range of the signed integers with the given bit depth
+                let lowest = -(2.0f64.powi(bit_depth as i32 - 1));
+                let highest = -lowest - 1.0;
+
+                let mut writer = WavWriter::create(filepath, spec)?;
+                for sample in &self.dither(bit_depth).samples {
+                    let value = (sample.clamp(-1.0, 1.0) * max)
+                        .round()
+                        .clamp(lowest, highest);
+                    writer.write_sample(value as i32)?;
+                }
+                writer.finalize()?;
+            }
+            WavSampleFormat::Float => {
+                if bit_depth != 32 {
+                    return Err(Box::new(UnsupportedBitDepth(bit_depth)));
+                }
+
+                let mut writer = WavWriter::create(filepath, spec)?;
+                for sample in &self.samples {
+                    writer.write_sample(*sample as f32)?;
+                }
+                writer.finalize()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the frames of a wav file one at a time, without reading the
+    /// whole file into memory
+    ///
+    /// The frames are the same as those given by `get_frames` on the audio
+    /// data from `read_wav_file`, but only the samples of the current frame
+    /// are held in memory. Wav files with 64-bit float samples cannot be
+    /// streamed.
+    ///
+    /// Returns the iterator wrapped in Ok if the file can be opened, and an
+    /// Error otherwise. Errors reading the samples are returned by the
+    /// iterator, after which it ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `filepath` - Path of the wav file to read
+    /// * `frame_width` - Number of samples each frame should contain
+    /// * `hop_size` - Interval between starting position of consecutive frames
+    pub fn stream_frames(
+        filepath: std::path::PathBuf,
+        frame_width: usize,
+        hop_size: usize,
+    ) -> Result<WavFrameStream, Box<dyn Error>> {
+        assert_ne!(0, frame_width, "frame width must be non-negative");
+        assert_ne!(0, hop_size, "hop size must be positive");
+
+        let reader = WavReader::open(filepath)?;
+        let spec = reader.spec();
+
+        let weights =
+            ChannelMixStrategy::default_for(spec.channels).channel_weights(spec.channels)?;
+
+        // Samples are read as floats in the same way as in `decode_wav`
+        let interleaved: Box<dyn Iterator<Item = hound::Result<f32>>> = match spec.sample_format {
+            SampleFormat::Float => Box::new(reader.into_samples::<f32>()),
+            SampleFormat::Int => {
+                let max = AudioData::max_int_sample(spec.bits_per_sample)?;
+                Box::new(
+                    reader
+                        .into_samples::<i32>()
+                        .map(move |sample| sample.map(|x| x as f32 / max as f32)),
+                )
+            }
+        };
+
+        Ok(WavFrameStream {
+            samples: AudioData::mix_stream_to_mono(interleaved, weights),
+            frame_width,
+            hop_size,
+            buffer: VecDeque::with_capacity(frame_width),
+            buffer_start: 0,
+            next_start: 0,
+            finished: false,
+        })
+    }
+
+    /// Converts a stream of interleaved samples to mono using the given
+    /// channel weights, in the same way as `mix_to_mono`
+    fn mix_stream_to_mono(
+        mut interleaved: Box<dyn Iterator<Item = hound::Result<f32>>>,
+        weights: Vec<f64>,
+    ) -> Box<dyn Iterator<Item = Result<f64, Box<dyn Error>>>> {
+        let total_weight: f64 = weights.iter().sum();
+
+        Box::new(std::iter::from_fn(move || {
+            let mut sum = 0.0;
+            for weight in &weights {
+                match interleaved.next()? {
+                    Ok(sample) => sum += sample as f64 * weight,
+                    Err(e) => return Some(Err(e.into())),
+                }
+            }
+            Some(Ok(sum / total_weight))
+        }))
+    }
+
+    /// Gets the audio data from a flac file
+    ///
+    /// Samples are normalised using the bit depth from the stream info, in
+    /// the same way as the samples of integer wav files. For stereo audio,
+    /// only the samples from the left channel are used.
+    ///
+    /// Returns an AudioData struct wrapped in Ok if there are no errors, and
+    /// an Error otherwise.
+    pub fn read_flac_file(filepath: std::path::PathBuf) -> Result<AudioData, Box<dyn Error>> {
+        AudioData::decode_flac(FlacReader::open(filepath)?)
+    }
+
+    /// Gets the audio data from an audio file, choosing the decoder based on
+    /// the file extension
+    ///
+    /// Wav files are read with `read_wav_file` and flac files are read with
+    /// `read_flac_file`. Mp3 and ogg files are decoded and converted to mono
+    /// in the same way as wav files.
+    ///
+    /// Returns an AudioData struct wrapped in Ok if there are no errors, and
+    /// an Error otherwise, including for unsupported file extensions.
+    pub fn read_audio_file(filepath: std::path::PathBuf) -> Result<AudioData, Box<dyn Error>> {
+        let extension = filepath
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match AudioFormat::from_extension(&extension) {
+            Some(AudioFormat::Wav) => AudioData::read_wav_file(filepath),
+            Some(AudioFormat::Flac) => AudioData::read_flac_file(filepath),
+            Some(format) => {
+                AudioData::decode_compressed(Box::new(File::open(filepath)?), format.extension())
+            }
+            None => Err(Box::new(UnsupportedFormat(extension))),
+        }
+    }
+
+    /// Gets the audio data from the contents of an audio file held in memory
+    ///
+    /// The audio is decoded in the same way as when reading a file of the
+    /// given format with `read_audio_file`.
+    ///
+    /// Returns an AudioData struct wrapped in Ok if there are no errors, and
+    /// an Error otherwise.
+    pub fn from_bytes(data: &[u8], format: AudioFormat) -> Result<AudioData, Box<dyn Error>> {
+        match format {
+            AudioFormat::Wav => AudioData::decode_wav_bytes(data, None),
+            AudioFormat::Flac => AudioData::decode_flac(FlacReader::new(Cursor::new(data))?),
+            AudioFormat::Mp3 | AudioFormat::Ogg => AudioData::decode_compressed(
+                Box::new(Cursor::new(data.to_vec())),
+                format.extension(),
+            ),
+        }
+    }
+
+    /// Reads the samples of a wav file held in memory and converts them to
+    /// mono using the given strategy, or the default strategy if None is given
+    ///
+    /// Files with 64-bit float samples are read by `decode_float64_wav`, and
+    /// all other files are read by hound.
+    fn decode_wav_bytes(
+        data: &[u8],
+        mix_strategy: Option<ChannelMixStrategy>,
+    ) -> Result<AudioData, Box<dyn Error>> {
+        match AudioData::decode_float64_wav(data, mix_strategy)? {
+            Some(audio_data) => Ok(audio_data),
+            None => AudioData::decode_wav(WavReader::new(Cursor::new(data))?, mix_strategy),
+        }
+    }
+
+    /// Reads the samples of a wav file with 64-bit float samples, which hound
+    /// cannot read, without losing any precision
+    ///
+    /// Returns None if the data is not a wav file with 64-bit float samples.
+    fn decode_float64_wav(
+        data: &[u8],
+        mix_strategy: Option<ChannelMixStrategy>,
+    ) -> Result<Option<AudioData>, Box<dyn Error>> {
+        if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+            return Ok(None);
+        }
+
+        // Number of channels and sample rate from the fmt chunk
+        let mut format: Option<(u16, u32)> = None;
+
+        let mut pos = 12;
+        while pos + 8 <= data.len() {
+            let chunk_id = &data[pos..pos + 4];
+            let chunk_size =
+                u32::from_le_bytes([data[pos + 4], data[pos + 5], data[pos + 6], data[pos + 7]])
+                    as usize;
+            let chunk = &data[pos + 8..data.len().min(pos + 8 + chunk_size)];
+
+            if chunk_id == b"fmt " {
+                if chunk.len() < 16 {
+                    return Err(Box::new(MalformedWav("fmt chunk is too short")));
+                }
+
+                let read_u16 = |i: usize| u16::from_le_bytes([chunk[i], chunk[i + 1]]);
+
+                // The format tag of extensible files is the first two bytes
+                // of the subformat GUID
+                let format_tag = match read_u16(0) {
+                    WAVE_FORMAT_EXTENSIBLE if chunk.len() >= 26 => read_u16(24),
+                    format_tag => format_tag,
+                };
+                let bits_per_sample = read_u16(14);
+
+                if format_tag != WAVE_FORMAT_IEEE_FLOAT || bits_per_sample != 64 {
+                    return Ok(None);
+                }
+
+                let sample_rate = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+                format = Some((read_u16(2), sample_rate));
+            } else if chunk_id == b"data" {
+                let Some((num_channels, sample_rate)) = format else {
+                    return Ok(None);
+                };
+
+                let samples: Vec<f64> = chunk
+                    .chunks_exact(8)
+                    .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()))
+                    .collect();
+
+                let mix_strategy =
+                    mix_strategy.unwrap_or_else(|| ChannelMixStrategy::default_for(num_channels));
+                let samples = AudioData::mix_to_mono(samples, num_channels, mix_strategy)?;
+
+                if samples.is_empty() {
+                    return Err(Box::new(EmptyAudio));
+                }
+
+                return Ok(Some(AudioData {
+                    sample_rate,
+                    duration: samples.len() as u32,
+                    samples,
+                }));
+            }
+
+            // Chunks are padded to an even number of bytes
+            pos += 8 + chunk_size + chunk_size % 2;
+        }
+
+        match format {
+            Some(_) => Err(Box::new(MalformedWav("no data chunk found"))),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the samples of a wav file and converts them to mono using the
+    /// given strategy, or the default strategy if None is given
+    fn decode_wav<R: Read>(
+        reader: WavReader<R>,
+        mix_strategy: Option<ChannelMixStrategy>,
+    ) -> Result<AudioData, Box<dyn Error>> {
+        let sample_rate = reader.spec().sample_rate;
+        let duration = reader.duration();
+        let bit_depth = reader.spec().bits_per_sample;
+        let num_channels = reader.spec().channels;
+
+        if duration == 0 {
+            return Err(Box::new(EmptyAudio));
+        }
+
+        // Read samples as floats
+        let samples: Vec<f32> = match reader.spec().sample_format {
+            SampleFormat::Float => reader
+                .into_samples::<f32>()
+                .map(|sample| sample.unwrap())
+                .collect(),
+            SampleFormat::Int => {
+                let max = AudioData::max_int_sample(bit_depth)?;
+                reader
+                    .into_samples::<i32>()
+                    .map(|sample| sample.unwrap() as f32 / max as f32)
+                    .collect()
+            }
+        };
+
+        let mix_strategy =
+            mix_strategy.unwrap_or_else(|| ChannelMixStrategy::default_for(num_channels));
+        let samples = AudioData::mix_to_mono(samples, num_channels, mix_strategy)?;
+
+        let audio_data = AudioData {
+            sample_rate,
+            duration,
+            samples,
+        };
+        Ok(audio_data)
+    }
+
+    /// Reads the samples of a flac stream and converts them to mono
+    fn decode_flac<R: Read>(mut reader: FlacReader<R>) -> Result<AudioData, Box<dyn Error>> {
+        let stream_info = reader.streaminfo();
+        let sample_rate = stream_info.sample_rate;
+        let bit_depth = stream_info.bits_per_sample as u16;
+        let num_channels = stream_info.channels as u16;
+
+        if num_channels > 2 {
+            return Err(Box::new(UnsupportedChannelCount(num_channels)));
+        }
+
+        let max = AudioData::max_int_sample(bit_depth)?;
+
+        let samples = reader
+            .samples()
+            .map(|sample| Ok(sample? as f32 / max as f32))
+            .collect::<Result<Vec<f32>, claxon::Error>>()?;
+
+        let samples = AudioData::mix_to_mono(
+            samples,
+            num_channels,
+            ChannelMixStrategy::default_for(num_channels),
+        )?;
+
+        Ok(AudioData {
+            sample_rate,
+            duration: samples.len() as u32,
+            samples,
+        })
+    }
+
+    /// Decodes compressed audio using symphonia
+    ///
+    /// Encoder delay and padding are removed if the audio contains the
+    /// information needed to do so.
+    fn decode_compressed(
+        source: Box<dyn MediaSource>,
+        extension: &str,
+    ) -> Result<AudioData, Box<dyn Error>> {
+        let source = MediaSourceStream::new(source, Default::default());
+
+        let mut hint = Hint::new();
+        hint.with_extension(extension);
+
+        let format_options = FormatOptions {
+            enable_gapless: true,
+            ..Default::default()
+        };
+
+        let mut format = symphonia::default::get_probe()
+            .format(&hint, source, &format_options, &MetadataOptions::default())?
+            .format;
+
+        let track = format
+            .default_track()
+            .ok_or_else(|| UnsupportedFormat(extension.to_string()))?;
+        let track_id = track.id;
+        let num_frames = track.codec_params.n_frames;
+        let sample_rate = track
+            .codec_params
+            .sample_rate
+            .ok_or_else(|| UnsupportedFormat(extension.to_string()))?;
+
+        let mut decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())?;
+
+        let mut samples: Vec<f32> = vec![];
+        let mut num_channels = 1;
+
+        loop {
+            let packet = match format.next_packet() {
+                Ok(packet) => packet,
+                // The end of the stream is reported as an unexpected end of
+                // file error
+                Err(SymphoniaError::IoError(e))
+                    if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    break
+                }
+                Err(e) => return Err(Box::new(e)),
+            };
+
+            if packet.track_id() != track_id {
+                continue;
+            }
+
+            let decoded = decoder.decode(&packet)?;
+            let spec = *decoded.spec();
+            num_channels = spec.channels.count() as u16;
+
+            let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+            buffer.copy_interleaved_ref(decoded);
+            samples.extend_from_slice(buffer.samples());
+        }
+
+        // Not all decoders remove padding at the end of the stream, so
+        // discard any samples beyond the length given by the container
+        if let Some(num_frames) = num_frames {
+            samples.truncate(num_frames as usize * num_channels as usize);
+        }
+
+        let samples = AudioData::mix_to_mono(
+            samples,
+            num_channels,
+            ChannelMixStrategy::default_for(num_channels),
+        )?;
+
+        Ok(AudioData {
+            sample_rate,
+            duration: samples.len() as u32,
+            samples,
+        })
+    }
+
+    /// Returns the value which the samples of integer audio with the given bit
+    /// depth are divided by to normalise them
+    fn max_int_sample(bit_depth: u16) -> Result<i32, Box<dyn Error>> {
+        match bit_depth {
+            // 8-bit wav samples are unsigned with 128 as silence, which hound
+            // shifts to the range -128 to 127 when reading them
+            8 => Ok(128),
+            16 => Ok(i16::MAX as i32),
+            24 => Ok(MAX_24BIT),
+            32 => Ok(i32::MAX),
+            _ => Err(Box::new(UnsupportedBitDepth(bit_depth))),
+        }
+    }
+
+    /// Converts interleaved samples with the given number of channels to mono
+    /// using the given strategy
+    ///
+    /// Each mono sample is the weighted average of the samples from each
+    /// channel, with the weights given by the strategy.
+    fn mix_to_mono<T: Copy + Into<f64>>(
+        samples: Vec<T>,
+        num_channels: u16,
+        mix_strategy: ChannelMixStrategy,
+    ) -> Result<Vec<f64>, Box<dyn Error>> {
+        let weights = mix_strategy.channel_weights(num_channels)?;
+        let total_weight: f64 = weights.iter().sum();
+
+        // Samples are interleaved, so each chunk contains one sample from
+        // each channel
+        Ok(samples
+            .chunks_exact(num_channels as usize)
+            .map(|frame| {
+                frame
+                    .iter()
+                    .zip(&weights)
+                    .map(|(sample, weight)| (*sample).into() * weight)
+                    .sum::<f64>()
+                    / total_weight
+            })
+            .collect())
+    }
+
+    /// Calculates the magnitude spectrum of each frame of the audio
+    ///
+    /// Each frame is multiplied by the window function before its FFT is
+    /// calculated. Only the first `frame_width / 2 + 1` bins are returned,
+    /// since the remaining bins of the FFT of a real signal are redundant.
+    /// Bin `k` is centred on the frequency `k * sample_rate / frame_width`.
+    /// One spectrum is returned for each frame given by
+    /// `get_frames(frame_width, hop_size, None, None)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_width` - Number of samples in each frame
+    /// * `hop_size` - Interval between starting positions of consecutive frames
+    /// * `window` - Window function to multiply each frame by
+    pub fn compute_spectrogram(
+        &self,
+        frame_width: usize,
+        hop_size: usize,
+        window: WindowFunction,
+    ) -> Vec<Vec<f64>> {
+        self.compute_complex_spectrogram(frame_width, hop_size, window)
+            .into_iter()
+            .map(|spectrum| spectrum.iter().map(|x| x.norm()).collect())
+            .collect()
+    }
+
+    /// Calculates the complex spectrum of each frame of the audio, with the
+    /// same bins as `compute_spectrogram`
+    fn compute_complex_spectrogram(
+        &self,
+        frame_width: usize,
+        hop_size: usize,
+        window: WindowFunction,
+    ) -> Vec<Vec<Complex<f64>>> {
+        let frames = self.get_windowed_frames(frame_width, hop_size, window, None, None);
+
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(frame_width);
+
+        frames
+            .into_iter()
+            .map(|frame| {
+                let mut spectrum: Vec<Complex<f64>> = frame
+                    .samples
+                    .iter()
+                    .map(|x| Complex { re: *x, im: 0.0 })
+                    .collect();
+                fft.process(&mut spectrum);
+
+                spectrum.truncate(frame_width / 2 + 1);
+                spectrum
+            })
+            .collect()
+    }
+
+    /// Calculates the spectral flux of each frame of the audio, which is the
+    /// sum of the increases in magnitude of each frequency bin since the
+    /// previous frame
+    ///
+    /// Each frame is multiplied by a Hann window before its magnitude
+    /// spectrum is calculated. Decreases in magnitude are ignored, so the
+    /// spectral flux rises sharply at note onsets. The first frame is compared
+    /// with silence. One value is returned for each frame given by
+    /// `get_frames(frame_width, hop_size, None, None)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_width` - Number of samples in each frame
+    /// * `hop_size` - Interval between starting positions of consecutive frames
+    pub fn spectral_flux(&self, frame_width: usize, hop_size: usize) -> Vec<f64> {
+        let spectrogram = self.compute_spectrogram(frame_width, hop_size, WindowFunction::Hann);
+
+        let mut previous_magnitudes = vec![0.0; frame_width / 2 + 1];
+
+        spectrogram
+            .into_iter()
+            .map(|magnitudes| {
+                let flux = magnitudes
+                    .iter()
+                    .zip(previous_magnitudes.iter())
+                    .map(|(current, previous)| (current - previous).max(0.0))
+                    .sum();
+
+                previous_magnitudes = magnitudes;
+                flux
+            })
+            .collect()
+    }
+
+    /// Calculates the complex domain deviation of each frame of the audio
+    ///
+    /// The spectrum of each frame is predicted from the two previous frames,
+    /// by assuming that the magnitude of each bin stays the same and that its
+    /// phase advances by the same amount as it did between those frames. The
+    /// deviation is the sum of the distances in the complex plane between the
+    /// predicted and actual bins. It rises at note onsets, including soft
+    /// onsets where only the frequency of the audio changes. Frames before
+    /// the start of the audio are treated as silence. One value is returned
+    /// for each frame given by `get_frames(frame_width, hop_size, None, None)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_width` - Number of samples in each frame
+    /// * `hop_size` - Interval between starting positions of consecutive frames
+    pub fn complex_domain_deviation(&self, frame_width: usize, hop_size: usize) -> Vec<f64> {
+        let spectrogram =
+            self.compute_complex_spectrogram(frame_width, hop_size, WindowFunction::Hann);
+
+        let silence = vec![Complex { re: 0.0, im: 0.0 }; frame_width / 2 + 1];
+        let mut previous = silence.clone();
+        let mut before_previous = silence;
+
+        spectrogram
+            .into_iter()
+            .map(|spectrum| {
+                let deviation = spectrum
+                    .iter()
+                    .zip(previous.iter().zip(before_previous.iter()))
+                    .map(|(current, (previous, before_previous))| {
+                        let predicted = Complex::from_polar(
+                            previous.norm(),
+                            2.0 * previous.arg() - before_previous.arg(),
+                        );
+                        (current - predicted).norm()
+                    })
+                    .sum();
+
+                before_previous = std::mem::replace(&mut previous, spectrum);
+                deviation
+            })
+            .collect()
+    }
+
+    /// Calculates the high-frequency content (HFC) of each frame of the audio
+    ///
+    /// The HFC of a frame is the sum of the squared magnitude of each bin of
+    /// its Hann windowed spectrum, weighted by the index of the bin. It rises
+    /// sharply at the start of percussive sounds, whose energy is spread
+    /// across high frequencies. One value is returned for each frame given by
+    /// `get_frames(frame_width, hop_size, None, None)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_width` - Number of samples in each frame
+    /// * `hop_size` - Interval between starting positions of consecutive frames
+    pub fn high_frequency_content(&self, frame_width: usize, hop_size: usize) -> Vec<f64> {
+        self.compute_spectrogram(frame_width, hop_size, WindowFunction::Hann)
+            .into_iter()
+            .map(|magnitudes| {
+                magnitudes
+                    .iter()
+                    .enumerate()
+                    .map(|(k, magnitude)| k as f64 * magnitude * magnitude)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Calculates the Mel-frequency cepstral coefficients (MFCCs) of each
+    /// frame of the audio
+    ///
+    /// The power spectrum of each Hann windowed frame is passed through a bank
+    /// of triangular filters spaced evenly on the mel scale between 0 Hz and
+    /// half the sample rate. The DCT-II (with orthonormal scaling) of the log
+    /// filter energies gives the coefficients. One vector of `n_mfcc`
+    /// coefficients is returned for each frame given by
+    /// `get_frames(frame_width, hop_size, None, None)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_width` - Number of samples in each frame
+    /// * `hop_size` - Interval between starting positions of consecutive frames
+    /// * `n_mels` - Number of mel filters
+    /// * `n_mfcc` - Number of coefficients to return for each frame, which
+    ///   must not be greater than `n_mels`
+    pub fn compute_mfcc(
+        &self,
+        frame_width: usize,
+        hop_size: usize,
+        n_mels: usize,
+        n_mfcc: usize,
+    ) -> Vec<Vec<f64>> {
+        assert_ne!(n_mels, 0, "number of mel filters must be positive");
+        assert!(
+            n_mfcc <= n_mels,
+            "number of coefficients must not be greater than the number of mel filters"
+        );
+
+        let filterbank = AudioData::mel_filterbank(n_mels, frame_width, self.sample_rate);
+
+        self.compute_spectrogram(frame_width, hop_size, WindowFunction::Hann)
+            .into_iter()
+            .map(|magnitudes| {
+                // Floor the energies so that silent bands have a finite log
+                let log_energies: Vec<f64> = filterbank
+                    .iter()
+                    .map(|filter| {
+                        let energy: f64 = filter
+                            .iter()
+                            .zip(magnitudes.iter())
+                            .map(|(weight, magnitude)| weight * magnitude * magnitude)
+                            .sum();
+                        energy.max(1e-10).ln()
+                    })
+                    .collect();
+
+                (0..n_mfcc)
+                    .map(|n| {
+                        let scale = if n == 0 { 1.0 } else { 2.0 };
+                        let sum: f64 = log_energies
+                            .iter()
+                            .enumerate()
+                            .map(|(m, energy)| {
+                                energy
+                                    * (std::f64::consts::PI * n as f64 * (m as f64 + 0.5)
+                                        / n_mels as f64)
+                                        .cos()
+                            })
+                            .sum();
+                        (scale / n_mels as f64).sqrt() * sum
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Calculates the short-term energy of each frame of the audio, which is
+    /// the mean of the squared samples in the frame
+    ///
+    /// One value is returned for each frame given by
+    /// `get_frames(frame_width, hop_size, None, None)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_width` - Number of samples in each frame
+    /// * `hop_size` - Interval between starting positions of consecutive frames
+    pub fn energy_envelope(&self, frame_width: usize, hop_size: usize) -> Vec<f64> {
+        self.frame_iter(frame_width, hop_size, None, None)
+            .map(|frame| frame.samples.iter().map(|x| x * x).sum::<f64>() / frame_width as f64)
+            .collect()
+    }
+
+    /// Calculates the zero-crossing rate of each frame of the audio, which is
+    /// the number of times consecutive samples change sign divided by the
+    /// frame width
+    ///
+    /// Unvoiced sounds such as noise and consonants have a high zero-crossing
+    /// rate, while pitched sounds have a rate close to twice their frequency
+    /// divided by the sample rate. Zero is counted as positive. One value is
+    /// returned for each frame given by
+    /// `get_frames(frame_width, hop_size, None, None)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_width` - Number of samples in each frame
+    /// * `hop_size` - Interval between starting positions of consecutive frames
+    pub fn zero_crossing_rate(&self, frame_width: usize, hop_size: usize) -> Vec<f64> {
+        self.get_frames(frame_width, hop_size, None, None)
+            .iter()
+            .map(|frame| {
+                AudioData::count_zero_crossings(&frame.samples) as f64 / frame_width as f64
+            })
+            .collect()
+    }
+
+    /// Converts a frequency in Hz to the mel scale
+    fn hz_to_mel(frequency: f64) -> f64 {
+        2595.0 * (1.0 + frequency / 700.0).log10()
+    }
+
+    /// Converts a frequency on the mel scale to Hz
+    fn mel_to_hz(mel: f64) -> f64 {
+        700.0 * (10f64.powf(mel / 2595.0) - 1.0)
+    }
+
+    /// Creates a bank of triangular filters spaced evenly on the mel scale
+    /// between 0 Hz and the Nyquist frequency
+    ///
+    /// Returns one vector of weights per filter, with one weight for each of
+    /// the `frame_width / 2 + 1` frequency bins of a spectrum.
+    fn mel_filterbank(n_mels: usize, frame_width: usize, sample_rate: u32) -> Vec<Vec<f64>> {
+        let max_mel = AudioData::hz_to_mel(sample_rate as f64 / 2.0);
+
+        // Edges of the filters, where filter m rises from edge m to a peak at
+        // edge m + 1 and falls to zero at edge m + 2
+        let edges: Vec<f64> = (0..n_mels + 2)
+            .map(|i| AudioData::mel_to_hz(max_mel * i as f64 / (n_mels + 1) as f64))
+            .collect();
+
+        (0..n_mels)
+            .map(|m| {
+                (0..=frame_width / 2)
+                    .map(|k| {
+                        let frequency = k as f64 * sample_rate as f64 / frame_width as f64;
+                        let rising = (frequency - edges[m]) / (edges[m + 1] - edges[m]);
+                        let falling = (edges[m + 2] - frequency) / (edges[m + 2] - edges[m + 1]);
+                        rising.min(falling).max(0.0)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Calculates the root mean square of the input samples
+    ///
+    /// Returns the root mean square wrapped in Some if the samples vector is
+    /// non-empty, otherwise returns None.
+    pub fn root_mean_square(samples: Vec<f64>) -> Option<f64> {
+        AudioData::slice_root_mean_square(&samples)
+    }
+
+    /// Calculates the root mean square of all the samples in the audio
+    ///
+    /// Returns the root mean square wrapped in Some if the audio is non-empty,
+    /// otherwise returns None.
+    pub fn rms(&self) -> Option<f64> {
+        AudioData::slice_root_mean_square(&self.samples)
+    }
+
+    /// Calculates the root mean square of the samples without taking
+    /// ownership of them
+    fn slice_root_mean_square(samples: &[f64]) -> Option<f64> {
+        if samples.is_empty() {
+            return None;
+        }
+        Some((samples.iter().map(|x| x * x).sum::<f64>() / samples.len() as f64).sqrt())
+    }
+
+    /// Counts the number of times consecutive samples change sign, where zero
+    /// is counted as positive
+    fn count_zero_crossings(samples: &[f64]) -> usize {
+        samples
+            .windows(2)
+            .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+            .count()
+    }
+}
+
+impl From<AudioData> for Vec<f64> {
+    /// Takes the samples out of the audio data
+    fn from(audio_data: AudioData) -> Vec<f64> {
+        audio_data.samples
+    }
+}
+
+impl TryFrom<(Vec<f64>, u32)> for AudioData {
+    type Error = ZeroSampleRateError;
+
+    /// Creates audio data from a pair of mono samples and their sample rate,
+    /// in the same way as `AudioData::from_raw_samples`
+    ///
+    /// Returns an Error if the sample rate is zero.
+    fn try_from((samples, sample_rate): (Vec<f64>, u32)) -> Result<AudioData, Self::Error> {
+        if sample_rate == 0 {
+            return Err(ZeroSampleRateError);
+        }
+
+        Ok(AudioData::from_raw_samples(samples, sample_rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Tests for wav file reader
+    mod read_wav_file_tests {
+        use crate::audio_utils::{AudioData, AudioFormat};
+        use crate::errors::AudioFileError;
+
+        #[test]
+        fn sample_rate_read_correctly() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let audio_data =
+                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+            assert_eq!(44100, audio_data.sample_rate);
+        }
+
+        #[test]
+        fn duration_read_correctly() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/sine_660Hz_22050samples_s32bit_44100Hz_mono.wav");
+
+            let audio_data =
+                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+            assert_eq!(22050, audio_data.duration);
+        }
+
+        #[test]
+        fn reading_stereo_audio_works_correctly() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_stereo.wav");
+
+            let audio_data =
+                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+            assert_eq!(44100, audio_data.sample_rate);
+            assert_eq!(44100, audio_data.duration);
+        }
+
+        #[test]
+        fn different_bit_depths_read_correctly() {
+            let mut filepath_signed_16 = std::path::PathBuf::new();
+            filepath_signed_16
+                .push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let audio_data_signed_16 =
                 AudioData::read_wav_file(filepath_signed_16).expect("Expected valid wav file data");
 
-            let mut filepath_signed_24 = std::path::PathBuf::new();
-            filepath_signed_24
-                .push("../resources/test/sine_440Hz_44100samples_s24bit_44100Hz_mono.wav");
+            let mut filepath_signed_24 = std::path::PathBuf::new();
+            filepath_signed_24
+                .push("../resources/test/sine_440Hz_44100samples_s24bit_44100Hz_mono.wav");
+
+            let audio_data_signed_24 =
+                AudioData::read_wav_file(filepath_signed_24).expect("Expected valid wav file data");
+
+            let mut filepath_float_32 = std::path::PathBuf::new();
+            filepath_float_32
+                .push("../resources/test/sine_440Hz_44100samples_f32bit_44100Hz_mono.wav");
+
+            let audio_data_float_32 =
+                AudioData::read_wav_file(filepath_float_32).expect("Expected valid wav file data");
+            assert_eq!(
+                audio_data_float_32.sample_rate,
+                audio_data_signed_16.sample_rate
+            );
+            assert_eq!(
+                audio_data_float_32.sample_rate,
+                audio_data_signed_24.sample_rate
+            );
+            assert_eq!(audio_data_float_32.duration, audio_data_signed_16.duration);
+            assert_eq!(audio_data_float_32.duration, audio_data_signed_24.duration);
+        }
+
+        #[test]
+        fn signed_24bit_samples_are_scaled_to_full_scale() {
+            let peak = |filename: &str| {
+                let mut filepath = std::path::PathBuf::from("../resources/test");
+                filepath.push(filename);
+
+                AudioData::read_wav_file(filepath)
+                    .expect("Expected valid wav file data")
+                    .samples
+                    .iter()
+                    .fold(0.0, |max: f64, x| max.max(x.abs()))
+            };
+
+            // Both fixtures contain the same sine, with a peak at half of
+            // full scale
+            let peak_float_32 = peak("sine_440Hz_44100samples_f32bit_44100Hz_mono.wav");
+            let peak_signed_24 = peak("sine_440Hz_44100samples_s24bit_44100Hz_mono.wav");
+
+            assert!((peak_float_32 - 0.5).abs() < 1e-3);
+            assert!(
+                (peak_signed_24 - peak_float_32).abs() < 1e-3,
+                "24-bit peak is {peak_signed_24}"
+            );
+        }
+
+        #[test]
+        fn different_sample_rates_read_correctly() {
+            let mut filepath_8000 = std::path::PathBuf::new();
+            filepath_8000.push("../resources/test/sine_440Hz_8000samples_s16bit_8000Hz_mono.wav");
+
+            let audio_data_8000 =
+                AudioData::read_wav_file(filepath_8000).expect("Expected valid wav file data");
+
+            assert_eq!(8000, audio_data_8000.sample_rate);
+
+            let mut filepath_22050 = std::path::PathBuf::new();
+            filepath_22050
+                .push("../resources/test/sine_440Hz_22050samples_s16bit_22050Hz_mono.wav");
+
+            let audio_data_22050 =
+                AudioData::read_wav_file(filepath_22050).expect("Expected valid wav file data");
+
+            assert_eq!(22050, audio_data_22050.sample_rate);
+
+            let mut filepath_44100 = std::path::PathBuf::new();
+            filepath_44100
+                .push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let audio_data_44100 =
+                AudioData::read_wav_file(filepath_44100).expect("Expected valid wav file data");
+
+            assert_eq!(44100, audio_data_44100.sample_rate);
+            // Duration (in seconds) should be the same for all 3 files
+            // duration in seconds = duration in samples / sample rate
+            assert_eq!(
+                audio_data_8000.duration / audio_data_8000.sample_rate,
+                audio_data_44100.duration / audio_data_44100.sample_rate
+            );
+            assert_eq!(
+                audio_data_22050.duration / audio_data_22050.sample_rate,
+                audio_data_44100.duration / audio_data_44100.sample_rate
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "Expected valid wav file data")]
+        fn reading_non_wav_file_should_panic() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/README.md");
+
+            AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+        }
+
+        #[test]
+        fn reading_empty_wav_file_returns_empty_audio_error() {
+            let dir = tempfile::tempdir().unwrap();
+            let filepath = dir.path().join("empty.wav");
+            let spec = hound::WavSpec {
+                channels: 1,
+                sample_rate: 44100,
+                bits_per_sample: 16,
+                sample_format: hound::SampleFormat::Int,
+            };
+            hound::WavWriter::create(&filepath, spec)
+                .unwrap()
+                .finalize()
+                .unwrap();
+
+            let error = AudioData::read_wav_file(filepath).err().unwrap();
+
+            assert!(matches!(
+                error.downcast_ref::<AudioFileError>(),
+                Some(AudioFileError::EmptyAudio)
+            ));
+        }
+
+        /// Builds a mono 8-bit wav file containing the given unsigned samples
+        fn unsigned_8bit_wav(samples: &[u8]) -> Vec<u8> {
+            let mut bytes = vec![];
+            bytes.extend_from_slice(b"RIFF");
+            bytes.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(b"WAVEfmt ");
+            bytes.extend_from_slice(&16u32.to_le_bytes());
+            bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+            bytes.extend_from_slice(&1u16.to_le_bytes()); // Channels
+            bytes.extend_from_slice(&8000u32.to_le_bytes()); // Sample rate
+            bytes.extend_from_slice(&8000u32.to_le_bytes()); // Byte rate
+            bytes.extend_from_slice(&1u16.to_le_bytes()); // Block align
+            bytes.extend_from_slice(&8u16.to_le_bytes()); // Bits per sample
+            bytes.extend_from_slice(b"data");
+            bytes.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(samples);
+            bytes
+        }
+
+        /// Builds a 64-bit float wav file containing the given interleaved
+        /// samples
+        fn float64_wav(samples: &[f64], num_channels: u16) -> Vec<u8> {
+            let data_size = samples.len() as u32 * 8;
+            let mut bytes = vec![];
+            bytes.extend_from_slice(b"RIFF");
+            bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+            bytes.extend_from_slice(b"WAVEfmt ");
+            bytes.extend_from_slice(&16u32.to_le_bytes());
+            bytes.extend_from_slice(&3u16.to_le_bytes()); // IEEE float
+            bytes.extend_from_slice(&num_channels.to_le_bytes());
+            bytes.extend_from_slice(&44100u32.to_le_bytes()); // Sample rate
+            bytes.extend_from_slice(&(44100 * 8 * num_channels as u32).to_le_bytes());
+            bytes.extend_from_slice(&(8 * num_channels).to_le_bytes()); // Block align
+            bytes.extend_from_slice(&64u16.to_le_bytes()); // Bits per sample
+            bytes.extend_from_slice(b"data");
+            bytes.extend_from_slice(&data_size.to_le_bytes());
+            for sample in samples {
+                bytes.extend_from_slice(&sample.to_le_bytes());
+            }
+            bytes
+        }
+
+        #[test]
+        fn float64_samples_read_without_precision_loss() {
+            // None of these values can be represented exactly as an f32
+            let samples = vec![0.1, 1.0 / 3.0, -0.7, 1e-10, -0.999999999];
+
+            let file = tempfile::NamedTempFile::new().unwrap();
+            std::fs::write(file.path(), float64_wav(&samples, 1)).unwrap();
+
+            let audio_data = AudioData::read_wav_file(file.path().to_path_buf())
+                .expect("Expected valid wav file data");
+
+            assert_eq!(44100, audio_data.sample_rate);
+            assert_eq!(5, audio_data.duration);
+            assert_eq!(samples, audio_data.samples);
+        }
+
+        #[test]
+        fn float64_stereo_samples_use_left_channel() {
+            let file = tempfile::NamedTempFile::new().unwrap();
+            std::fs::write(file.path(), float64_wav(&[0.1, 0.5, -0.7, 0.5], 2)).unwrap();
+
+            let audio_data = AudioData::read_wav_file(file.path().to_path_buf())
+                .expect("Expected valid wav file data");
+
+            assert_eq!(2, audio_data.duration);
+            assert_eq!(vec![0.1, -0.7], audio_data.samples);
+        }
+
+        #[test]
+        fn float64_bytes_read_correctly() {
+            let samples = vec![0.1, -0.7];
+
+            let audio_data =
+                AudioData::from_bytes(&float64_wav(&samples, 1), AudioFormat::Wav).unwrap();
+
+            assert_eq!(samples, audio_data.samples);
+        }
+
+        #[test]
+        fn float64_file_without_data_returns_error() {
+            let mut bytes = float64_wav(&[], 1);
+            bytes.truncate(36);
+
+            let error = AudioData::from_bytes(&bytes, AudioFormat::Wav).unwrap_err();
+
+            assert_eq!("malformed wav file: no data chunk found", error.to_string());
+        }
+
+        #[test]
+        fn unsigned_8bit_samples_read_correctly() {
+            let file = tempfile::NamedTempFile::new().unwrap();
+            std::fs::write(file.path(), unsigned_8bit_wav(&[128, 255, 0, 192, 64])).unwrap();
+
+            let audio_data = AudioData::read_wav_file(file.path().to_path_buf())
+                .expect("Expected valid wav file data");
+
+            assert_eq!(8000, audio_data.sample_rate);
+            assert_eq!(5, audio_data.duration);
+            // 128 is silence, so it is read as zero
+            assert_eq!(
+                vec![0.0, 127.0 / 128.0, -1.0, 0.5, -0.5],
+                audio_data.samples
+            );
+        }
+    }
+
+    mod read_wav_file_with_options_tests {
+        use crate::audio_utils::{AudioData, ChannelMixStrategy};
+        use crate::test_utils;
+        use hound::{SampleFormat, WavSpec};
+        use std::path::PathBuf;
+        use tempfile::TempDir;
+
+        /// Writes a 32-bit float wav file where each frame contains the given
+        /// channel values
+        fn write_wav(dir: &TempDir, channels: &[f32], num_frames: usize) -> PathBuf {
+            let spec = WavSpec {
+                channels: channels.len() as u16,
+                sample_rate: 44100,
+                bits_per_sample: 32,
+                sample_format: SampleFormat::Float,
+            };
+
+            test_utils::write_wav(dir, spec, &channels.repeat(num_frames))
+        }
+
+        #[test]
+        fn four_channel_file_is_averaged() {
+            let dir = TempDir::new().unwrap();
+            let filepath = write_wav(&dir, &[0.1, 0.2, 0.3, 0.4], 1000);
+
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+
+            assert_eq!(1000, audio_data.duration);
+            assert_eq!(1000, audio_data.samples.len());
+            assert!(audio_data.samples.iter().all(|x| (x - 0.25).abs() < 1e-6));
+        }
+
+        #[test]
+        fn left_only_uses_first_channel() {
+            let dir = TempDir::new().unwrap();
+            let filepath = write_wav(&dir, &[0.1, 0.2, 0.3, 0.4], 10);
+
+            let audio_data =
+                AudioData::read_wav_file_with_options(filepath, ChannelMixStrategy::LeftOnly)
+                    .unwrap();
+
+            assert!(audio_data.samples.iter().all(|x| (x - 0.1).abs() < 1e-6));
+        }
+
+        #[test]
+        fn downmix_leaves_out_lfe_channel() {
+            let dir = TempDir::new().unwrap();
+            // 5.1 audio with only the low-frequency effects channel active
+            let filepath = write_wav(&dir, &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0], 10);
+
+            let audio_data =
+                AudioData::read_wav_file_with_options(filepath, ChannelMixStrategy::Downmix)
+                    .unwrap();
+
+            assert_eq!(10, audio_data.duration);
+            assert!(audio_data.samples.iter().all(|x| *x == 0.0));
+        }
+
+        #[test]
+        fn downmix_attenuates_surround_channels() {
+            let dir = TempDir::new().unwrap();
+            let filepath = write_wav(&dir, &[1.0, 1.0, 1.0, 0.0, 0.0, 0.0], 10);
+
+            let front =
+                AudioData::read_wav_file_with_options(filepath, ChannelMixStrategy::Downmix)
+                    .unwrap()
+                    .samples[0];
+
+            let filepath = write_wav(&dir, &[0.0, 0.0, 0.0, 0.0, 1.0, 1.0], 10);
+
+            let surround =
+                AudioData::read_wav_file_with_options(filepath, ChannelMixStrategy::Downmix)
+                    .unwrap()
+                    .samples[0];
+
+            assert!((surround / front - std::f64::consts::FRAC_1_SQRT_2 * 2.0 / 3.0).abs() < 1e-6);
+        }
+    }
+
+    mod write_wav_file_tests {
+        use crate::audio_utils::{AudioData, WavSampleFormat};
+        use crate::errors::AudioFileError;
+        use crate::test_utils::sine_samples;
+        use tempfile::TempDir;
+
+        fn sine() -> AudioData {
+            let samples = sine_samples(440.0, 44100, 1000)
+                .iter()
+                .map(|x| 0.8 * x)
+                .collect();
+            AudioData::from_raw_samples(samples, 44100)
+        }
+
+        fn round_trip(bit_depth: u16, sample_format: WavSampleFormat) -> AudioData {
+            let dir = TempDir::new().unwrap();
+            let filepath = dir.path().join("audio.wav");
+
+            sine()
+                .write_wav_file_with_options(filepath.clone(), bit_depth, sample_format)
+                .unwrap();
+
+            AudioData::read_wav_file(filepath).unwrap()
+        }
+
+        fn assert_samples_close(expected: &AudioData, actual: &AudioData, tolerance: f64) {
+            assert_eq!(expected.sample_rate, actual.sample_rate);
+            assert_eq!(expected.duration, actual.duration);
+            for (x, y) in expected.samples.iter().zip(&actual.samples) {
+                assert!((x - y).abs() <= tolerance, "expected {x}, found {y}");
+            }
+        }
+
+        #[test]
+        fn samples_round_trip_at_16_bits() {
+            let dir = TempDir::new().unwrap();
+            let filepath = dir.path().join("audio.wav");
+
+            sine().write_wav_file(filepath.clone(), 16).unwrap();
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+
+            // Dither adds up to one LSB of noise before rounding
+            assert_samples_close(&sine(), &audio_data, 2.0 / i16::MAX as f64);
+        }
+
+        #[test]
+        fn samples_round_trip_at_every_bit_depth() {
+            for bit_depth in [8, 24, 32] {
+                // Dither adds up to one LSB of noise before rounding, and
+                // integer samples are converted to f32 when they are read
+                let tolerance = (2.0 / 2.0f64.powi(bit_depth as i32 - 1)).max(f32::EPSILON as f64);
+
+                assert_samples_close(
+                    &sine(),
+                    &round_trip(bit_depth, WavSampleFormat::Int),
+                    tolerance,
+                );
+            }
+
+            assert_samples_close(
+                &sine(),
+                &round_trip(32, WavSampleFormat::Float),
+                f32::EPSILON as f64,
+            );
+        }
+
+        #[test]
+        fn samples_outside_full_scale_are_clipped() {
+            let dir = TempDir::new().unwrap();
+            let filepath = dir.path().join("audio.wav");
+
+            AudioData::from_raw_samples(vec![1.5, -1.5], 44100)
+                .write_wav_file(filepath.clone(), 16)
+                .unwrap();
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+
+            assert_eq!(vec![1.0, -1.0], audio_data.samples);
+        }
+
+        #[test]
+        fn unsupported_bit_depths_return_error() {
+            let dir = TempDir::new().unwrap();
+
+            for (bit_depth, sample_format) in [
+                (12, WavSampleFormat::Int),
+                (64, WavSampleFormat::Int),
+                (16, WavSampleFormat::Float),
+            ] {
+                let filepath = dir.path().join("audio.wav");
+
+                let error = sine()
+                    .write_wav_file_with_options(filepath.clone(), bit_depth, sample_format)
+                    .unwrap_err();
+
+                assert!(matches!(
+                    error.downcast_ref::<AudioFileError>(),
+                    Some(AudioFileError::UnsupportedBitDepth(depth)) if *depth == bit_depth
+                ));
+                assert!(!filepath.exists());
+            }
+        }
+    }
+
+    mod stream_frames_tests {
+        use crate::audio_utils::{AudioData, Frame};
+        use crate::test_utils::write_wav;
+        use hound::{SampleFormat, WavSpec};
+        use std::path::PathBuf;
+        use tempfile::TempDir;
+
+        /// Writes a 16-bit stereo wav file with 1000 frames of ramps
+        fn write_stereo_wav(dir: &TempDir) -> PathBuf {
+            let spec = WavSpec {
+                channels: 2,
+                sample_rate: 8000,
+                bits_per_sample: 16,
+                sample_format: SampleFormat::Int,
+            };
+            let samples: Vec<i16> = (0..1000).flat_map(|i| [i * 10, -i]).collect();
+
+            write_wav(dir, spec, &samples)
+        }
+
+        fn assert_same_frames(filepath: PathBuf, frame_width: usize, hop_size: usize) {
+            let audio_data = AudioData::read_wav_file(filepath.clone()).unwrap();
+
+            let streamed: Vec<Frame> = AudioData::stream_frames(filepath, frame_width, hop_size)
+                .unwrap()
+                .map(|frame| frame.unwrap())
+                .collect();
+
+            assert_eq!(
+                audio_data.get_frames(frame_width, hop_size, None, None),
+                streamed
+            );
+        }
+
+        #[test]
+        fn streamed_frames_match_in_memory_frames() {
+            let dir = TempDir::new().unwrap();
+            let filepath = write_stereo_wav(&dir);
+
+            for (frame_width, hop_size) in [(100, 50), (128, 128), (64, 100), (1000, 1), (1001, 1)]
+            {
+                assert_same_frames(filepath.clone(), frame_width, hop_size);
+            }
+        }
+
+        #[test]
+        fn streamed_frames_match_for_fixture_files() {
+            for name in [
+                "sine_440Hz_8000samples_s16bit_8000Hz_mono.wav",
+                "sine_440Hz_44100samples_s24bit_44100Hz_mono.wav",
+                "sine_440Hz_44100samples_f32bit_44100Hz_mono.wav",
+            ] {
+                let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+                filepath.push("../resources/test");
+                filepath.push(name);
+
+                assert_same_frames(filepath, 2048, 512);
+            }
+        }
+
+        #[test]
+        fn missing_file_returns_error() {
+            let dir = TempDir::new().unwrap();
+
+            assert!(AudioData::stream_frames(dir.path().join("missing.wav"), 100, 50).is_err());
+        }
+
+        #[test]
+        #[should_panic(expected = "hop size must be positive")]
+        fn zero_hop_size_should_panic() {
+            let dir = TempDir::new().unwrap();
+            let _ = AudioData::stream_frames(write_stereo_wav(&dir), 100, 0);
+        }
+    }
+
+    mod mid_side_tests {
+        use crate::audio_utils::{AudioData, ChannelMixStrategy};
+        use crate::test_utils::{write_stereo_wav, write_wav};
+        use hound::{SampleFormat, WavSpec};
+        use std::path::PathBuf;
+        use tempfile::TempDir;
+
+        fn read(filepath: PathBuf, mix_strategy: ChannelMixStrategy) -> Vec<f64> {
+            AudioData::read_wav_file_with_options(filepath, mix_strategy)
+                .unwrap()
+                .samples
+        }
+
+        #[test]
+        fn opposite_channels_are_mixed_correctly() {
+            let dir = TempDir::new().unwrap();
+            let filepath = write_stereo_wav(&dir, &[1.0, 1.0], &[-1.0, -1.0]);
+
+            assert_eq!(
+                vec![0.0, 0.0],
+                read(filepath.clone(), ChannelMixStrategy::Average)
+            );
+            assert_eq!(
+                vec![1.0, 1.0],
+                read(filepath.clone(), ChannelMixStrategy::LeftOnly)
+            );
+            assert_eq!(vec![0.0, 0.0], read(filepath, ChannelMixStrategy::MidSide));
+        }
+
+        #[test]
+        fn mid_signal_is_used() {
+            let dir = TempDir::new().unwrap();
+            let filepath = write_stereo_wav(&dir, &[1.0, 0.5], &[0.5, -0.5]);
+
+            assert_eq!(vec![0.75, 0.0], read(filepath, ChannelMixStrategy::MidSide));
+        }
+
+        #[test]
+        fn more_than_two_channels_returns_error() {
+            let dir = TempDir::new().unwrap();
+            let spec = WavSpec {
+                channels: 4,
+                sample_rate: 44100,
+                bits_per_sample: 16,
+                sample_format: SampleFormat::Int,
+            };
+            let filepath = write_wav(&dir, spec, &[0i16; 4]);
+
+            let error =
+                AudioData::read_wav_file_with_options(filepath, ChannelMixStrategy::MidSide)
+                    .err()
+                    .unwrap();
+
+            assert_eq!(
+                "unsupported channel count `4`, expected mono or stereo audio",
+                error.to_string()
+            );
+        }
+    }
+
+    mod read_audio_file_tests {
+        use crate::audio_utils::AudioData;
+        use std::path::PathBuf;
+
+        fn test_file(name: &str) -> PathBuf {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test");
+            filepath.push(name);
+            filepath
+        }
+
+        #[test]
+        fn mp3_file_read_correctly() {
+            let audio_data =
+                AudioData::read_audio_file(test_file("sine_440Hz_44100samples_44100Hz_stereo.mp3"))
+                    .expect("Expected valid mp3 file data");
+
+            assert_eq!(44100, audio_data.sample_rate);
+            assert_eq!(44100, audio_data.duration);
+            // Stereo audio is converted to mono
+            assert_eq!(44100, audio_data.samples.len());
+        }
+
+        #[test]
+        fn mp3_samples_are_normalised() {
+            let audio_data =
+                AudioData::read_audio_file(test_file("sine_440Hz_44100samples_44100Hz_stereo.mp3"))
+                    .unwrap();
+
+            // The sine wave has a peak amplitude of 0.8
+            let peak = audio_data
+                .samples
+                .iter()
+                .fold(0.0, |max: f64, x| max.max(x.abs()));
+            assert!((peak - 0.8).abs() < 0.05, "peak was {}", peak);
+        }
+
+        #[test]
+        fn wav_file_read_correctly() {
+            let audio_data = AudioData::read_audio_file(test_file(
+                "sine_440Hz_44100samples_s16bit_44100Hz_stereo.wav",
+            ))
+            .unwrap();
+
+            assert_eq!(44100, audio_data.sample_rate);
+            assert_eq!(44100, audio_data.duration);
+            assert_eq!(44100, audio_data.samples.len());
+        }
+
+        #[test]
+        fn flac_file_read_correctly() {
+            let audio_data = AudioData::read_audio_file(test_file(
+                "sine_440Hz_44100samples_s24bit_44100Hz_mono.flac",
+            ))
+            .expect("Expected valid flac file data");
+
+            assert_eq!(44100, audio_data.sample_rate);
+            assert_eq!(44100, audio_data.duration);
+        }
+
+        #[test]
+        fn flac_samples_match_wav_samples() {
+            // The flac file was encoded from the samples of the wav file
+            let flac_audio_data = AudioData::read_flac_file(test_file(
+                "sine_440Hz_44100samples_s24bit_44100Hz_mono.flac",
+            ))
+            .unwrap();
+            let wav_audio_data = AudioData::read_wav_file(test_file(
+                "sine_440Hz_44100samples_s24bit_44100Hz_mono.wav",
+            ))
+            .unwrap();
+
+            assert_eq!(wav_audio_data.samples, flac_audio_data.samples);
+        }
+
+        #[test]
+        fn flac_file_with_more_than_two_channels_returns_error() {
+            let error = AudioData::read_flac_file(test_file(
+                "sine_440Hz_4410samples_s16bit_44100Hz_quad.flac",
+            ))
+            .err()
+            .unwrap();
+
+            assert_eq!(
+                "unsupported channel count `4`, expected mono or stereo audio",
+                error.to_string()
+            );
+        }
+
+        #[test]
+        fn ogg_file_read_correctly() {
+            let audio_data =
+                AudioData::read_audio_file(test_file("sine_440Hz_44100samples_44100Hz_mono.ogg"))
+                    .expect("Expected valid ogg file data");
+
+            assert_eq!(44100, audio_data.sample_rate);
+            assert_eq!(44100, audio_data.duration);
+        }
+
+        #[test]
+        fn unsupported_extension_returns_error() {
+            let error = AudioData::read_audio_file(test_file("README.md"))
+                .err()
+                .unwrap();
+
+            assert_eq!(
+                "unsupported audio file format `md`, expected wav, flac, mp3, or ogg",
+                error.to_string()
+            );
+        }
+    }
+
+    mod from_bytes_tests {
+        use crate::audio_utils::{AudioData, AudioFormat};
+        use std::path::PathBuf;
+
+        fn test_file(name: &str) -> PathBuf {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test");
+            filepath.push(name);
+            filepath
+        }
+
+        fn assert_same_audio(name: &str, format: AudioFormat) {
+            let bytes = std::fs::read(test_file(name)).unwrap();
+
+            let from_bytes = AudioData::from_bytes(&bytes, format).unwrap();
+            let from_file = AudioData::read_audio_file(test_file(name)).unwrap();
+
+            assert_eq!(from_file.sample_rate, from_bytes.sample_rate);
+            assert_eq!(from_file.duration, from_bytes.duration);
+            assert_eq!(from_file.samples, from_bytes.samples);
+        }
+
+        #[test]
+        fn wav_bytes_read_correctly() {
+            assert_same_audio(
+                "sine_440Hz_44100samples_s16bit_44100Hz_stereo.wav",
+                AudioFormat::Wav,
+            );
+        }
+
+        #[test]
+        fn flac_bytes_read_correctly() {
+            assert_same_audio(
+                "sine_440Hz_44100samples_s24bit_44100Hz_mono.flac",
+                AudioFormat::Flac,
+            );
+        }
+
+        #[test]
+        fn mp3_bytes_read_correctly() {
+            assert_same_audio(
+                "sine_440Hz_44100samples_44100Hz_stereo.mp3",
+                AudioFormat::Mp3,
+            );
+        }
+
+        #[test]
+        fn ogg_bytes_read_correctly() {
+            assert_same_audio("sine_440Hz_44100samples_44100Hz_mono.ogg", AudioFormat::Ogg);
+        }
+
+        #[test]
+        fn invalid_bytes_return_error() {
+            assert!(AudioData::from_bytes(b"not audio", AudioFormat::Wav).is_err());
+        }
+    }
+
+    mod from_raw_samples_tests {
+        use crate::audio_utils::AudioData;
+
+        #[test]
+        fn duration_is_number_of_samples() {
+            let audio_data = AudioData::from_raw_samples(vec![0.0, 0.5, -0.5], 8000);
+
+            assert_eq!(8000, audio_data.sample_rate);
+            assert_eq!(3, audio_data.duration);
+            assert_eq!(vec![0.0, 0.5, -0.5], audio_data.samples);
+        }
+
+        #[test]
+        #[should_panic(expected = "sample rate must be non-zero")]
+        fn zero_sample_rate_should_panic() {
+            AudioData::from_raw_samples(vec![0.0; 10], 0);
+        }
+    }
+
+    mod from_tests {
+        use crate::audio_utils::AudioData;
+        use crate::errors::ZeroSampleRateError;
+
+        #[test]
+        fn samples_are_extracted_from_audio_data() {
+            let samples = vec![0.0, 0.5, -0.5];
+            let audio_data = AudioData::from_raw_samples(samples.clone(), 8000);
+
+            assert_eq!(samples, Vec::<f64>::from(audio_data));
+        }
+
+        #[test]
+        fn tuple_gives_same_audio_data_as_from_raw_samples() {
+            let samples = vec![0.0, 0.5, -0.5];
+
+            assert_eq!(
+                AudioData::from_raw_samples(samples.clone(), 8000),
+                AudioData::try_from((samples, 8000)).unwrap()
+            );
+        }
+
+        #[test]
+        fn tuple_round_trips_through_get_frames() {
+            let samples: Vec<f64> = (0..8).map(|x| x as f64).collect();
+            let audio_data: AudioData = (samples.clone(), 8000).try_into().unwrap();
+
+            let frames = audio_data.get_frames(4, 4, None, None);
+            let joined: Vec<f64> = frames.into_iter().flat_map(|f| f.samples).collect();
+
+            assert_eq!(8, audio_data.duration);
+            assert_eq!(samples, joined);
+        }
+
+        #[test]
+        fn zero_sample_rate_returns_error() {
+            assert_eq!(
+                Err(ZeroSampleRateError),
+                AudioData::try_from((vec![0.0; 10], 0))
+            );
+        }
+    }
+
+    mod derive_tests {
+        use crate::audio_utils::AudioData;
+
+        #[test]
+        fn clone_is_equal_to_original() {
+            let audio_data = AudioData::from_raw_samples(vec![0.0, 0.5, -0.5], 8000);
+
+            assert_eq!(audio_data, audio_data.clone());
+        }
+
+        #[test]
+        fn different_samples_are_not_equal() {
+            let audio_data = AudioData::from_raw_samples(vec![0.0, 0.5, -0.5], 8000);
+
+            assert_ne!(
+                audio_data,
+                AudioData::from_raw_samples(vec![0.0, 0.5, 0.5], 8000)
+            );
+            assert_ne!(
+                audio_data,
+                AudioData::from_raw_samples(vec![0.0, 0.5, -0.5], 44100)
+            );
+        }
+
+        #[test]
+        fn debug_output_includes_fields() {
+            let audio_data = AudioData::from_raw_samples(vec![0.25], 8000);
+
+            let debug = format!("{:?}", audio_data);
+
+            assert!(debug.contains("sample_rate: 8000"));
+            assert!(debug.contains("samples: [0.25]"));
+        }
+
+        #[test]
+        fn resampling_to_same_rate_gives_equal_audio() {
+            let audio_data = AudioData::from_raw_samples(vec![0.0, 0.5, -0.5], 8000);
+
+            assert_eq!(audio_data, audio_data.resample(8000));
+        }
+    }
+
+    /// Tests for AudioData methods
+    mod slice_tests {
+        use crate::audio_utils::AudioData;
+
+        fn ramp() -> AudioData {
+            AudioData::from_raw_samples((0..1000).map(|i| i as f64 / 1000.0).collect(), 8000)
+        }
+
+        #[test]
+        fn slice_contains_correct_samples() {
+            let audio_data = ramp();
+
+            assert_eq!(audio_data.samples[0..100], audio_data.slice(0, 100).samples);
+            assert_eq!(
+                audio_data.samples[250..750],
+                audio_data.slice(250, 750).samples
+            );
+            assert_eq!(audio_data.samples, audio_data.slice(0, 1000).samples);
+        }
+
+        #[test]
+        fn slice_has_correct_duration_and_sample_rate() {
+            let slice = ramp().slice(250, 750);
+
+            assert_eq!(500, slice.duration);
+            assert_eq!(8000, slice.sample_rate);
+            assert_eq!(0, ramp().slice(300, 300).duration);
+        }
+
+        #[test]
+        #[should_panic(expected = "slice end must not be greater than the number of samples")]
+        fn end_past_last_sample_should_panic() {
+            ramp().slice(0, 1001);
+        }
+
+        #[test]
+        #[should_panic(expected = "slice start must not be greater than slice end")]
+        fn start_after_end_should_panic() {
+            ramp().slice(500, 400);
+        }
+    }
+
+    mod concat_tests {
+        use crate::errors::IncompatibleSampleRateError;
+        use crate::test_utils::sine;
+
+        #[test]
+        fn concatenated_sines_have_combined_length() {
+            let first = sine(440.0, 44100, 44100);
+            let second = sine(440.0, 44100, 44100);
+
+            let combined = first.concat(&second).unwrap();
+
+            assert_eq!(88200, combined.samples.len());
+            assert_eq!(88200, combined.duration);
+            assert_eq!(44100, combined.sample_rate);
+            assert_eq!(first.samples, combined.samples[..44100]);
+            assert_eq!(second.samples, combined.samples[44100..]);
+        }
+
+        #[test]
+        fn different_sample_rates_return_error() {
+            let error = sine(440.0, 44100, 44100)
+                .concat(&sine(440.0, 48000, 48000))
+                .err()
+                .unwrap();
+
+            assert_eq!(
+                IncompatibleSampleRateError {
+                    expected: 44100,
+                    found: 48000
+                },
+                error
+            );
+            assert_eq!(
+                "incompatible sample rates `44100` Hz and `48000` Hz",
+                error.to_string()
+            );
+        }
+    }
+
+    mod mix_tests {
+        use crate::audio_utils::AudioData;
+        use crate::errors::IncompatibleSampleRateError;
+        use crate::test_utils::decaying_chord;
+        use crate::transcription::{NoteName, Transcriber};
+
+        #[test]
+        fn samples_are_added() {
+            let a = AudioData::from_raw_samples(vec![0.1, 0.2, -0.3], 44100);
+            let b = AudioData::from_raw_samples(vec![0.4, -0.2, 0.1], 44100);
+
+            let mixed = a.mix(&b).unwrap();
+
+            let expected = [0.5, 0.0, -0.2];
+            for (x, y) in expected.iter().zip(&mixed.samples) {
+                assert!((x - y).abs() < 1e-12);
+            }
+        }
+
+        #[test]
+        fn shorter_audio_is_padded_with_silence() {
+            let a = AudioData::from_raw_samples(vec![0.1, 0.2], 44100);
+            let b = AudioData::from_raw_samples(vec![0.1, 0.2, 0.3, 0.4], 44100);
+
+            let mixed = a.mix(&b).unwrap();
+
+            assert_eq!(4, mixed.duration);
+            assert_eq!(vec![0.2, 0.4, 0.3, 0.4], mixed.samples);
+        }
+
+        #[test]
+        fn mixed_samples_are_clipped() {
+            let a = AudioData::from_raw_samples(vec![0.8, -0.8], 44100);
+
+            assert_eq!(vec![1.0, -1.0], a.mix(&a).unwrap().samples);
+        }
+
+        #[test]
+        fn identical_signals_at_half_gain_have_original_rms() {
+            let original = decaying_chord(&[440.0], 0.4);
+            let half = original.apply_gain(20.0 * 0.5f64.log10(), true);
+
+            let mixed = half.mix(&half).unwrap();
+
+            assert!((original.rms().unwrap() - mixed.rms().unwrap()).abs() < 1e-9);
+        }
+
+        #[test]
+        fn mixed_notes_are_detected_as_chord() {
+            let mixed = decaying_chord(&[440.0], 0.4)
+                .mix(&decaying_chord(&[523.25], 0.4))
+                .unwrap();
+
+            let chords = Transcriber::get_chords(mixed);
+
+            assert_eq!(1, chords.len());
+            let names: Vec<NoteName> = chords[0].notes.iter().map(|n| n.name).collect();
+            assert_eq!(vec![NoteName::A(4), NoteName::C(5)], names);
+        }
+
+        #[test]
+        fn different_sample_rates_return_error() {
+            let a = AudioData::from_raw_samples(vec![0.0; 10], 44100);
+            let b = AudioData::from_raw_samples(vec![0.0; 10], 22050);
+
+            assert_eq!(
+                Err(IncompatibleSampleRateError {
+                    expected: 44100,
+                    found: 22050
+                }),
+                a.mix(&b)
+            );
+        }
+    }
+
+    mod get_frames_tests {
+        use crate::audio_utils::AudioData;
+
+        #[test]
+        fn returns_correct_number_of_frames() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let audio_data =
+                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+
+            assert_eq!(10, audio_data.get_frames(4410, 4410, None, None).len());
+            // Expected number of frames is 19 since the 20th frame starting at
+            // sample 41895 would not have width 4410, as there are only 44100
+            // samples in total
+            assert_eq!(19, audio_data.get_frames(4410, 2205, None, None).len());
+            assert_eq!(20, audio_data.get_frames(2205, 2205, None, None).len());
+            assert_eq!(
+                audio_data.samples.len(),
+                audio_data.get_frames(1, 1, None, None).len()
+            );
+            assert_eq!(
+                10,
+                audio_data.get_frames(2205, 2205, Some(22050), None).len()
+            );
+            assert_eq!(
+                1,
+                audio_data.get_frames(2205, 2205, Some(41895), None).len()
+            );
+            assert_eq!(
+                10,
+                audio_data.get_frames(2205, 2205, None, Some(22050)).len()
+            );
+            assert_eq!(1, audio_data.get_frames(2205, 2205, None, Some(2205)).len());
+            assert_eq!(
+                0,
+                audio_data
+                    .get_frames(2205, 2205, Some(22050), Some(22050))
+                    .len()
+            );
+            assert_eq!(
+                0,
+                audio_data
+                    .get_frames(2205, 2205, Some(22051), Some(22050))
+                    .len()
+            );
+        }
+
+        #[test]
+        fn overlapping_frame_contents_are_consistent() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let audio_data =
+                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+            let frames = audio_data.get_frames(4410, 2205, None, None);
+
+            assert_eq!(frames[0].samples[2205], frames[1].samples[0]);
+            assert_eq!(frames[0].samples[4409], frames[1].samples[2204]);
+            assert_eq!(frames[1].samples[2205], frames[2].samples[0]);
+            assert_eq!(frames[1].samples[4409], frames[2].samples[2204]);
+        }
+
+        #[test]
+        fn different_starting_point_frame_contents_are_consistent() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let audio_data =
+                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+            let frames_1 = audio_data.get_frames(4410, 2205, None, None);
+            let frames_2 = audio_data.get_frames(4410, 2205, Some(2205), None);
+
+            assert_eq!(frames_1[0].samples[2205], frames_2[0].samples[0]);
+            assert_eq!(frames_1[0].samples[4400], frames_2[0].samples[2195]);
+            assert_eq!(frames_1[1].samples[2205], frames_2[1].samples[0]);
+            assert_eq!(frames_1[1].samples[4400], frames_2[1].samples[2195]);
+        }
+
+        #[test]
+        #[should_panic]
+        fn zero_step_size_should_panic() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let audio_data =
+                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+            audio_data.get_frames(4410, 0, None, None);
+        }
+
+        #[test]
+        #[should_panic(expected = "frame width must be non-negative")]
+        fn zero_frame_width_should_panic() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let audio_data =
+                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+            audio_data.get_frames(0, 4410, None, None);
+        }
+
+        #[test]
+        fn end_point_before_start_point_returns_no_frames() {
+            let samples = vec![0.0; 10000];
+
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: samples.len() as u32,
+                samples,
+            };
+
+            assert!(audio_data
+                .get_frames(1000, 1, Some(5000), Some(100))
+                .is_empty());
+            assert!(audio_data.get_frames(1000, 1, None, Some(999)).is_empty());
+            assert!(audio_data.get_frames(20000, 1000, None, None).is_empty());
+        }
+
+        #[test]
+        fn end_point_greater_than_number_of_samples_should_work_correctly() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let audio_data =
+                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+
+            // If the end point is greater than the number of samples, the frames
+            // should cover all the samples, as if no end point was set
+            assert_eq!(
+                10,
+                audio_data.get_frames(4410, 4410, None, Some(50000)).len()
+            );
+        }
+    }
+
+    mod frame_iter_tests {
+        use crate::audio_utils::AudioData;
+
+        fn audio_data() -> AudioData {
+            AudioData::from_raw_samples((0..1000).map(|i| i as f64).collect(), 44100)
+        }
+
+        #[test]
+        fn produces_same_frames_as_get_frames() {
+            let audio_data = audio_data();
+
+            for (frame_width, step_size, start, end) in [
+                (100, 50, None, None),
+                (128, 100, Some(10), Some(900)),
+                (1000, 1, None, None),
+                (1001, 1, None, None),
+                (10, 7, Some(995), None),
+            ] {
+                assert_eq!(
+                    audio_data.get_frames(frame_width, step_size, start, end),
+                    audio_data
+                        .frame_iter(frame_width, step_size, start, end)
+                        .collect::<Vec<_>>()
+                );
+            }
+        }
+
+        #[test]
+        fn size_hint_is_number_of_remaining_frames() {
+            let audio_data = audio_data();
+            let mut frames = audio_data.frame_iter(100, 50, None, None);
+
+            assert_eq!((19, Some(19)), frames.size_hint());
+            frames.next();
+            assert_eq!((18, Some(18)), frames.size_hint());
+        }
+    }
+
+    mod get_windowed_frames_tests {
+        use crate::audio_utils::{AudioData, WindowFunction};
+
+        fn constant_audio() -> AudioData {
+            AudioData::from_raw_samples(vec![0.5; 4410], 44100)
+        }
+
+        #[test]
+        fn hann_window_has_zero_first_and_last_samples() {
+            let mut filepath = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+
+            for audio_data in [audio_data, constant_audio()] {
+                let frames =
+                    audio_data.get_windowed_frames(1024, 512, WindowFunction::Hann, None, None);
+
+                assert!(!frames.is_empty());
+                for frame in frames {
+                    assert!(frame.samples[0].abs() < 1e-12);
+                    assert!(frame.samples[1023].abs() < 1e-12);
+                }
+            }
+        }
+
+        #[test]
+        fn rectangular_window_matches_get_frames() {
+            let audio_data = constant_audio();
+
+            let windowed = audio_data.get_windowed_frames(
+                1000,
+                500,
+                WindowFunction::Rectangular,
+                Some(100),
+                None,
+            );
+            let unwindowed = audio_data.get_frames(1000, 500, Some(100), None);
+
+            assert_eq!(unwindowed, windowed);
+        }
+
+        #[test]
+        fn windows_peak_at_centre() {
+            let audio_data = constant_audio();
+
+            for window in [
+                WindowFunction::Hann,
+                WindowFunction::Hamming,
+                WindowFunction::Blackman,
+            ] {
+                let frame = &audio_data.get_windowed_frames(101, 101, window, None, None)[0];
+
+                assert!((frame.samples[50] - 0.5).abs() < 1e-12);
+            }
+        }
+
+        #[test]
+        fn window_edge_coefficients_are_correct() {
+            let hamming = WindowFunction::Hamming.coefficients(64);
+            assert!((hamming[0] - 0.08).abs() < 1e-12);
+            assert!((hamming[63] - 0.08).abs() < 1e-12);
+
+            let blackman = WindowFunction::Blackman.coefficients(64);
+            assert!(blackman[0].abs() < 1e-12);
+            assert!(blackman[63].abs() < 1e-12);
+
+            assert_eq!(vec![1.0], WindowFunction::Hann.coefficients(1));
+        }
+    }
+
+    mod get_frames_by_index_tests {
+        use crate::audio_utils::AudioData;
+
+        #[test]
+        fn returns_correct_number_of_frames() {
+            let samples = vec![0.0; 10];
+
+            let indices1 = vec![0, 2, 4, 6, 8];
+            let indices2 = vec![0, 4, 8];
+            let indices3 = vec![4, 8];
+
+            let audio_data = AudioData::from_raw_samples(samples, 44100);
+
+            assert_eq!(5, audio_data.get_frames_by_index(indices1).unwrap().len());
+            assert_eq!(3, audio_data.get_frames_by_index(indices2).unwrap().len());
+            assert_eq!(2, audio_data.get_frames_by_index(indices3).unwrap().len());
+        }
+
+        #[test]
+        fn no_indices_returns_empty_vector() {
+            let samples = vec![0.0; 10];
+
+            let indices: Vec<usize> = vec![];
+
+            let audio_data = AudioData::from_raw_samples(samples, 44100);
+
+            assert_eq!(0, audio_data.get_frames_by_index(indices).unwrap().len());
+        }
+
+        #[test]
+        fn index_out_of_bounds_returns_error() {
+            let samples = vec![0.0; 10];
+
+            let indices: Vec<usize> = vec![20];
+
+            let audio_data = AudioData::from_raw_samples(samples, 44100);
+
+            assert_eq!(
+                "index `20` is out of bounds",
+                audio_data
+                    .get_frames_by_index(indices)
+                    .unwrap_err()
+                    .to_string()
+            );
+        }
+
+        #[test]
+        fn duplicate_indices_returns_error() {
+            let samples = vec![0.0; 10];
+
+            let indices1: Vec<usize> = vec![0, 0];
+            let indices2: Vec<usize> = vec![0, 1, 1];
+
+            let audio_data = AudioData::from_raw_samples(samples, 44100);
+
+            assert_eq!(
+                "duplicate index `0` at positions 0 and 1 in `indices`",
+                audio_data
+                    .get_frames_by_index(indices1)
+                    .unwrap_err()
+                    .to_string()
+            );
+            assert_eq!(
+                "duplicate index `1` at positions 1 and 2 in `indices`",
+                audio_data
+                    .get_frames_by_index(indices2)
+                    .unwrap_err()
+                    .to_string()
+            );
+        }
+
+        #[test]
+        fn indices_vector_must_be_sorted() {
+            let samples = vec![0.0; 10];
+
+            let indices: Vec<usize> = vec![3, 2, 1];
+
+            let audio_data = AudioData::from_raw_samples(samples, 44100);
+
+            assert_eq!(
+                "`indices` must be sorted in ascending order",
+                audio_data
+                    .get_frames_by_index(indices)
+                    .unwrap_err()
+                    .to_string()
+            )
+        }
+
+        #[test]
+        fn frames_contain_correct_samples() {
+            let samples: Vec<f64> = vec![0.0, 1.0, 2.0, 3.0, 4.0];
+
+            let indices1: Vec<usize> = vec![0, 1, 2, 3, 4];
+            let indices2: Vec<usize> = vec![0, 2, 4];
+
+            let audio_data = AudioData::from_raw_samples(samples, 44100);
+
+            let frames1 = audio_data.get_frames_by_index(indices1).unwrap();
+            let frames2 = audio_data.get_frames_by_index(indices2).unwrap();
+
+            assert_eq!(0.0, frames1[0].samples[0]);
+            assert_eq!(1.0, frames1[1].samples[0]);
+            assert_eq!(2.0, frames1[2].samples[0]);
+            assert_eq!(3.0, frames1[3].samples[0]);
+            assert_eq!(4.0, frames1[4].samples[0]);
+
+            assert_eq!(0.0, frames2[0].samples[0]);
+            assert_eq!(1.0, frames2[0].samples[1]);
+            assert_eq!(2.0, frames2[1].samples[0]);
+            assert_eq!(3.0, frames2[1].samples[1]);
+            assert_eq!(4.0, frames2[2].samples[0]);
+        }
+    }
+
+    mod chunk_iter_tests {
+        use crate::audio_utils::AudioData;
+
+        #[test]
+        fn chunks_cover_all_samples() {
+            let samples: Vec<f64> = (0..10).map(|x| x as f64).collect();
+
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: samples.len() as u32,
+                samples,
+            };
+
+            let chunks: Vec<&[f64]> = audio_data.chunk_iter(4).collect();
+
+            assert_eq!(3, chunks.len());
+            assert_eq!(&[0.0, 1.0, 2.0, 3.0], chunks[0]);
+            assert_eq!(&[4.0, 5.0, 6.0, 7.0], chunks[1]);
+            assert_eq!(&[8.0, 9.0], chunks[2]);
+        }
+
+        #[test]
+        #[should_panic(expected = "chunk size must be non-zero")]
+        fn zero_chunk_size_should_panic() {
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: 1,
+                samples: vec![0.0],
+            };
+
+            let _ = audio_data.chunk_iter(0);
+        }
+    }
+
+    mod split_into_n_equal_parts_tests {
+        use crate::audio_utils::AudioData;
+
+        #[test]
+        fn parts_contain_all_samples() {
+            let samples: Vec<f64> = (0..10).map(|x| x as f64).collect();
+
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: samples.len() as u32,
+                samples,
+            };
+
+            for n in 1..=10 {
+                let parts = audio_data.split_into_n_equal_parts(n);
+
+                assert_eq!(n, parts.len());
+                assert_eq!(
+                    audio_data.samples.len(),
+                    parts.iter().map(|p| p.samples.len()).sum::<usize>()
+                );
+                assert_eq!(
+                    audio_data.samples,
+                    parts
+                        .into_iter()
+                        .flat_map(|p| p.samples)
+                        .collect::<Vec<f64>>()
+                );
+            }
+        }
+
+        #[test]
+        fn part_lengths_are_roughly_equal() {
+            let samples = vec![0.0; 10];
+
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: samples.len() as u32,
+                samples,
+            };
+
+            let parts = audio_data.split_into_n_equal_parts(4);
+
+            assert_eq!(
+                vec![3, 3, 2, 2],
+                parts
+                    .iter()
+                    .map(|p| p.samples.len())
+                    .collect::<Vec<usize>>()
+            );
+            assert!(parts.iter().all(|p| p.sample_rate == 44100));
+            assert!(parts.iter().all(|p| p.duration as usize == p.samples.len()));
+        }
+
+        #[test]
+        fn more_parts_than_samples_pads_with_empty_parts() {
+            let samples = vec![1.0, 2.0, 3.0];
+
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: samples.len() as u32,
+                samples,
+            };
+
+            let parts = audio_data.split_into_n_equal_parts(5);
+
+            assert_eq!(5, parts.len());
+            assert_eq!(
+                audio_data.samples.len(),
+                parts.iter().map(|p| p.samples.len()).sum::<usize>()
+            );
+            assert_eq!(
+                vec![1, 1, 1, 0, 0],
+                parts
+                    .iter()
+                    .map(|p| p.samples.len())
+                    .collect::<Vec<usize>>()
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "number of parts must be non-zero")]
+        fn zero_parts_should_panic() {
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: 1,
+                samples: vec![0.0],
+            };
+
+            audio_data.split_into_n_equal_parts(0);
+        }
+    }
+
+    mod detect_bpm_from_onsets_tests {
+        use crate::onset_detection::{
+            ComplexDomainDetector, HfcDetector, OnsetDetector, SimpleEnvelopeDetector,
+            SpectralFluxDetector,
+        };
+        use crate::test_utils::click_track;
+
+        #[test]
+        fn detects_tempo_of_click_tracks() {
+            for bpm in [120.0, 180.0] {
+                let detected_bpm = click_track(bpm, 16, 0.01)
+                    .detect_bpm_from_onsets(&SimpleEnvelopeDetector::default())
+                    .expect("Expected a tempo to be detected");
+
+                assert!(
+                    (detected_bpm - bpm).abs() < 2.0,
+                    "expected {bpm} BPM, detected {detected_bpm} BPM"
+                );
+            }
+        }
+
+        #[test]
+        fn detects_tempo_with_any_onset_detector() {
+            let detectors: [Box<dyn OnsetDetector>; 3] = [
+                Box::new(SpectralFluxDetector::default()),
+                Box::new(HfcDetector::default()),
+                Box::new(ComplexDomainDetector::default()),
+            ];
+
+            for detector in detectors {
+                let detected_bpm = click_track(120.0, 16, 0.01)
+                    .detect_bpm_from_onsets(detector.as_ref())
+                    .expect("Expected a tempo to be detected");
+
+                assert!(
+                    (detected_bpm - 120.0).abs() < 2.0,
+                    "detected {detected_bpm} BPM"
+                );
+            }
+        }
+
+        #[test]
+        fn too_few_onsets_returns_none() {
+            assert_eq!(
+                None,
+                click_track(120.0, 3, 0.01)
+                    .detect_bpm_from_onsets(&SimpleEnvelopeDetector::default())
+            );
+        }
+    }
+
+    mod peak_sample_tests {
+        use crate::audio_utils::Frame;
+
+        #[test]
+        fn peak_sample_works_correctly() {
+            let frame = Frame {
+                start_pos: 100,
+                samples: vec![0.1, -0.5, 0.3, -0.9, 0.8],
+            };
+
+            assert_eq!(Some((3, -0.9)), frame.peak_sample());
+        }
+
+        #[test]
+        fn first_of_equal_peaks_is_returned() {
+            let frame = Frame {
+                start_pos: 0,
+                samples: vec![0.0, 0.5, -0.5, 0.5],
+            };
+
+            assert_eq!(Some((1, 0.5)), frame.peak_sample());
+        }
+
+        #[test]
+        fn empty_frame_has_no_peak_sample() {
+            let frame = Frame {
+                start_pos: 0,
+                samples: vec![],
+            };
+
+            assert_eq!(None, frame.peak_sample());
+        }
+    }
+
+    mod apply_window_tests {
+        use crate::audio_utils::{AudioData, Frame, WindowFunction};
+
+        fn frame() -> Frame {
+            Frame {
+                start_pos: 100,
+                samples: vec![0.5; 64],
+            }
+        }
+
+        #[test]
+        fn hann_window_is_zero_at_endpoints() {
+            let windowed = frame().apply_window(&WindowFunction::Hann);
+
+            assert_eq!(100, windowed.start_pos);
+            assert_eq!(64, windowed.samples.len());
+            assert_eq!(0.0, windowed.samples[0]);
+            assert_eq!(0.0, windowed.samples[63]);
+        }
+
+        #[test]
+        fn rectangular_window_leaves_frame_unchanged() {
+            assert_eq!(frame(), frame().apply_window(&WindowFunction::Rectangular));
+        }
+
+        #[test]
+        fn matches_get_windowed_frames() {
+            let audio_data =
+                AudioData::from_raw_samples((0..256).map(|i| (i as f64).sin()).collect(), 44100);
+
+            let frames = audio_data.get_frames(64, 32, None, None);
+            let windowed_frames =
+                audio_data.get_windowed_frames(64, 32, WindowFunction::Blackman, None, None);
+
+            for (frame, windowed) in frames.iter().zip(windowed_frames.iter()) {
+                assert_eq!(*windowed, frame.apply_window(&WindowFunction::Blackman));
+            }
+        }
+    }
+
+    mod frequency_bins_tests {
+        use crate::audio_utils::Frame;
+
+        fn frame(len: usize) -> Frame {
+            Frame {
+                start_pos: 0,
+                samples: vec![0.0; len],
+            }
+        }
+
+        #[test]
+        fn last_bin_is_nyquist_frequency() {
+            let bins = frame(4096).frequency_bins(44100);
+
+            assert_eq!(2049, bins.len());
+            assert_eq!(0.0, bins[0]);
+            assert_eq!(22050.0, bins[2048]);
+        }
+
+        #[test]
+        fn bins_are_evenly_spaced() {
+            let bins = frame(100).frequency_bins(1000);
+
+            assert_eq!(51, bins.len());
+            for (k, bin) in bins.iter().enumerate() {
+                assert_eq!(10.0 * k as f64, *bin);
+            }
+        }
+
+        #[test]
+        fn odd_frame_length_excludes_nyquist_frequency() {
+            let bins = frame(5).frequency_bins(10);
+
+            assert_eq!(vec![0.0, 2.0, 4.0], bins);
+        }
+
+        #[test]
+        fn empty_frame_has_no_bins() {
+            assert!(frame(0).frequency_bins(44100).is_empty());
+        }
+    }
+
+    mod zero_crossing_rate_per_second_tests {
+        use crate::audio_utils::AudioData;
+
+        #[test]
+        fn returns_one_value_per_second() {
+            let mut filepath = std::path::PathBuf::new();
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let audio_data =
+                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+            let zcr = audio_data.zero_crossing_rate_per_second();
+
+            assert_eq!(1, zcr.len());
+            // A 440 Hz sine crosses zero twice per period
+            assert!((zcr[0] - 880.0).abs() <= 2.0);
+        }
+
+        #[test]
+        fn partial_final_second_is_included() {
+            // 2.5 seconds of a signal alternating sign every sample, followed by
+            // a constant signal in the final half second
+            let sample_rate = 100;
+            let mut samples: Vec<f64> = (0..200)
+                .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+                .collect();
+            samples.extend(vec![1.0; 50]);
+
+            let audio_data = AudioData {
+                sample_rate,
+                duration: samples.len() as u32,
+                samples,
+            };
+            let zcr = audio_data.zero_crossing_rate_per_second();
+
+            assert_eq!(3, zcr.len());
+            assert_eq!(99.0, zcr[0]);
+            assert_eq!(100.0, zcr[1]);
+            // Only the crossing at the start of the final window is counted,
+            // which is 1 crossing in half a second
+            assert_eq!(2.0, zcr[2]);
+        }
+
+        #[test]
+        fn empty_audio_returns_empty_vector() {
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: 0,
+                samples: vec![],
+            };
+
+            assert!(audio_data.zero_crossing_rate_per_second().is_empty());
+        }
+    }
+
+    mod detect_key_tests {
+        use crate::audio_utils::AudioData;
+        use crate::transcription::{Mode, NoteName, TranscriptionConfig};
+        use std::path::PathBuf;
+
+        #[test]
+        fn detects_key_of_c_major_scale() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/piano_C_Major_scale.wav");
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+
+            assert_eq!(
+                Some((NoteName::C(0), Mode::Major)),
+                audio_data.detect_key(&TranscriptionConfig::default())
+            );
+        }
+
+        #[test]
+        fn config_is_used_to_find_notes() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/piano_C_Major_scale.wav");
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+
+            // Every frame is quieter than this, so no notes are detected
+            let config = TranscriptionConfig {
+                silence_rms_ratio: 100.0,
+                ..TranscriptionConfig::default()
+            };
+
+            assert_eq!(None, audio_data.detect_key(&config));
+        }
+
+        #[test]
+        fn silent_audio_has_no_key() {
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: 44100,
+                samples: vec![0.0; 44100],
+            };
+
+            assert_eq!(None, audio_data.detect_key(&TranscriptionConfig::default()));
+        }
+    }
+
+    mod to_chromagram_tests {
+        use crate::audio_utils::AudioData;
+        use crate::test_utils::sine;
+
+        fn peak_bin(chroma: &[f64; 12]) -> usize {
+            (0..12)
+                .max_by(|a, b| chroma[*a].total_cmp(&chroma[*b]))
+                .unwrap()
+        }
+
+        #[test]
+        fn returns_one_chroma_vector_per_frame() {
+            assert_eq!(
+                20,
+                sine(440.0, 44100, 44100).to_chromagram(2048, 4096).len()
+            );
+        }
+
+        #[test]
+        fn c4_sine_has_peak_in_c_bin() {
+            let chromagram = sine(261.63, 44100, 44100).to_chromagram(2048, 4096);
+
+            assert!(chromagram.iter().all(|chroma| peak_bin(chroma) == 0));
+        }
+
+        #[test]
+        fn a4_sine_has_peak_in_a_bin() {
+            let chromagram = sine(440.0, 44100, 44100).to_chromagram(2048, 4096);
+
+            assert!(chromagram.iter().all(|chroma| peak_bin(chroma) == 9));
+        }
+
+        #[test]
+        fn frames_have_unit_norm() {
+            for chroma in sine(329.63, 44100, 44100).to_chromagram(2048, 4096) {
+                let norm = chroma.iter().map(|x| x * x).sum::<f64>().sqrt();
+                assert!((norm - 1.0).abs() < 1e-9);
+            }
+        }
+
+        #[test]
+        fn silent_frames_are_all_zeros() {
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: 8192,
+                samples: vec![0.0; 8192],
+            };
+
+            for chroma in audio_data.to_chromagram(1024, 4096) {
+                assert_eq!([0.0; 12], chroma);
+            }
+        }
+    }
+
+    mod channel_phase_difference_tests {
+        use crate::audio_utils::AudioData;
+
+        fn signal() -> Vec<f64> {
+            (0..1024)
+                .map(|i| 0.5 + 0.5 * (2.0 * std::f64::consts::PI * i as f64 / 64.0).sin())
+                .collect()
+        }
+
+        #[test]
+        fn returns_one_value_per_bin() {
+            let left = signal();
+
+            assert_eq!(
+                513,
+                AudioData::channel_phase_difference(&left, &left, 44100).len()
+            );
+        }
+
+        #[test]
+        fn identical_channels_are_in_phase() {
+            let left = signal();
+            let phase_difference = AudioData::channel_phase_difference(&left, &left, 44100);
+
+            assert!(phase_difference.iter().all(|p| p.abs() < 1e-9));
+        }
+
+        #[test]
+        fn inverted_channel_is_out_of_phase_at_dc() {
+            let left = signal();
+            let right: Vec<f64> = left.iter().map(|x| -x).collect();
+            let phase_difference = AudioData::channel_phase_difference(&left, &right, 44100);
+
+            assert!((phase_difference[0] - 180.0).abs() < 1e-9);
+            assert!((phase_difference[16] - 180.0).abs() < 1e-9);
+        }
+
+        #[test]
+        fn hard_left_signal_has_zero_phase_difference() {
+            let left = signal();
+            let right = vec![0.0; left.len()];
+            let phase_difference = AudioData::channel_phase_difference(&left, &right, 44100);
+
+            assert_eq!(0.0, phase_difference[0]);
+        }
+
+        #[test]
+        fn hard_right_signal_is_out_of_phase_at_dc() {
+            let right = signal();
+            let left = vec![0.0; right.len()];
+            let phase_difference = AudioData::channel_phase_difference(&left, &right, 44100);
+
+            assert_eq!(180.0, phase_difference[0]);
+        }
+
+        #[test]
+        #[should_panic(expected = "left and right channels must have the same length")]
+        fn different_channel_lengths_should_panic() {
+            AudioData::channel_phase_difference(&[0.0, 1.0], &[0.0], 44100);
+        }
+    }
+
+    mod frequency_domain_tests {
+        use crate::audio_utils::AudioData;
+        use crate::test_utils::sine;
+
+        #[test]
+        fn spectrum_is_zero_padded_to_power_of_two() {
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: 1000,
+                samples: vec![0.5; 1000],
+            };
+
+            let spectrum = audio_data.to_frequency_domain();
+
+            assert_eq!(1024, spectrum.len());
+            // The DC bin contains the sum of the samples
+            assert!((spectrum[0].re - 500.0).abs() < 1e-9);
+        }
+
+        #[test]
+        fn round_trip_preserves_samples() {
+            let audio_data = sine(440.0, 44100, 1000);
+
+            let round_trip =
+                AudioData::from_frequency_domain(audio_data.to_frequency_domain(), 44100);
+
+            assert_eq!(44100, round_trip.sample_rate);
+            assert_eq!(1024, round_trip.samples.len());
+            assert!(audio_data
+                .samples
+                .iter()
+                .zip(round_trip.samples.iter())
+                .all(|(a, b)| (a - b).abs() < 1e-12));
+            assert!(round_trip.samples[1000..].iter().all(|x| x.abs() < 1e-12));
+        }
+    }
+
+    mod detect_transients_tests {
+        use crate::audio_utils::AudioData;
+        use crate::test_utils::sine_samples;
+
+        /// Creates a drum-like hit, starting at its peak amplitude and
+        /// decaying exponentially
+        fn drum_hit(length: usize) -> Vec<f64> {
+            (0..length)
+                .map(|i| {
+                    let t = i as f64 / 44100.0;
+                    (-t / 0.05).exp() * (2.0 * std::f64::consts::PI * 150.0 * t).cos()
+                })
+                .collect()
+        }
+
+        #[test]
+        fn detects_drum_hits() {
+            let mut samples = vec![0.0; 10000];
+            samples.extend(drum_hit(20000));
+            samples.extend(drum_hit(20000));
+
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: samples.len() as u32,
+                samples,
+            };
+
+            let transients = audio_data.detect_transients(0.2, 4);
+
+            assert_eq!(2, transients.len(), "transients at {:?}", transients);
+            assert!(transients[0].abs_diff(10000) <= 16);
+            assert!(transients[1].abs_diff(30000) <= 16);
+        }
+
+        #[test]
+        fn steady_tone_has_no_transients() {
+            let samples: Vec<f64> = sine_samples(440.0, 44100, 44100)
+                .iter()
+                .map(|x| 0.5 * x)
+                .collect();
+
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: samples.len() as u32,
+                samples,
+            };
+
+            assert!(audio_data.detect_transients(0.2, 4).is_empty());
+        }
+    }
+
+    mod dither_tests {
+        use crate::audio_utils::AudioData;
+
+        fn silence() -> AudioData {
+            AudioData {
+                sample_rate: 44100,
+                duration: 44100,
+                samples: vec![0.0; 44100],
+            }
+        }
+
+        #[test]
+        fn dither_noise_is_within_one_lsb() {
+            let lsb = 1.0 / 2.0f64.powi(15);
+            let dithered = silence().dither(16);
+
+            assert!(dithered.samples.iter().all(|x| x.abs() <= lsb));
+            assert!(dithered.samples.iter().any(|x| *x != 0.0));
+        }
+
+        #[test]
+        fn dither_noise_has_triangular_distribution() {
+            let lsb = 1.0 / 2.0f64.powi(7);
+            let dithered = silence().dither(8);
+            let n = dithered.samples.len() as f64;
+
+            let mean = dithered.samples.iter().sum::<f64>() / n;
+            let variance = dithered.samples.iter().map(|x| x * x).sum::<f64>() / n;
+
+            assert!(mean.abs() < 0.01 * lsb);
+            // The variance of TPDF noise spanning one LSB either side of zero
+            // is LSB^2 / 6
+            assert!((variance / (lsb * lsb / 6.0) - 1.0).abs() < 0.05);
+        }
+
+        #[test]
+        fn dither_is_reproducible() {
+            assert_eq!(silence().dither(16).samples, silence().dither(16).samples);
+        }
+
+        #[test]
+        fn dither_preserves_audio_properties() {
+            let dithered = silence().dither(24);
+
+            assert_eq!(44100, dithered.sample_rate);
+            assert_eq!(44100, dithered.duration);
+            assert_eq!(44100, dithered.samples.len());
+        }
+    }
+
+    mod resample_tests {
+        use crate::audio_utils::AudioData;
+        use crate::pitch_detection::{Mpm, PitchDetector};
+        use std::path::PathBuf;
+
+        #[test]
+        fn upsampled_sine_has_correct_pitch() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/sine_440Hz_8000samples_s16bit_8000Hz_mono.wav");
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+
+            let resampled = audio_data.resample(44100);
+
+            assert_eq!(44100, resampled.sample_rate);
+            assert_eq!(44100, resampled.duration);
+            assert_eq!(44100, resampled.samples.len());
+
+            let pitch = Mpm::new(0.7, 44100)
+                .get_pitch(resampled.samples[20000..24096].to_vec())
+                .unwrap();
+            assert!((pitch - 440.0).abs() < 2.0, "detected {pitch} Hz");
+        }
+
+        #[test]
+        #[should_panic(expected = "sample rate must be non-zero")]
+        fn zero_sample_rate_should_panic() {
+            AudioData::from_raw_samples(vec![0.0; 10], 44100).resample(0);
+        }
+    }
+
+    mod pre_emphasis_tests {
+        use crate::audio_utils::AudioData;
+        use crate::errors::FilterError;
+        use rand::{rngs::StdRng, Rng, SeedableRng};
 
-            let audio_data_signed_24 =
-                AudioData::read_wav_file(filepath_signed_24).expect("Expected valid wav file data");
+        fn white_noise() -> AudioData {
+            let mut rng = StdRng::seed_from_u64(0);
+            AudioData::from_raw_samples(
+                (0..8192).map(|_| rng.gen_range(-0.5..0.5)).collect(),
+                44100,
+            )
+        }
 
-            let mut filepath_float_32 = std::path::PathBuf::new();
-            filepath_float_32
-                .push("../resources/test/sine_440Hz_44100samples_f32bit_44100Hz_mono.wav");
+        /// Returns the ratio of the RMS magnitude of the upper half of the
+        /// spectrum to that of the lower half
+        fn high_to_low_ratio(audio_data: &AudioData) -> f64 {
+            let spectrum = audio_data.to_frequency_domain();
+            let half = spectrum.len() / 2;
+            let rms = |bins: &[rustfft::num_complex::Complex<f64>]| {
+                (bins.iter().map(|x| x.norm_sqr()).sum::<f64>() / bins.len() as f64).sqrt()
+            };
+
+            rms(&spectrum[half / 2..half]) / rms(&spectrum[..half / 2])
+        }
+
+        #[test]
+        fn boosts_high_frequencies_of_white_noise() {
+            let noise = white_noise();
+            let emphasised = noise.apply_pre_emphasis(0.97).unwrap();
+
+            assert!(high_to_low_ratio(&emphasised) > 2.0 * high_to_low_ratio(&noise));
+        }
+
+        #[test]
+        fn filter_is_applied_correctly() {
+            let audio_data = AudioData::from_raw_samples(vec![1.0, 0.5, -0.5, 0.0], 8000);
+
+            let emphasised = audio_data.apply_pre_emphasis(0.5).unwrap();
+
+            assert_eq!(vec![1.0, 0.0, -0.75, 0.25], emphasised.samples);
+            assert_eq!(audio_data.sample_rate, emphasised.sample_rate);
+            assert_eq!(audio_data.duration, emphasised.duration);
+        }
+
+        #[test]
+        fn zero_alpha_leaves_samples_unchanged() {
+            let noise = white_noise();
 
-            let audio_data_float_32 =
-                AudioData::read_wav_file(filepath_float_32).expect("Expected valid wav file data");
             assert_eq!(
-                audio_data_float_32.sample_rate,
-                audio_data_signed_16.sample_rate
+                noise.samples,
+                noise.apply_pre_emphasis(0.0).unwrap().samples
+            );
+        }
+
+        #[test]
+        fn alpha_out_of_range_returns_error() {
+            for alpha in [-0.1, 1.0, 1.5] {
+                let error = white_noise().apply_pre_emphasis(alpha).err().unwrap();
+
+                assert_eq!(
+                    Some(&FilterError::InvalidPreEmphasisCoefficient(alpha)),
+                    error.downcast_ref::<FilterError>()
+                );
+            }
+        }
+    }
+
+    mod band_pass_filter_tests {
+        use crate::audio_utils::AudioData;
+        use crate::test_utils::sine_samples;
+
+        /// Amplitude of the component of the samples with the given frequency
+        fn amplitude(samples: &[f64], frequency: f64) -> f64 {
+            let (re, im) = samples
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(re, im), (i, x)| {
+                    let phase = 2.0 * std::f64::consts::PI * frequency * i as f64 / 44100.0;
+                    (re + x * phase.cos(), im + x * phase.sin())
+                });
+
+            2.0 * (re * re + im * im).sqrt() / samples.len() as f64
+        }
+
+        #[test]
+        fn frequencies_outside_range_are_attenuated() {
+            let samples = sine_samples(100.0, 44100, 44100)
+                .iter()
+                .zip(sine_samples(1000.0, 44100, 44100))
+                .map(|(x, y)| 0.5 * (x + y))
+                .collect();
+            let audio_data = AudioData::from_raw_samples(samples, 44100);
+
+            let filtered = audio_data.band_pass_filter(0.0, 500.0).unwrap();
+
+            assert!((amplitude(&filtered.samples, 100.0) - 0.5).abs() < 0.005);
+            let attenuation_db = 20.0
+                * (amplitude(&filtered.samples, 1000.0) / amplitude(&audio_data.samples, 1000.0))
+                    .log10();
+            assert!(
+                attenuation_db < -40.0,
+                "attenuation was {attenuation_db} dB"
             );
+        }
+
+        #[test]
+        fn frequencies_inside_range_are_unchanged() {
+            let audio_data = AudioData::from_raw_samples(sine_samples(440.0, 44100, 44100), 44100);
+
+            let filtered = audio_data.band_pass_filter(200.0, 1000.0).unwrap();
+
+            for (x, y) in audio_data.samples.iter().zip(&filtered.samples) {
+                assert!((x - y).abs() < 1e-9);
+            }
+        }
+
+        #[test]
+        fn cutoff_above_nyquist_returns_error() {
+            let audio_data = AudioData::from_raw_samples(sine_samples(440.0, 44100, 44100), 44100);
+
+            let error = audio_data.band_pass_filter(100.0, 30000.0).unwrap_err();
+
             assert_eq!(
-                audio_data_float_32.sample_rate,
-                audio_data_signed_24.sample_rate
+                "cutoff frequency `30000` Hz is above the Nyquist frequency `22050` Hz",
+                error.to_string()
             );
-            assert_eq!(audio_data_float_32.duration, audio_data_signed_16.duration);
-            assert_eq!(audio_data_float_32.duration, audio_data_signed_24.duration);
         }
 
         #[test]
-        fn different_sample_rates_read_correctly() {
-            let mut filepath_8000 = std::path::PathBuf::new();
-            filepath_8000.push("../resources/test/sine_440Hz_8000samples_s16bit_8000Hz_mono.wav");
+        fn invalid_pass_band_returns_error() {
+            let audio_data = AudioData::from_raw_samples(sine_samples(440.0, 44100, 44100), 44100);
 
-            let audio_data_8000 =
-                AudioData::read_wav_file(filepath_8000).expect("Expected valid wav file data");
+            assert!(audio_data.band_pass_filter(500.0, 100.0).is_err());
+            assert!(audio_data.band_pass_filter(-10.0, 100.0).is_err());
+        }
 
-            assert_eq!(8000, audio_data_8000.sample_rate);
+        #[test]
+        fn empty_audio_is_unchanged() {
+            let audio_data = AudioData::from_raw_samples(vec![], 44100);
 
-            let mut filepath_22050 = std::path::PathBuf::new();
-            filepath_22050
-                .push("../resources/test/sine_440Hz_22050samples_s16bit_22050Hz_mono.wav");
+            assert_eq!(audio_data, audio_data.band_pass_filter(0.0, 500.0).unwrap());
+        }
+    }
 
-            let audio_data_22050 =
-                AudioData::read_wav_file(filepath_22050).expect("Expected valid wav file data");
+    mod remove_dc_offset_tests {
+        use crate::audio_utils::AudioData;
+        use crate::pitch_detection::{Mpm, PitchDetector};
+        use crate::test_utils::sine_samples;
 
-            assert_eq!(22050, audio_data_22050.sample_rate);
+        fn sine(offset: f64) -> AudioData {
+            AudioData::from_raw_samples(
+                sine_samples(440.0, 44100, 44100)
+                    .iter()
+                    .map(|x| 0.5 * x + offset)
+                    .collect(),
+                44100,
+            )
+        }
 
-            let mut filepath_44100 = std::path::PathBuf::new();
-            filepath_44100
-                .push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+        fn mean(samples: &[f64]) -> f64 {
+            samples.iter().sum::<f64>() / samples.len() as f64
+        }
 
-            let audio_data_44100 =
-                AudioData::read_wav_file(filepath_44100).expect("Expected valid wav file data");
+        #[test]
+        fn offset_signal_has_zero_mean() {
+            let corrected = sine(0.1).remove_dc_offset();
 
-            assert_eq!(44100, audio_data_44100.sample_rate);
-            // Duration (in seconds) should be the same for all 3 files
-            // duration in seconds = duration in samples / sample rate
+            assert!(mean(&corrected.samples).abs() < 1e-12);
+            assert_eq!(44100, corrected.duration);
+            assert_eq!(44100, corrected.sample_rate);
+        }
+
+        #[test]
+        fn pitch_of_corrected_signal_matches_original() {
+            let original = sine(0.0);
+            let corrected = sine(0.1).remove_dc_offset();
+
+            let mpm = Mpm::new(0.7, 44100);
+            let original_pitch = mpm.get_pitch(original.samples[..4096].to_vec()).unwrap();
+            let corrected_pitch = mpm.get_pitch(corrected.samples[..4096].to_vec()).unwrap();
+
+            assert!((original_pitch - corrected_pitch).abs() < 0.1);
+        }
+
+        #[test]
+        fn centred_signal_is_unchanged() {
+            let audio_data = AudioData::from_raw_samples(vec![0.5, -0.5, 0.25, -0.25], 8000);
+
+            assert_eq!(audio_data.samples, audio_data.remove_dc_offset().samples);
+        }
+
+        #[test]
+        fn empty_audio_is_unchanged() {
+            assert!(AudioData::from_raw_samples(vec![], 8000)
+                .remove_dc_offset()
+                .samples
+                .is_empty());
+        }
+    }
+
+    mod apply_gain_tests {
+        use crate::audio_utils::AudioData;
+
+        fn audio_data() -> AudioData {
+            AudioData::from_raw_samples(vec![0.0, 0.25, -0.4, 0.1], 44100)
+        }
+
+        #[test]
+        fn zero_gain_does_not_change_samples() {
+            assert_eq!(audio_data(), audio_data().apply_gain(0.0, true));
+        }
+
+        #[test]
+        fn six_decibels_approximately_doubles_amplitude() {
+            let gained = audio_data().apply_gain(6.0, true);
+
+            for (x, y) in audio_data().samples.iter().zip(&gained.samples) {
+                assert!((2.0 * x - y).abs() < 0.01);
+            }
+        }
+
+        #[test]
+        fn negative_infinity_gives_silence() {
+            let gained = audio_data().apply_gain(f64::NEG_INFINITY, true);
+
+            assert!(gained.samples.iter().all(|x| *x == 0.0));
+        }
+
+        #[test]
+        fn samples_are_clipped_only_if_requested() {
             assert_eq!(
-                audio_data_8000.duration / audio_data_8000.sample_rate,
-                audio_data_44100.duration / audio_data_44100.sample_rate
+                vec![0.0, 1.0, -1.0, 1.0],
+                audio_data().apply_gain(40.0, true).samples
+            );
+
+            let unclipped = audio_data().apply_gain(40.0, false);
+            assert!((unclipped.samples[2] + 40.0).abs() < 1e-9);
+        }
+    }
+
+    mod peak_normalization_tests {
+        use crate::audio_utils::AudioData;
+
+        #[test]
+        fn peak_is_scaled_to_one() {
+            let normalized =
+                AudioData::from_raw_samples(vec![0.0, 0.25, -0.5, 0.1], 44100).peak_normalization();
+
+            assert_eq!(vec![0.0, 0.5, -1.0, 0.2], normalized.samples);
+        }
+
+        #[test]
+        fn silence_is_unchanged() {
+            let silence = AudioData::from_raw_samples(vec![0.0; 10], 44100);
+
+            assert_eq!(silence, silence.peak_normalization());
+        }
+    }
+
+    mod fade_tests {
+        use crate::audio_utils::AudioData;
+
+        fn audio_data() -> AudioData {
+            AudioData::from_raw_samples(vec![0.5; 8], 44100)
+        }
+
+        #[test]
+        fn fade_in_ramps_up_from_zero() {
+            let faded = audio_data().fade_in(4);
+
+            assert_eq!(
+                vec![0.0, 0.125, 0.25, 0.375, 0.5, 0.5, 0.5, 0.5],
+                faded.samples
             );
+        }
+
+        #[test]
+        fn fade_out_ramps_down_to_zero() {
+            let faded = audio_data().fade_out(4);
+
             assert_eq!(
-                audio_data_22050.duration / audio_data_22050.sample_rate,
-                audio_data_44100.duration / audio_data_44100.sample_rate
+                vec![0.5, 0.5, 0.5, 0.5, 0.375, 0.25, 0.125, 0.0],
+                faded.samples
+            );
+        }
+
+        #[test]
+        fn zero_duration_does_not_change_samples() {
+            assert_eq!(audio_data(), audio_data().fade_in(0));
+            assert_eq!(audio_data(), audio_data().fade_out(0));
+        }
+
+        #[test]
+        fn fade_can_cover_all_samples() {
+            let faded = audio_data().fade_out(8);
+
+            assert_eq!(0.4375, faded.samples[0]);
+            assert_eq!(0.0, faded.samples[7]);
+        }
+
+        #[test]
+        #[should_panic(expected = "fade duration must not exceed the number of samples")]
+        fn fade_in_longer_than_audio_should_panic() {
+            audio_data().fade_in(9);
+        }
+
+        #[test]
+        #[should_panic(expected = "fade duration must not exceed the number of samples")]
+        fn fade_out_longer_than_audio_should_panic() {
+            audio_data().fade_out(9);
+        }
+    }
+
+    mod pcm_tests {
+        use crate::audio_utils::AudioData;
+
+        fn audio(samples: Vec<f64>) -> AudioData {
+            AudioData {
+                sample_rate: 44100,
+                duration: samples.len() as u32,
+                samples,
+            }
+        }
+
+        #[test]
+        fn f32_samples_are_converted_correctly() {
+            let pcm = audio(vec![0.0, 0.25, -0.5, 1.0]).to_pcm_f32();
+
+            assert_eq!(vec![0.0, 0.25, -0.5, 1.0], pcm);
+        }
+
+        #[test]
+        fn f32_samples_are_clipped() {
+            let pcm = audio(vec![1.5, -2.0, 0.5]).to_pcm_f32();
+
+            assert_eq!(vec![1.0, -1.0, 0.5], pcm);
+        }
+
+        #[test]
+        fn i16_samples_are_scaled_and_rounded() {
+            let pcm = audio(vec![0.0, 1.0, -1.0, 0.5, 0.6 / 32767.0, -0.6 / 32767.0]).to_pcm_i16();
+
+            assert_eq!(vec![0, 32767, -32767, 16384, 1, -1], pcm);
+        }
+
+        #[test]
+        fn i16_samples_are_clipped() {
+            let pcm = audio(vec![1.5, -2.0]).to_pcm_i16();
+
+            assert_eq!(vec![32767, -32767], pcm);
+        }
+    }
+
+    mod resample_to_match_tests {
+        use crate::audio_utils::AudioData;
+        use crate::test_utils::sine;
+
+        #[test]
+        fn matching_sample_rates_leave_audio_unchanged() {
+            let audio_data = sine(440.0, 44100, 1000);
+            let resampled = audio_data.resample_to_match(&sine(220.0, 44100, 10));
+
+            assert_eq!(44100, resampled.sample_rate);
+            assert_eq!(audio_data.samples, resampled.samples);
+        }
+
+        #[test]
+        fn upsampled_sine_matches_original_waveform() {
+            let audio_data = sine(440.0, 8000, 8000);
+            let resampled = audio_data.resample_to_match(&sine(440.0, 44100, 10));
+            let expected = sine(440.0, 44100, 44100);
+
+            assert_eq!(44100, resampled.sample_rate);
+            assert_eq!(44100, resampled.duration);
+            assert_eq!(44100, resampled.samples.len());
+
+            // Ignore the edges, where the interpolation window is truncated
+            for i in 1000..43100 {
+                assert!(
+                    (resampled.samples[i] - expected.samples[i]).abs() < 0.01,
+                    "sample {} differs",
+                    i
+                );
+            }
+        }
+
+        #[test]
+        fn downsampling_removes_frequencies_above_nyquist() {
+            // 6 kHz is above the Nyquist frequency at 8 kHz
+            let audio_data = sine(6000.0, 44100, 44100);
+            let resampled = audio_data.resample_to_match(&sine(440.0, 8000, 10));
+
+            assert_eq!(8000, resampled.samples.len());
+
+            let rms = AudioData::root_mean_square(resampled.samples[1000..7000].to_vec()).unwrap();
+            assert!(rms < 0.01, "rms was {}", rms);
+        }
+    }
+
+    mod cross_correlation_tests {
+        use crate::audio_utils::{AudioData, WindowFunction};
+        use crate::test_utils::sine_samples;
+
+        /// Creates a Hann-windowed burst of a 440 Hz sine
+        fn burst(sample_rate: u32, num_samples: usize) -> Vec<f64> {
+            sine_samples(440.0, sample_rate, num_samples)
+                .iter()
+                .zip(WindowFunction::Hann.coefficients(num_samples))
+                .map(|(x, w)| x * w)
+                .collect()
+        }
+
+        /// Returns the lag with the largest cross-correlation
+        fn best_lag(correlation: &[f64]) -> usize {
+            (0..correlation.len())
+                .max_by(|&a, &b| correlation[a].total_cmp(&correlation[b]))
+                .unwrap()
+        }
+
+        /// Places the burst 1000 samples into a longer recording
+        fn delayed_burst() -> AudioData {
+            let mut samples = vec![0.0; 1000];
+            samples.extend(burst(44100, 4410));
+            samples.extend(vec![0.0; 1000]);
+
+            AudioData::from_raw_samples(samples, 44100)
+        }
+
+        #[test]
+        fn delay_of_other_audio_is_found() {
+            let other = AudioData::from_raw_samples(burst(44100, 4410), 44100);
+
+            let correlation = delayed_burst().cross_correlation(&other);
+
+            assert_eq!(6410, correlation.len());
+            assert_eq!(1000, best_lag(&correlation));
+        }
+
+        #[test]
+        fn other_audio_is_resampled_to_match() {
+            let other = AudioData::from_raw_samples(burst(22050, 2205), 22050);
+
+            let correlation = delayed_burst().cross_correlation(&other);
+
+            assert_eq!(6410, correlation.len());
+            assert!(best_lag(&correlation).abs_diff(1000) <= 1);
+        }
+
+        #[test]
+        fn empty_audio_has_zero_correlation() {
+            let other = AudioData::from_raw_samples(vec![], 44100);
+
+            assert_eq!(vec![0.0; 6410], delayed_burst().cross_correlation(&other));
+            assert!(other.cross_correlation(&delayed_burst()).is_empty());
+        }
+    }
+
+    mod root_mean_square_tests {
+        use crate::audio_utils::{AudioData, Frame};
+
+        #[test]
+        fn root_mean_square_works_correctly() {
+            assert_eq!(0.0, AudioData::root_mean_square(vec![0.0]).unwrap());
+            assert_eq!(1.0, AudioData::root_mean_square(vec![1.0, 1.0]).unwrap());
+            assert_eq!(1.0, AudioData::root_mean_square(vec![-1.0, 1.0]).unwrap());
+            assert_eq!(
+                1.0,
+                AudioData::root_mean_square(vec![-1.0, 1.0, 1.0]).unwrap()
+            );
+            assert_eq!(
+                10.0,
+                AudioData::root_mean_square(vec![-10.0, -10.0]).unwrap()
+            );
+            assert!(
+                (5.0 * 10.0f64.sqrt())
+                    - AudioData::root_mean_square(vec![-10.0, 20.0])
+                        .unwrap()
+                        .abs()
+                    < 1e-12
             );
         }
 
         #[test]
-        #[should_panic(expected = "Expected valid wav file data")]
-        fn reading_non_wav_file_should_panic() {
-            let mut filepath = std::path::PathBuf::new();
-            filepath.push("../resources/test/README.md");
-
-            AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+        fn root_mean_square_of_empty_vector_is_none() {
+            assert_eq!(None, AudioData::root_mean_square(vec![]));
         }
-    }
-
-    /// Tests for AudioData methods
-    mod get_frames_tests {
-        use crate::audio_utils::AudioData;
 
         #[test]
-        fn returns_correct_number_of_frames() {
-            let mut filepath = std::path::PathBuf::new();
-            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
-
+        fn instance_methods_match_root_mean_square() {
             let audio_data =
-                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+                AudioData::from_raw_samples((0..1000).map(|i| (i as f64).sin()).collect(), 44100);
+            let frame = Frame {
+                start_pos: 0,
+                samples: audio_data.samples[100..300].to_vec(),
+            };
 
-            assert_eq!(10, audio_data.get_frames(4410, 4410, None, None).len());
-            // Expected number of frames is 19 since the 20th frame starting at
-            // sample 41895 would not have width 4410, as there are only 44100
-            // samples in total
-            assert_eq!(19, audio_data.get_frames(4410, 2205, None, None).len());
-            assert_eq!(20, audio_data.get_frames(2205, 2205, None, None).len());
-            assert_eq!(
-                audio_data.samples.len(),
-                audio_data.get_frames(1, 1, None, None).len()
-            );
-            assert_eq!(
-                10,
-                audio_data.get_frames(2205, 2205, Some(22050), None).len()
-            );
-            assert_eq!(
-                1,
-                audio_data.get_frames(2205, 2205, Some(41895), None).len()
-            );
-            assert_eq!(
-                10,
-                audio_data.get_frames(2205, 2205, None, Some(22050)).len()
-            );
-            assert_eq!(1, audio_data.get_frames(2205, 2205, None, Some(2205)).len());
             assert_eq!(
-                0,
-                audio_data
-                    .get_frames(2205, 2205, Some(22050), Some(22050))
-                    .len()
+                AudioData::root_mean_square(audio_data.samples.clone()),
+                audio_data.rms()
             );
             assert_eq!(
-                0,
-                audio_data
-                    .get_frames(2205, 2205, Some(22051), Some(22050))
-                    .len()
+                AudioData::root_mean_square(frame.samples.clone()),
+                frame.rms()
             );
         }
 
         #[test]
-        fn overlapping_frame_contents_are_consistent() {
-            let mut filepath = std::path::PathBuf::new();
-            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
-
-            let audio_data =
-                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
-            let frames = audio_data.get_frames(4410, 2205, None, None);
+        fn empty_audio_and_frame_have_no_rms() {
+            let frame = Frame {
+                start_pos: 0,
+                samples: vec![],
+            };
 
-            assert_eq!(frames[0].samples[2205], frames[1].samples[0]);
-            assert_eq!(frames[0].samples[4409], frames[1].samples[2204]);
-            assert_eq!(frames[1].samples[2205], frames[2].samples[0]);
-            assert_eq!(frames[1].samples[4409], frames[2].samples[2204]);
+            assert_eq!(None, AudioData::from_raw_samples(vec![], 44100).rms());
+            assert_eq!(None, frame.rms());
         }
+    }
+
+    mod compute_spectrogram_tests {
+        use crate::audio_utils::{AudioData, WindowFunction};
+        use crate::test_utils::sine;
 
         #[test]
-        fn different_starting_point_frame_contents_are_consistent() {
-            let mut filepath = std::path::PathBuf::new();
-            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+        fn sine_peaks_at_its_frequency() {
+            let audio_data = sine(440.0, 44100, 44100);
 
-            let audio_data =
-                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
-            let frames_1 = audio_data.get_frames(4410, 2205, None, None);
-            let frames_2 = audio_data.get_frames(4410, 2205, Some(2205), None);
+            let spectrogram = audio_data.compute_spectrogram(2048, 1024, WindowFunction::Hann);
 
-            assert_eq!(frames_1[0].samples[2205], frames_2[0].samples[0]);
-            assert_eq!(frames_1[0].samples[4400], frames_2[0].samples[2195]);
-            assert_eq!(frames_1[1].samples[2205], frames_2[1].samples[0]);
-            assert_eq!(frames_1[1].samples[4400], frames_2[1].samples[2195]);
+            let bin_width = 44100.0 / 2048.0;
+            for spectrum in spectrogram {
+                let peak = (0..spectrum.len())
+                    .max_by(|a, b| spectrum[*a].total_cmp(&spectrum[*b]))
+                    .unwrap();
+
+                assert!((peak as f64 * bin_width - 440.0).abs() <= bin_width);
+            }
         }
 
         #[test]
-        #[should_panic]
-        fn zero_step_size_should_panic() {
-            let mut filepath = std::path::PathBuf::new();
-            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+        fn returns_non_redundant_bins_for_each_frame() {
+            let audio_data = AudioData::from_raw_samples(vec![0.0; 8192], 44100);
 
-            let audio_data =
-                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
-            audio_data.get_frames(4410, 0, None, None);
+            let spectrogram =
+                audio_data.compute_spectrogram(1024, 512, WindowFunction::Rectangular);
+
+            assert_eq!(
+                audio_data.get_frames(1024, 512, None, None).len(),
+                spectrogram.len()
+            );
+            assert!(spectrogram.iter().all(|spectrum| spectrum.len() == 513));
         }
+    }
+
+    mod complex_domain_deviation_tests {
+        use crate::test_utils::legato;
 
         #[test]
-        #[should_panic(expected = "frame width must be non-negative")]
-        fn zero_frame_width_should_panic() {
-            let mut filepath = std::path::PathBuf::new();
-            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+        fn steady_sine_is_predicted() {
+            let deviation = legato(0, 44100).complex_domain_deviation(1024, 512);
+            let first = deviation[0];
 
-            let audio_data =
-                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
-            audio_data.get_frames(0, 4410, None, None);
+            assert!(deviation[2..].iter().all(|x| *x < 0.01 * first));
         }
 
         #[test]
-        fn end_point_greater_than_number_of_samples_should_work_correctly() {
-            let mut filepath = std::path::PathBuf::new();
-            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+        fn frequency_change_increases_deviation() {
+            let deviation = legato(0, 22050).complex_domain_deviation(1024, 512);
 
-            let audio_data =
-                AudioData::read_wav_file(filepath).expect("Expected valid wav file data");
+            let (max_frame, _) = deviation[2..]
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap();
+            let max_frame = max_frame + 2;
 
-            // If the end point is greater than the number of samples, the frames
-            // should cover all the samples, as if no end point was set
-            assert_eq!(
-                10,
-                audio_data.get_frames(4410, 4410, None, Some(50000)).len()
-            );
+            // The frame containing the change is the one which starts
+            // within a frame width before it
+            assert!((22050 - 1024..22050).contains(&(max_frame * 512)));
         }
     }
 
-    mod get_frames_by_index_tests {
+    mod high_frequency_content_tests {
         use crate::audio_utils::AudioData;
+        use crate::test_utils::sine;
 
         #[test]
-        fn returns_correct_number_of_frames() {
-            let samples = vec![0.0; 10];
-
-            let indices1 = vec![0, 2, 4, 6, 8];
-            let indices2 = vec![0, 4, 8];
-            let indices3 = vec![4, 8];
-
-            let audio_data = AudioData {
-                sample_rate: 44100,
-                duration: samples.len() as u32,
-                samples: samples,
-            };
+        fn higher_frequencies_have_more_hfc() {
+            let low = sine(200.0, 44100, 8192).high_frequency_content(1024, 512);
+            let high = sine(4000.0, 44100, 8192).high_frequency_content(1024, 512);
 
-            assert_eq!(5, audio_data.get_frames_by_index(indices1).unwrap().len());
-            assert_eq!(3, audio_data.get_frames_by_index(indices2).unwrap().len());
-            assert_eq!(2, audio_data.get_frames_by_index(indices3).unwrap().len());
+            assert_eq!(15, low.len());
+            assert!(low.iter().zip(&high).all(|(l, h)| h > &(10.0 * l)));
         }
 
         #[test]
-        fn no_indices_returns_empty_vector() {
-            let samples = vec![0.0; 10];
+        fn silence_has_no_hfc() {
+            let silence = AudioData::from_raw_samples(vec![0.0; 4096], 44100);
 
-            let indices: Vec<usize> = vec![];
+            assert!(silence
+                .high_frequency_content(1024, 512)
+                .iter()
+                .all(|x| *x == 0.0));
+        }
+    }
 
-            let audio_data = AudioData {
-                sample_rate: 44100,
-                duration: samples.len() as u32,
-                samples: samples,
-            };
+    mod compute_mfcc_tests {
+        use crate::audio_utils::AudioData;
+        use crate::test_utils::sine_samples;
 
-            assert_eq!(0, audio_data.get_frames_by_index(indices).unwrap().len());
+        /// Creates a 440 Hz sine whose amplitude doubles every 4096 samples
+        fn rising_sine() -> AudioData {
+            AudioData::from_raw_samples(
+                sine_samples(440.0, 44100, 32768)
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| 0.01 * 2f64.powi(i as i32 / 4096) * x)
+                    .collect(),
+                44100,
+            )
         }
 
         #[test]
-        fn index_out_of_bounds_returns_error() {
-            let samples = vec![0.0; 10];
-
-            let indices: Vec<usize> = vec![20];
+        fn returns_n_mfcc_coefficients_per_frame() {
+            let audio_data = rising_sine();
 
-            let audio_data = AudioData {
-                sample_rate: 44100,
-                duration: samples.len() as u32,
-                samples: samples,
-            };
+            let mfcc = audio_data.compute_mfcc(2048, 1024, 40, 13);
 
             assert_eq!(
-                "index `20` is out of bounds",
-                audio_data
-                    .get_frames_by_index(indices)
-                    .unwrap_err()
-                    .to_string()
+                audio_data.get_frames(2048, 1024, None, None).len(),
+                mfcc.len()
             );
+            assert!(mfcc.iter().all(|coefficients| coefficients.len() == 13));
         }
 
         #[test]
-        fn duplicate_indices_returns_error() {
-            let samples = vec![0.0; 10];
-
-            let indices1: Vec<usize> = vec![0, 0];
-            let indices2: Vec<usize> = vec![0, 1, 1];
+        fn first_coefficient_increases_with_energy() {
+            let audio_data = rising_sine();
 
-            let audio_data = AudioData {
-                sample_rate: 44100,
-                duration: samples.len() as u32,
-                samples: samples,
-            };
+            // Frames which lie within a single amplitude step
+            let c0: Vec<f64> = audio_data
+                .compute_mfcc(2048, 4096, 40, 13)
+                .iter()
+                .map(|coefficients| coefficients[0])
+                .collect();
 
-            assert_eq!(
-                "duplicate index `0` at positions 0 and 1 in `indices`",
-                audio_data
-                    .get_frames_by_index(indices1)
-                    .unwrap_err()
-                    .to_string()
-            );
-            assert_eq!(
-                "duplicate index `1` at positions 1 and 2 in `indices`",
-                audio_data
-                    .get_frames_by_index(indices2)
-                    .unwrap_err()
-                    .to_string()
-            );
+            assert_eq!(8, c0.len());
+            assert!(c0.windows(2).all(|w| w[0] < w[1]));
         }
 
         #[test]
-        fn indices_vector_must_be_sorted() {
-            let samples = vec![0.0; 10];
+        #[should_panic(
+            expected = "number of coefficients must not be greater than the number of mel filters"
+        )]
+        fn more_coefficients_than_filters_should_panic() {
+            rising_sine().compute_mfcc(2048, 1024, 10, 13);
+        }
+    }
 
-            let indices: Vec<usize> = vec![3, 2, 1];
+    mod energy_envelope_tests {
+        use crate::audio_utils::AudioData;
+        use crate::test_utils::{sine, sine_samples};
 
-            let audio_data = AudioData {
-                sample_rate: 44100,
-                duration: samples.len() as u32,
-                samples: samples,
-            };
+        #[test]
+        fn sine_has_uniform_energy() {
+            let sine = sine(440.0, 44100, 44100);
 
-            assert_eq!(
-                "`indices` must be sorted in ascending order",
-                audio_data
-                    .get_frames_by_index(indices)
-                    .unwrap_err()
-                    .to_string()
-            )
+            let envelope = sine.energy_envelope(1024, 1024);
+
+            assert_eq!(43, envelope.len());
+            // The mean square of a sine with amplitude 1 is 0.5
+            assert!(envelope.iter().all(|energy| (energy - 0.5).abs() < 0.01));
         }
 
         #[test]
-        fn frames_contain_correct_samples() {
-            let samples: Vec<f64> = vec![0.0, 1.0, 2.0, 3.0, 4.0];
+        fn ramp_has_increasing_energy() {
+            let ramp = AudioData::from_raw_samples(
+                sine_samples(440.0, 44100, 44100)
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| i as f64 / 44100.0 * x)
+                    .collect(),
+                44100,
+            );
 
-            let indices1: Vec<usize> = vec![0, 1, 2, 3, 4];
-            let indices2: Vec<usize> = vec![0, 2, 4];
+            let envelope = ramp.energy_envelope(1024, 1024);
 
-            let audio_data = AudioData {
-                sample_rate: 44100,
-                duration: samples.len() as u32,
-                samples: samples,
-            };
+            assert!(envelope[0] < 1e-4);
+            assert!(envelope.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
 
-            let frames1 = audio_data.get_frames_by_index(indices1).unwrap();
-            let frames2 = audio_data.get_frames_by_index(indices2).unwrap();
+    mod zero_crossing_rate_tests {
+        use crate::audio_utils::AudioData;
+        use crate::test_utils::sine;
+        use rand::{rngs::StdRng, Rng, SeedableRng};
 
-            assert_eq!(0.0, frames1[0].samples[0]);
-            assert_eq!(1.0, frames1[1].samples[0]);
-            assert_eq!(2.0, frames1[2].samples[0]);
-            assert_eq!(3.0, frames1[3].samples[0]);
-            assert_eq!(4.0, frames1[4].samples[0]);
+        #[test]
+        fn sine_has_lower_rate_than_white_noise() {
+            let mut rng = StdRng::seed_from_u64(0);
+            let noise = AudioData::from_raw_samples(
+                (0..44100).map(|_| rng.gen_range(-1.0..1.0)).collect(),
+                44100,
+            );
+            let sine = sine(440.0, 44100, 44100);
 
-            assert_eq!(0.0, frames2[0].samples[0]);
-            assert_eq!(1.0, frames2[0].samples[1]);
-            assert_eq!(2.0, frames2[1].samples[0]);
-            assert_eq!(3.0, frames2[1].samples[1]);
-            assert_eq!(4.0, frames2[2].samples[0]);
+            let sine_rates = sine.zero_crossing_rate(1024, 512);
+            let noise_rates = noise.zero_crossing_rate(1024, 512);
+
+            assert_eq!(sine_rates.len(), noise_rates.len());
+            for (sine_rate, noise_rate) in sine_rates.iter().zip(noise_rates.iter()) {
+                assert!(sine_rate < noise_rate);
+            }
+        }
+
+        #[test]
+        fn sine_rate_is_twice_frequency_over_sample_rate() {
+            let sine = AudioData::from_raw_samples(
+                (0..44100)
+                    .map(|i| {
+                        (2.0 * std::f64::consts::PI * 441.0 * (i as f64 + 0.5) / 44100.0).sin()
+                    })
+                    .collect(),
+                44100,
+            );
+
+            // Each frame contains 10 periods, but the crossing at the end of
+            // the last period may fall after the final sample
+            for rate in sine.zero_crossing_rate(1000, 1000) {
+                assert!((rate - 0.02).abs() < 0.002, "rate was {rate}");
+            }
         }
     }
 
-    mod root_mean_square_tests {
+    mod spectral_flux_tests {
         use crate::audio_utils::AudioData;
+        use crate::test_utils::sine_burst;
 
         #[test]
-        fn root_mean_square_works_correctly() {
-            assert_eq!(0.0, AudioData::root_mean_square(vec![0.0]).unwrap());
-            assert_eq!(1.0, AudioData::root_mean_square(vec![1.0, 1.0]).unwrap());
-            assert_eq!(1.0, AudioData::root_mean_square(vec![-1.0, 1.0]).unwrap());
-            assert_eq!(
-                1.0,
-                AudioData::root_mean_square(vec![-1.0, 1.0, 1.0]).unwrap()
-            );
+        fn flux_spikes_at_note_onset() {
+            let flux = sine_burst(440.0, 22050).spectral_flux(1024, 512);
+
+            let peak = (0..flux.len())
+                .max_by(|a, b| flux[*a].total_cmp(&flux[*b]))
+                .unwrap();
+
+            // The onset at sample 22050 is in the frames starting at 21504
+            // and 22016
+            assert!([42, 43].contains(&peak), "flux peaked at frame {peak}");
+            assert!(flux[..42].iter().all(|x| *x == 0.0));
+
+            // Once the sine is steady, the flux is much lower than at the onset
+            assert!(flux[50..].iter().all(|x| *x < 0.05 * flux[peak]));
+        }
+
+        #[test]
+        fn one_value_per_frame() {
+            let audio_data = sine_burst(440.0, 22050);
+
             assert_eq!(
-                10.0,
-                AudioData::root_mean_square(vec![-10.0, -10.0]).unwrap()
-            );
-            assert!(
-                (5.0 * 10.0f64.sqrt())
-                    - AudioData::root_mean_square(vec![-10.0, 20.0])
-                        .unwrap()
-                        .abs()
-                    < 1e-12
+                audio_data.get_frames(1024, 512, None, None).len(),
+                audio_data.spectral_flux(1024, 512).len()
             );
         }
 
         #[test]
-        fn root_mean_square_of_empty_vector_is_none() {
-            assert_eq!(None, AudioData::root_mean_square(vec![]));
+        fn silence_has_zero_flux() {
+            let audio_data = AudioData::from_raw_samples(vec![0.0; 8192], 44100);
+
+            assert!(audio_data
+                .spectral_flux(1024, 512)
+                .iter()
+                .all(|x| *x == 0.0));
         }
     }
 }
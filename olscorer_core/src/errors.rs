@@ -18,15 +18,48 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use crate::transcription::NoteName;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
-pub enum WavFileError {
-    #[error("unsupported bit depth `{0}`, expected 16, 24, or 32")]
+#[allow(clippy::enum_variant_names)]
+pub enum AudioFileError {
+    #[error("unsupported bit depth `{0}`, expected 8, 16, 24, or 32")]
     UnsupportedBitDepth(u16),
 
     #[error("unsupported channel count `{0}`, expected mono or stereo audio")]
     UnsupportedChannelCount(u16),
+
+    #[error("unsupported audio file format `{0}`, expected wav, flac, mp3, or ogg")]
+    UnsupportedFormat(String),
+
+    #[error("audio file contains no samples")]
+    EmptyAudio,
+
+    #[error("malformed wav file: {0}")]
+    MalformedWav(&'static str),
+}
+
+#[cfg(feature = "microphone")]
+#[derive(Error, Debug)]
+pub enum AudioInputError {
+    #[error("no audio input device is available")]
+    NoInputDevice,
+
+    #[error("the input device does not support a sample rate of `{0}` Hz")]
+    UnsupportedSampleRate(u32),
+
+    #[error("unsupported input sample format `{0}`, expected f32, i16, or u16")]
+    UnsupportedSampleFormat(String),
+
+    #[error("error querying input device: {0}")]
+    SupportedConfigs(#[from] cpal::SupportedStreamConfigsError),
+
+    #[error("error creating input stream: {0}")]
+    BuildStream(#[from] cpal::BuildStreamError),
+
+    #[error("error starting input stream: {0}")]
+    PlayStream(#[from] cpal::PlayStreamError),
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -40,3 +73,59 @@ pub enum FrameError {
     #[error("duplicate index `{0}` at positions {1} and {2} in `indices`")]
     DuplicateFrameIndices(usize, usize, usize),
 }
+
+#[derive(Error, Debug, PartialEq)]
+#[error("incompatible sample rates `{expected}` Hz and `{found}` Hz")]
+pub struct IncompatibleSampleRateError {
+    pub expected: u32,
+    pub found: u32,
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("sample rate must be non-zero")]
+pub struct ZeroSampleRateError;
+
+#[derive(Error, Debug, PartialEq)]
+#[error(
+    "invalid note name `{0}`, expected a letter, an optional `#` or `b`, and an octave from 0 to 9"
+)]
+pub struct NoteParseError(pub String);
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FilterError {
+    #[error("pre-emphasis coefficient `{0}` is out of range, expected a value in [0, 1)")]
+    InvalidPreEmphasisCoefficient(f64),
+
+    #[error("pass band `{0}` Hz to `{1}` Hz is invalid, expected 0 <= low < high")]
+    InvalidPassBand(f64, f64),
+
+    #[error("cutoff frequency `{0}` Hz is above the Nyquist frequency `{1}` Hz")]
+    CutoffAboveNyquist(f64, f64),
+}
+
+#[derive(Error, Debug)]
+pub enum MidiExportError {
+    #[error("note `{0}` is outside the MIDI note range")]
+    NoteOutOfRange(NoteName),
+
+    #[error("tempo `{0}` BPM is too slow to be written to a MIDI file")]
+    TempoOutOfRange(f64),
+
+    #[error("error writing MIDI file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum OlscorerError {
+    #[error(
+        "cannot tell the export format of `{0}`, expected a musicxml, xml, mid, midi, abc, ly, \
+         json, or csv file"
+    )]
+    UnknownFormat(String),
+
+    #[error("error exporting MIDI file: {0}")]
+    Midi(#[from] MidiExportError),
+
+    #[error("error writing export file: {0}")]
+    Io(#[from] std::io::Error),
+}
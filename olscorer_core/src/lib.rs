@@ -18,7 +18,20 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+pub mod abc_export;
+#[cfg(feature = "microphone")]
+pub mod audio_input;
 pub mod audio_utils;
-mod errors;
-mod pitch_detection;
+pub mod errors;
+pub mod lilypond_export;
+pub mod midi_export;
+pub mod musicxml_export;
+pub mod onset_detection;
+pub mod pitch_detection;
+mod score;
+#[cfg(test)]
+mod test_utils;
 pub mod transcription;
+mod wavelets;
+
+pub use crate::score::QuantisationConfig;
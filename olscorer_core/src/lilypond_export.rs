@@ -0,0 +1,211 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::score::{QuantisationConfig, Score};
+use crate::transcription::Note;
+use std::fmt::Write;
+
+/// LilyPond note names (in the default Dutch note language) of each pitch
+/// class, where C is 0 and B is 11
+const NOTE_NAMES: [&str; 12] = [
+    "c", "cis", "d", "dis", "e", "f", "fis", "g", "gis", "a", "ais", "b",
+];
+
+/// Letter of each pitch class, counted in steps up from C, where C is 0 and
+/// B is 6
+const LETTER_STEPS: [i32; 12] = [0, 0, 1, 1, 2, 3, 3, 4, 4, 5, 5, 6];
+
+/// Number of steps between C0 and C4 (`c'` in LilyPond), the reference pitch
+/// of the relative block
+const RELATIVE_REFERENCE_STEP: i32 = 4 * 7;
+
+/// Converts the notes to LilyPond source in 4/4 time, quantising positions
+/// and durations to semiquavers
+///
+/// # Arguments
+///
+/// * `notes` - Notes to convert, with start positions and durations in samples
+/// * `sample_rate` - Sample rate (in Hz) of the audio the notes were
+///   transcribed from
+/// * `bpm` - Tempo of the audio (in quarter notes per minute)
+pub fn to_lilypond(notes: &[Note], sample_rate: u32, bpm: f64) -> String {
+    to_lilypond_with_config(
+        notes,
+        sample_rate,
+        (4, 4),
+        bpm,
+        QuantisationConfig::default(),
+    )
+}
+
+/// Converts the notes to LilyPond source, using the given time signature
+/// and quantisation
+///
+/// The notes are quantised and arranged into measures in the same way as
+/// for `musicxml_export::to_musicxml_with_config`. The notes are written in
+/// a `\relative c'` block with one measure per line, and each line ends with
+/// a bar check. The tempo is written to the nearest whole BPM, so it must be
+/// at least 0.5 BPM.
+pub fn to_lilypond_with_config(
+    notes: &[Note],
+    sample_rate: u32,
+    time_signature: (u8, u8),
+    bpm: f64,
+    config: QuantisationConfig,
+) -> String {
+    assert!(bpm >= 0.5, "tempo must round to at least 1 BPM");
+    let score = Score::new(notes, sample_rate, time_signature, bpm, &config);
+
+    let mut ly = String::new();
+    ly.push_str("\\version \"2.24.0\"\n\n");
+    ly.push_str("\\relative c' {\n");
+    let _ = writeln!(ly, "  \\tempo 4 = {}", bpm.round());
+    let _ = writeln!(ly, "  \\time {}/{}", time_signature.0, time_signature.1);
+
+    let mut previous_step = RELATIVE_REFERENCE_STEP;
+    let mut events = score.events.iter().peekable();
+
+    for measure in 0..score.num_measures() {
+        let measure_end = (measure + 1) * score.measure_length;
+        let mut words = vec![];
+
+        while let Some(event) = events.next_if(|e| e.start < measure_end) {
+            let word = match event.name {
+                Some(name) => {
+                    let pitch_class = name.pitch_class() as usize;
+                    let step = 7 * name.octave() as i32 + LETTER_STEPS[pitch_class];
+
+                    // In relative mode, a note without octave marks is placed
+                    // within a fourth of the previous note
+                    let octaves = (step - previous_step + 3).div_euclid(7);
+                    let marks = if octaves >= 0 {
+                        "'".repeat(octaves as usize)
+                    } else {
+                        ",".repeat(-octaves as usize)
+                    };
+                    previous_step = step;
+
+                    let tie = if event.tied_to_next { "~" } else { "" };
+                    format!(
+                        "{}{}{}{}",
+                        NOTE_NAMES[pitch_class], marks, event.note_value, tie
+                    )
+                }
+                None => format!("r{}", event.note_value),
+            };
+            words.push(word);
+        }
+
+        let _ = writeln!(ly, "  {} |", words.join(" "));
+    }
+
+    ly.push_str("  \\bar \"|.\"\n");
+    ly.push_str("}\n");
+
+    ly
+}
+
+#[cfg(test)]
+mod tests {
+    mod to_lilypond_tests {
+        use crate::lilypond_export::{to_lilypond, to_lilypond_with_config};
+        use crate::test_utils::note;
+        use crate::transcription::NoteName;
+        use crate::QuantisationConfig;
+
+        #[test]
+        fn simple_melody_matches_expected_source() {
+            // At 120 BPM, a quarter note is 22050 samples at 44100 Hz
+            let notes = [
+                note(NoteName::C(4), 0, 22050),
+                note(NoteName::E(4), 22050, 22050),
+                note(NoteName::G(4), 44100, 44100),
+            ];
+
+            let expected = r#"\version "2.24.0"
+
+\relative c' {
+  \tempo 4 = 120
+  \time 4/4
+  c4 e4 g2 |
+  \bar "|."
+}
+"#;
+
+            assert_eq!(expected, to_lilypond(&notes, 44100, 120.0));
+        }
+
+        #[test]
+        fn octave_marks_follow_relative_mode() {
+            // C4 to G4 is a fifth, so it needs a mark to go up, and the
+            // following C4 needs a mark to come back down
+            let notes = [
+                note(NoteName::C(4), 0, 22050),
+                note(NoteName::G(4), 22050, 22050),
+                note(NoteName::C(4), 44100, 22050),
+                note(NoteName::ASharp(2), 66150, 22050),
+            ];
+
+            let ly = to_lilypond(&notes, 44100, 120.0);
+
+            assert!(ly.contains("  c4 g'4 c,4 ais,4 |\n"), "{ly}");
+        }
+
+        #[test]
+        fn rests_and_ties_are_written() {
+            // A rest, then a half note starting on the fourth beat, which is
+            // tied across the barline
+            let notes = [note(NoteName::FSharp(4), 66150, 44100)];
+
+            let ly = to_lilypond(&notes, 44100, 120.0);
+
+            assert!(ly.contains("  r2 r4 fis4~ |\n  fis4 r2 r4 |\n"), "{ly}");
+        }
+
+        #[test]
+        fn beat_shorter_than_grid_position_is_supported() {
+            // A 3/32 measure is one and a half semiquavers, so the quarter
+            // note is split across three measures
+            let notes = [note(NoteName::C(4), 0, 22050)];
+
+            let ly = to_lilypond_with_config(
+                &notes,
+                44100,
+                (3, 32),
+                120.0,
+                QuantisationConfig::default(),
+            );
+
+            assert!(ly.contains("  \\time 3/32\n"), "{ly}");
+            assert!(
+                ly.contains("  c16~ c32~ |\n  c16~ c32~ |\n  c16 r32 |\n"),
+                "{ly}"
+            );
+        }
+
+        #[test]
+        #[should_panic]
+        fn tempo_rounding_to_zero_should_panic() {
+            let notes = [note(NoteName::C(4), 0, 22050)];
+
+            to_lilypond(&notes, 44100, 0.4);
+        }
+    }
+}
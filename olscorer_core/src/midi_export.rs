@@ -0,0 +1,304 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::errors::MidiExportError;
+use crate::transcription::{Note, NoteName};
+use midly::num::{u15, u24, u28, u4, u7};
+use midly::{Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind};
+use std::io::Write;
+
+/// Number of MIDI ticks in each quarter note
+const TICKS_PER_QUARTER_NOTE: u16 = 480;
+
+/// Parameters for MIDI file export
+pub struct MidiExportConfig {
+    /// Tempo (in beats per minute) used to convert note positions to MIDI
+    /// ticks, where each beat is a quarter note
+    pub bpm: f64,
+
+    /// Velocity of every note, from 0 to 127
+    pub velocity: u8,
+}
+
+impl Default for MidiExportConfig {
+    fn default() -> Self {
+        MidiExportConfig {
+            bpm: 120.0,
+            velocity: 64,
+        }
+    }
+}
+
+/// Writes the notes to a single-track standard MIDI file, assuming a tempo
+/// of 120 BPM
+///
+/// # Arguments
+///
+/// * `notes` - Notes to write, with start positions and durations in samples
+/// * `sample_rate` - Sample rate (in Hz) of the audio the notes were
+///   transcribed from
+/// * `output` - Destination of the MIDI file
+pub fn write_midi(
+    notes: &[Note],
+    sample_rate: u32,
+    output: impl Write,
+) -> Result<(), MidiExportError> {
+    write_midi_with_config(notes, sample_rate, MidiExportConfig::default(), output)
+}
+
+/// Writes the notes to a single-track standard MIDI file, using the given
+/// tempo and velocity
+///
+/// Each note becomes a note on and note off pair on channel 1. Where one
+/// note ends as another starts, the note off is written first. Returns an
+/// Error if a note is outside the MIDI note range, if the tempo is too slow
+/// to be written (below about 3.6 BPM), or if writing fails.
+pub fn write_midi_with_config(
+    notes: &[Note],
+    sample_rate: u32,
+    config: MidiExportConfig,
+    output: impl Write,
+) -> Result<(), MidiExportError> {
+    assert_ne!(0, sample_rate, "sample rate must be non-zero");
+    assert!(config.bpm > 0.0, "tempo must be positive");
+    assert!(
+        config.velocity <= 127,
+        "velocity must not be greater than 127"
+    );
+
+    let ticks_per_sample = config.bpm / 60.0 * TICKS_PER_QUARTER_NOTE as f64 / sample_rate as f64;
+    let to_ticks = |samples: usize| (samples as f64 * ticks_per_sample).round() as u32;
+
+    // Events are sorted by tick, with note offs before note ons at the same
+    // tick so that repeated notes don't overlap
+    let mut events = Vec::with_capacity(2 * notes.len());
+
+    for note in notes {
+        if note.name > NoteName::G(9) {
+            return Err(MidiExportError::NoteOutOfRange(note.name));
+        }
+        let key = u7::new(note.name.to_midi_number());
+
+        events.push((
+            to_ticks(note.start + note.duration),
+            0,
+            MidiMessage::NoteOff {
+                key,
+                vel: u7::new(0),
+            },
+        ));
+        events.push((
+            to_ticks(note.start),
+            1,
+            MidiMessage::NoteOn {
+                key,
+                vel: u7::new(config.velocity),
+            },
+        ));
+    }
+
+    events.sort_by_key(|(tick, order, _)| (*tick, *order));
+
+    // The tempo is written as the number of microseconds per beat, which
+    // must fit in 24 bits
+    let microseconds_per_beat = (60_000_000.0 / config.bpm).round() as u32;
+    let tempo =
+        u24::try_from(microseconds_per_beat).ok_or(MidiExportError::TempoOutOfRange(config.bpm))?;
+
+    let mut track = vec![TrackEvent {
+        delta: u28::new(0),
+        kind: TrackEventKind::Meta(MetaMessage::Tempo(tempo)),
+    }];
+
+    let mut previous_tick = 0;
+    for (tick, _, message) in events {
+        track.push(TrackEvent {
+            delta: u28::new(tick - previous_tick),
+            kind: TrackEventKind::Midi {
+                channel: u4::new(0),
+                message,
+            },
+        });
+        previous_tick = tick;
+    }
+
+    track.push(TrackEvent {
+        delta: u28::new(0),
+        kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+    });
+
+    let mut smf = Smf::new(Header::new(
+        Format::SingleTrack,
+        Timing::Metrical(u15::new(TICKS_PER_QUARTER_NOTE)),
+    ));
+    smf.tracks.push(track);
+
+    smf.write_std(output)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    mod write_midi_tests {
+        use crate::errors::MidiExportError;
+        use crate::midi_export::{write_midi, write_midi_with_config, MidiExportConfig};
+        use crate::test_utils::note;
+        use crate::transcription::NoteName;
+        use midly::{MetaMessage, MidiMessage, Smf, Timing, TrackEventKind};
+
+        /// Returns the absolute tick and message of each MIDI event in the
+        /// first track
+        fn midi_events(smf: &Smf) -> Vec<(u32, MidiMessage)> {
+            let mut tick = 0;
+            let mut events = vec![];
+
+            for event in smf.tracks[0].iter() {
+                tick += event.delta.as_int();
+                if let TrackEventKind::Midi { message, .. } = event.kind {
+                    events.push((tick, message));
+                }
+            }
+
+            events
+        }
+
+        #[test]
+        fn single_note_round_trips() {
+            // A4 starting after half a second and lasting one second, which
+            // is one beat and two beats at 120 BPM
+            let mut bytes = vec![];
+            write_midi(&[note(NoteName::A(4), 22050, 44100)], 44100, &mut bytes).unwrap();
+
+            let smf = Smf::parse(&bytes).unwrap();
+
+            assert_eq!(Timing::Metrical(480.into()), smf.header.timing);
+            assert_eq!(
+                vec![
+                    (
+                        480,
+                        MidiMessage::NoteOn {
+                            key: 69.into(),
+                            vel: 64.into()
+                        }
+                    ),
+                    (
+                        1440,
+                        MidiMessage::NoteOff {
+                            key: 69.into(),
+                            vel: 0.into()
+                        }
+                    ),
+                ],
+                midi_events(&smf)
+            );
+        }
+
+        #[test]
+        fn tempo_and_velocity_are_configurable() {
+            let mut bytes = vec![];
+            write_midi_with_config(
+                &[note(NoteName::C(4), 0, 44100)],
+                44100,
+                MidiExportConfig {
+                    bpm: 60.0,
+                    velocity: 100,
+                },
+                &mut bytes,
+            )
+            .unwrap();
+
+            let smf = Smf::parse(&bytes).unwrap();
+
+            assert!(smf.tracks[0]
+                .iter()
+                .any(|event| event.kind
+                    == TrackEventKind::Meta(MetaMessage::Tempo(1_000_000.into()))));
+            assert_eq!(
+                vec![
+                    (
+                        0,
+                        MidiMessage::NoteOn {
+                            key: 60.into(),
+                            vel: 100.into()
+                        }
+                    ),
+                    (
+                        480,
+                        MidiMessage::NoteOff {
+                            key: 60.into(),
+                            vel: 0.into()
+                        }
+                    ),
+                ],
+                midi_events(&smf)
+            );
+        }
+
+        #[test]
+        fn note_off_comes_before_note_on_at_same_tick() {
+            let mut bytes = vec![];
+            write_midi(
+                &[
+                    note(NoteName::C(4), 0, 22050),
+                    note(NoteName::C(4), 22050, 22050),
+                ],
+                44100,
+                &mut bytes,
+            )
+            .unwrap();
+
+            let smf = Smf::parse(&bytes).unwrap();
+            let events = midi_events(&smf);
+
+            assert_eq!(4, events.len());
+            assert!(matches!(events[1], (480, MidiMessage::NoteOff { .. })));
+            assert!(matches!(events[2], (480, MidiMessage::NoteOn { .. })));
+        }
+
+        #[test]
+        fn note_above_midi_range_returns_error() {
+            let result = write_midi(&[note(NoteName::A(9), 0, 100)], 44100, vec![]);
+
+            assert!(matches!(
+                result,
+                Err(MidiExportError::NoteOutOfRange(NoteName::A(9)))
+            ));
+        }
+
+        #[test]
+        fn tempo_too_slow_for_midi_returns_error() {
+            let result = write_midi_with_config(
+                &[note(NoteName::C(4), 0, 100)],
+                44100,
+                MidiExportConfig {
+                    bpm: 3.5,
+                    ..MidiExportConfig::default()
+                },
+                vec![],
+            );
+
+            assert!(matches!(
+                result,
+                Err(MidiExportError::TempoOutOfRange(bpm)) if bpm == 3.5
+            ));
+        }
+    }
+}
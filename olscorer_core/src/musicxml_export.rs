@@ -0,0 +1,345 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::score::{QuantisationConfig, Score, ScoreEvent};
+use crate::transcription::Note;
+use std::fmt::Write;
+
+/// Names of the MusicXML note types, starting with the whole note and halving
+/// in length with each entry
+const NOTE_TYPES: [&str; 9] = [
+    "whole", "half", "quarter", "eighth", "16th", "32nd", "64th", "128th", "256th",
+];
+
+/// Step and alteration of each pitch class, where C is 0 and B is 11
+const PITCH_SPELLINGS: [(&str, i8); 12] = [
+    ("C", 0),
+    ("C", 1),
+    ("D", 0),
+    ("D", 1),
+    ("E", 0),
+    ("F", 0),
+    ("F", 1),
+    ("G", 0),
+    ("G", 1),
+    ("A", 0),
+    ("A", 1),
+    ("B", 0),
+];
+
+/// Converts the notes to a MusicXML score with a single part, quantising
+/// positions and durations to semiquavers
+///
+/// # Arguments
+///
+/// * `notes` - Notes to convert, with start positions and durations in samples
+/// * `sample_rate` - Sample rate (in Hz) of the audio the notes were
+///   transcribed from
+/// * `time_signature` - Number of beats in each measure, and the note value
+///   of each beat
+/// * `bpm` - Tempo of the audio (in quarter notes per minute)
+pub fn to_musicxml(notes: &[Note], sample_rate: u32, time_signature: (u8, u8), bpm: f64) -> String {
+    to_musicxml_with_config(
+        notes,
+        sample_rate,
+        time_signature,
+        bpm,
+        QuantisationConfig::default(),
+    )
+}
+
+/// Converts the notes to a MusicXML score with a single part, quantising
+/// positions and durations with the given configuration
+///
+/// The start and end of each note are moved to the nearest grid position,
+/// and notes are at least one grid position long. Notes are treated as a
+/// single melody, so a note is cut short where the next note starts, and
+/// the gaps between notes are filled with rests. Notes which cross a barline
+/// or whose length is not a single note value are split into tied notes.
+pub fn to_musicxml_with_config(
+    notes: &[Note],
+    sample_rate: u32,
+    time_signature: (u8, u8),
+    bpm: f64,
+    config: QuantisationConfig,
+) -> String {
+    let (beats, beat_type) = time_signature;
+    let score = Score::new(notes, sample_rate, time_signature, bpm, &config);
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    xml.push_str(concat!(
+        "<!DOCTYPE score-partwise PUBLIC \"-//Recordare//DTD MusicXML 4.0 Partwise//EN\" ",
+        "\"http://www.musicxml.org/dtds/partwise.dtd\">\n"
+    ));
+    xml.push_str("<score-partwise version=\"4.0\">\n");
+    xml.push_str("  <part-list>\n");
+    xml.push_str("    <score-part id=\"P1\">\n");
+    xml.push_str("      <part-name>Transcription</part-name>\n");
+    xml.push_str("    </score-part>\n");
+    xml.push_str("  </part-list>\n");
+    xml.push_str("  <part id=\"P1\">\n");
+
+    let mut events = score.events.iter().peekable();
+
+    for measure in 0..score.num_measures() {
+        let _ = writeln!(xml, "    <measure number=\"{}\">", measure + 1);
+
+        if measure == 0 {
+            xml.push_str("      <attributes>\n");
+            let _ = writeln!(xml, "        <divisions>{}</divisions>", score.divisions);
+            let _ = writeln!(
+                xml,
+                "        <time><beats>{}</beats><beat-type>{}</beat-type></time>",
+                beats, beat_type
+            );
+            xml.push_str("        <clef><sign>G</sign><line>2</line></clef>\n");
+            xml.push_str("      </attributes>\n");
+            xml.push_str("      <direction placement=\"above\">\n");
+            let _ = writeln!(
+                xml,
+                "        <direction-type><metronome><beat-unit>quarter</beat-unit>\
+                 <per-minute>{}</per-minute></metronome></direction-type>",
+                bpm
+            );
+            let _ = writeln!(xml, "        <sound tempo=\"{}\"/>", bpm);
+            xml.push_str("      </direction>\n");
+        }
+
+        let measure_end = (measure + 1) * score.measure_length;
+        while let Some(event) = events.next_if(|e| e.start < measure_end) {
+            write_event(&mut xml, event);
+        }
+
+        xml.push_str("    </measure>\n");
+    }
+
+    xml.push_str("  </part>\n");
+    xml.push_str("</score-partwise>\n");
+
+    xml
+}
+
+/// Writes the MusicXML note element for the note or rest
+fn write_event(xml: &mut String, event: &ScoreEvent) {
+    xml.push_str("      <note>\n");
+
+    match event.name {
+        Some(name) => {
+            let (step, alter) = PITCH_SPELLINGS[name.pitch_class() as usize];
+            let alter = if alter != 0 {
+                format!("<alter>{}</alter>", alter)
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                xml,
+                "        <pitch><step>{}</step>{}<octave>{}</octave></pitch>",
+                step,
+                alter,
+                name.octave()
+            );
+        }
+        None => xml.push_str("        <rest/>\n"),
+    }
+
+    let _ = writeln!(xml, "        <duration>{}</duration>", event.length);
+
+    if event.tied_from_previous {
+        xml.push_str("        <tie type=\"stop\"/>\n");
+    }
+    if event.tied_to_next {
+        xml.push_str("        <tie type=\"start\"/>\n");
+    }
+
+    let note_type = NOTE_TYPES[event.note_value.trailing_zeros() as usize];
+    let _ = writeln!(xml, "        <type>{}</type>", note_type);
+
+    if event.tied_from_previous || event.tied_to_next {
+        xml.push_str("        <notations>\n");
+        if event.tied_from_previous {
+            xml.push_str("          <tied type=\"stop\"/>\n");
+        }
+        if event.tied_to_next {
+            xml.push_str("          <tied type=\"start\"/>\n");
+        }
+        xml.push_str("        </notations>\n");
+    }
+
+    xml.push_str("      </note>\n");
+}
+
+#[cfg(test)]
+mod tests {
+    mod to_musicxml_tests {
+        use crate::musicxml_export::{to_musicxml, to_musicxml_with_config};
+        use crate::test_utils::note;
+        use crate::transcription::NoteName;
+        use crate::QuantisationConfig;
+        use quick_xml::events::Event;
+        use quick_xml::Reader;
+
+        /// Parses the XML, panicking if it is not well-formed, and returns
+        /// the names of all the elements in document order
+        fn element_names(xml: &str) -> Vec<String> {
+            let mut reader = Reader::from_str(xml);
+            let mut names = vec![];
+
+            loop {
+                match reader.read_event() {
+                    Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                        names.push(String::from_utf8(e.name().as_ref().to_vec()).unwrap())
+                    }
+                    Ok(Event::Eof) => break,
+                    Err(e) => panic!("invalid XML: {e}"),
+                    _ => (),
+                }
+            }
+
+            names
+        }
+
+        fn count(names: &[String], name: &str) -> usize {
+            names.iter().filter(|n| *n == name).count()
+        }
+
+        #[test]
+        fn notes_filling_a_measure_have_no_rests() {
+            // At 120 BPM, a quarter note is 22050 samples at 44100 Hz
+            let notes = [
+                note(NoteName::C(4), 0, 22050),
+                note(NoteName::E(4), 22050, 22050),
+                note(NoteName::G(4), 44100, 44100),
+            ];
+
+            let xml = to_musicxml(&notes, 44100, (4, 4), 120.0);
+            let names = element_names(&xml);
+
+            assert_eq!(1, count(&names, "measure"));
+            assert_eq!(3, count(&names, "note"));
+            assert_eq!(3, count(&names, "pitch"));
+            assert_eq!(0, count(&names, "rest"));
+            assert!(xml.contains("<pitch><step>G</step><octave>4</octave></pitch>"));
+            assert!(xml.contains("<type>half</type>"));
+        }
+
+        #[test]
+        fn gaps_are_filled_with_rests() {
+            let notes = [
+                note(NoteName::CSharp(5), 22050, 22050),
+                note(NoteName::A(4), 66150, 22050),
+            ];
+
+            let xml = to_musicxml(&notes, 44100, (4, 4), 120.0);
+            let names = element_names(&xml);
+
+            assert_eq!(4, count(&names, "note"));
+            assert_eq!(2, count(&names, "pitch"));
+            assert_eq!(2, count(&names, "rest"));
+            assert!(xml.contains("<pitch><step>C</step><alter>1</alter><octave>5</octave></pitch>"));
+        }
+
+        #[test]
+        fn notes_crossing_barlines_are_tied() {
+            // A half note starting on the fourth beat of a 4/4 measure
+            let notes = [note(NoteName::D(4), 66150, 44100)];
+
+            let xml = to_musicxml(&notes, 44100, (4, 4), 120.0);
+            let names = element_names(&xml);
+
+            assert_eq!(2, count(&names, "measure"));
+            assert_eq!(2, count(&names, "pitch"));
+            assert_eq!(2, count(&names, "tie"));
+            assert_eq!(2, count(&names, "tied"));
+        }
+
+        #[test]
+        fn positions_are_quantised_to_the_grid() {
+            // Slightly early and slightly long quarter notes
+            let notes = [
+                note(NoteName::C(4), 300, 22500),
+                note(NoteName::D(4), 21800, 22400),
+            ];
+
+            let xml = to_musicxml_with_config(
+                &notes,
+                44100,
+                (2, 4),
+                120.0,
+                QuantisationConfig { subdivisions: 8 },
+            );
+            let names = element_names(&xml);
+
+            assert_eq!(2, count(&names, "note"));
+            assert_eq!(0, count(&names, "rest"));
+            assert!(xml.contains("<divisions>2</divisions>"));
+            assert_eq!(2, xml.matches("<type>quarter</type>").count());
+        }
+
+        #[test]
+        fn measure_shorter_than_grid_multiple_is_filled() {
+            // A 3/8 measure is one and a half quarter notes, so it is not a
+            // whole number of grid positions long
+            let notes = [note(NoteName::C(4), 0, 22050)];
+
+            let xml = to_musicxml_with_config(
+                &notes,
+                44100,
+                (3, 8),
+                120.0,
+                QuantisationConfig { subdivisions: 4 },
+            );
+            let names = element_names(&xml);
+
+            assert_eq!(1, count(&names, "measure"));
+            assert_eq!(1, count(&names, "pitch"));
+            assert_eq!(1, count(&names, "rest"));
+            assert!(xml.contains("<divisions>2</divisions>"));
+            assert!(xml.contains("<type>quarter</type>"));
+            assert!(xml.contains("<type>eighth</type>"));
+        }
+
+        #[test]
+        fn beat_shorter_than_grid_position_is_supported() {
+            // A 3/32 measure is one and a half semiquavers, so the quarter
+            // note is split across three measures
+            let notes = [note(NoteName::C(4), 0, 22050)];
+
+            let xml = to_musicxml(&notes, 44100, (3, 32), 120.0);
+            let names = element_names(&xml);
+
+            assert_eq!(3, count(&names, "measure"));
+            assert_eq!(5, count(&names, "pitch"));
+            assert_eq!(1, count(&names, "rest"));
+            assert!(xml.contains("<divisions>8</divisions>"));
+            assert_eq!(3, xml.matches("<type>32nd</type>").count());
+        }
+
+        #[test]
+        fn no_notes_gives_a_measure_of_rest() {
+            let xml = to_musicxml(&[], 44100, (3, 4), 120.0);
+            let names = element_names(&xml);
+
+            assert_eq!(1, count(&names, "measure"));
+            assert_eq!(0, count(&names, "pitch"));
+            assert!(count(&names, "rest") > 0);
+        }
+    }
+}
@@ -0,0 +1,638 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::audio_utils::{AudioData, Frame};
+
+/// Number of blocks each onset frame is divided into when locating the
+/// position of an onset within the frame
+const ONSET_BLOCKS_PER_FRAME: usize = 16;
+
+/// Number of onset detection function values on either side of a value which
+/// are used to calculate its onset threshold
+const ONSET_THRESHOLD_WINDOW: usize = 8;
+
+/// Minimum complex domain deviation for an onset found by the complex domain
+/// detector, as a fraction of the largest deviation. The predicted phase and
+/// magnitude of each bin are never exact, so even a steady tone leaves a
+/// small deviation in every frame, whose peaks must not be picked.
+const MIN_RELATIVE_DEVIATION: f64 = 0.1;
+
+/// Minimum rise in the HFC for an onset found by the HFC detector, as a
+/// fraction of the largest rise in the HFC. Vibrato and beating between
+/// partials make the HFC of a held note wobble from frame to frame, which
+/// would otherwise give extra onsets after each attack.
+const MIN_RELATIVE_HFC_RISE: f64 = 0.1;
+
+/// A note onset found by an onset detector
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnsetEvent {
+    /// Index of the sample at which the onset was detected
+    pub sample: usize,
+
+    /// Strength of the onset relative to the strongest onset in the audio,
+    /// between 0 and 1
+    pub confidence: f64,
+}
+
+pub trait OnsetDetector: Send + Sync {
+    /// Finds the onsets of notes in the audio
+    ///
+    /// Returns the onsets which were detected, in ascending order of their
+    /// sample indices.
+    fn get_onsets(&self, audio_data: &AudioData) -> Vec<OnsetEvent>;
+
+    /// Finds the sample indices of the onsets of notes in the audio, in
+    /// ascending order
+    fn get_onset_samples(&self, audio_data: &AudioData) -> Vec<usize> {
+        self.get_onsets(audio_data)
+            .iter()
+            .map(|onset| onset.sample)
+            .collect()
+    }
+}
+
+/// Onset detector which looks for increases in the amplitude envelope of the
+/// audio
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimpleEnvelopeDetector {
+    /// Width (in samples) of the frames used to calculate the amplitude
+    /// envelope of the audio
+    pub frame_width: usize,
+
+    /// Minimum increase in the envelope between consecutive frames for an
+    /// onset to be detected
+    pub threshold: f64,
+}
+
+impl Default for SimpleEnvelopeDetector {
+    fn default() -> Self {
+        SimpleEnvelopeDetector {
+            frame_width: 1600,
+            threshold: 0.125,
+        }
+    }
+}
+
+impl OnsetDetector for SimpleEnvelopeDetector {
+    /// Finds the onsets of notes in the audio
+    ///
+    /// The envelope is the peak absolute sample value of each frame. An onset
+    /// is detected in a frame if the envelope increases by more than the
+    /// threshold over the previous frame, unless an onset was detected in
+    /// the previous frame.
+    fn get_onsets(&self, audio_data: &AudioData) -> Vec<OnsetEvent> {
+        // Calculate envelope
+        let onset_frames = audio_data.get_frames(self.frame_width, self.frame_width, None, None);
+
+        // Make all samples positive
+        let abs_frames: Vec<Vec<f64>> = onset_frames
+            .iter()
+            .map(|f| f.samples.iter().map(|x| x.abs()).collect::<Vec<f64>>())
+            .collect();
+
+        let envelope: Vec<f64> = abs_frames
+            .into_iter()
+            .map(|samples| {
+                samples
+                    .into_iter()
+                    .max_by(|a, b| a.total_cmp(b))
+                    .unwrap_or(0.0)
+            })
+            .collect();
+
+        // Get differences between consecutive elements of the envelope
+        let mut differences = vec![0.0];
+
+        for i in 1..envelope.len() {
+            differences.push(envelope[i] - envelope[i - 1]);
+        }
+
+        let max_difference = max_value(&differences);
+
+        // Get the onsets
+        let mut onsets = vec![];
+        let mut add_onset = true;
+
+        for (i, frame) in onset_frames.iter().enumerate() {
+            if differences[i] > self.threshold && add_onset {
+                onsets.push(OnsetEvent {
+                    sample: locate_onset(audio_data, frame),
+                    confidence: differences[i] / max_difference,
+                });
+                add_onset = false;
+            } else {
+                add_onset = true;
+            }
+        }
+
+        onsets
+    }
+}
+
+/// Onset detector which looks for peaks in the spectral flux of the audio
+///
+/// This is the onset detector used by default for transcription.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpectralFluxDetector {
+    /// Width (in samples) of the frames used to calculate the spectral flux.
+    /// Consecutive frames overlap by half their width.
+    pub frame_width: usize,
+
+    /// Number of standard deviations above the local mean of the spectral
+    /// flux at which a peak in the spectral flux is detected as an onset
+    pub threshold_multiplier: f64,
+
+    /// Minimum spectral flux for an onset, as a fraction of the largest
+    /// spectral flux value in the audio, which stops small fluctuations in
+    /// quiet passages being detected as onsets. Lower values detect softer
+    /// onsets.
+    pub threshold: f64,
+}
+
+impl Default for SpectralFluxDetector {
+    fn default() -> Self {
+        SpectralFluxDetector {
+            frame_width: 1024,
+            threshold_multiplier: 1.5,
+            threshold: 0.2,
+        }
+    }
+}
+
+impl OnsetDetector for SpectralFluxDetector {
+    /// Finds the onsets of notes in the audio
+    ///
+    /// A spectral flux value is detected as an onset if it is the largest
+    /// value in a window around it, and it exceeds the mean of the window by
+    /// more than the configured number of standard deviations. Values less
+    /// than the configured fraction of the largest spectral flux value are
+    /// ignored. The position of each onset within its frame is then located
+    /// from the amplitude of the samples.
+    fn get_onsets(&self, audio_data: &AudioData) -> Vec<OnsetEvent> {
+        let frame_width = self.frame_width;
+        let hop_size = std::cmp::max(frame_width / 2, 1);
+        let flux = audio_data.spectral_flux(frame_width, hop_size);
+        let max_flux = max_value(&flux);
+
+        pick_peaks(&flux, self.threshold_multiplier, self.threshold * max_flux)
+            .into_iter()
+            .map(|i| {
+                let frame_start = i * hop_size;
+                let frame = Frame {
+                    start_pos: frame_start,
+                    samples: audio_data.samples[frame_start..frame_start + frame_width].to_vec(),
+                };
+                OnsetEvent {
+                    sample: locate_onset(audio_data, &frame),
+                    confidence: flux[i] / max_flux,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Onset detector which looks for sharp rises in the high-frequency content
+/// (HFC) of the audio
+///
+/// This works better than the envelope based detector for percussive
+/// instruments, whose attacks contain a lot of high-frequency energy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HfcDetector {
+    /// Width (in samples) of the frames used to calculate the HFC.
+    /// Consecutive frames overlap by half their width.
+    pub frame_width: usize,
+
+    /// Number of standard deviations above the local mean of the rise in the
+    /// HFC at which a peak in the rise is detected as an onset
+    pub threshold: f64,
+}
+
+impl Default for HfcDetector {
+    fn default() -> Self {
+        HfcDetector {
+            frame_width: 1024,
+            threshold: 1.5,
+        }
+    }
+}
+
+impl OnsetDetector for HfcDetector {
+    /// Finds the onsets of notes in the audio
+    ///
+    /// The rise in the HFC of each frame is the amount by which it exceeds
+    /// the HFC of the previous frame, or zero if it is lower, so a sustained
+    /// note gives a single onset when it starts. A rise is detected as an
+    /// onset if it is the largest value in a window around it, and it exceeds
+    /// the mean of the window by more than the threshold number of standard
+    /// deviations, and is at least a tenth of the largest rise. Each onset is
+    /// placed at the start of the hop in the middle of its frame, where the
+    /// Hann window applied to the frame is largest.
+    fn get_onsets(&self, audio_data: &AudioData) -> Vec<OnsetEvent> {
+        let hop_size = std::cmp::max(self.frame_width / 2, 1);
+        let hfc = audio_data.high_frequency_content(self.frame_width, hop_size);
+
+        // The audio is silent before the first frame
+        let rise: Vec<f64> = std::iter::once(0.0)
+            .chain(hfc.iter().copied())
+            .zip(&hfc)
+            .map(|(previous, current)| (current - previous).max(0.0))
+            .collect();
+        let max_rise = max_value(&rise);
+
+        pick_peaks(&rise, self.threshold, MIN_RELATIVE_HFC_RISE * max_rise)
+            .into_iter()
+            .map(|i| OnsetEvent {
+                sample: i * hop_size + self.frame_width / 2,
+                confidence: rise[i] / max_rise,
+            })
+            .collect()
+    }
+}
+
+/// Onset detector which looks for peaks in the complex domain deviation of
+/// the audio
+///
+/// The complex domain deviation measures changes in both the magnitude and
+/// the phase of the spectrum, so this detects soft onsets, such as legato
+/// changes of pitch, which do not appear in the amplitude envelope.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComplexDomainDetector {
+    /// Width (in samples) of the frames used to calculate the complex domain
+    /// deviation. Consecutive frames overlap by half their width.
+    pub frame_width: usize,
+
+    /// Number of standard deviations above the local mean of the complex
+    /// domain deviation at which a peak is detected as an onset
+    pub threshold: f64,
+}
+
+impl Default for ComplexDomainDetector {
+    fn default() -> Self {
+        ComplexDomainDetector {
+            frame_width: 1024,
+            threshold: 1.5,
+        }
+    }
+}
+
+impl OnsetDetector for ComplexDomainDetector {
+    /// Finds the onsets of notes in the audio
+    ///
+    /// A deviation is detected as an onset if it is the largest value in a
+    /// window around it, and it exceeds the mean of the window by more than
+    /// the threshold number of standard deviations, and is at least a tenth
+    /// of the largest deviation. Each onset is placed at the start of the hop
+    /// in the middle of its frame.
+    fn get_onsets(&self, audio_data: &AudioData) -> Vec<OnsetEvent> {
+        let hop_size = std::cmp::max(self.frame_width / 2, 1);
+        let deviation = audio_data.complex_domain_deviation(self.frame_width, hop_size);
+
+        let max_deviation = max_value(&deviation);
+
+        pick_peaks(
+            &deviation,
+            self.threshold,
+            MIN_RELATIVE_DEVIATION * max_deviation,
+        )
+        .into_iter()
+        .map(|i| OnsetEvent {
+            sample: i * hop_size + self.frame_width / 2,
+            confidence: deviation[i] / max_deviation,
+        })
+        .collect()
+    }
+}
+
+/// Returns the largest value of an onset detection function, or 0 if it has
+/// no positive values
+fn max_value(values: &[f64]) -> f64 {
+    values.iter().fold(0.0, |max: f64, x| max.max(*x))
+}
+
+/// Finds the indices of the values which are onsets in an onset detection
+/// function
+///
+/// A value is an onset if it is the largest value in a window around it, it
+/// exceeds the mean of the window by more than `threshold_multiplier`
+/// standard deviations, and it is greater than `min_value`.
+fn pick_peaks(values: &[f64], threshold_multiplier: f64, min_value: f64) -> Vec<usize> {
+    let mut peaks = vec![];
+
+    for (i, value) in values.iter().enumerate() {
+        let start = i.saturating_sub(ONSET_THRESHOLD_WINDOW);
+        let end = std::cmp::min(i + ONSET_THRESHOLD_WINDOW + 1, values.len());
+        let window = &values[start..end];
+
+        // Only the first of several equal values in the window is a peak
+        let is_peak = values[start..i].iter().all(|x| x < value)
+            && values[i + 1..end].iter().all(|x| x <= value);
+
+        let mean = window.iter().sum::<f64>() / window.len() as f64;
+        let std_dev =
+            (window.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / window.len() as f64).sqrt();
+
+        if is_peak && *value > min_value && *value > mean + threshold_multiplier * std_dev {
+            peaks.push(i);
+        }
+    }
+
+    peaks
+}
+
+/// Finds the position of a note onset within a frame in which an onset
+/// was detected
+///
+/// The frame is divided into blocks, and the onset is placed at the start
+/// of the block whose peak amplitude increases the most over the peak
+/// amplitude of the preceding block.
+fn locate_onset(audio_data: &AudioData, frame: &Frame) -> usize {
+    let block_width = std::cmp::max(frame.samples.len() / ONSET_BLOCKS_PER_FRAME, 1);
+
+    let block_peak = |block: &[f64]| block.iter().fold(0.0, |max: f64, x| max.max(x.abs()));
+
+    // The block preceding the first block of the frame is the last block of
+    // the previous frame
+    let mut previous_peak = match frame.start_pos.checked_sub(block_width) {
+        Some(start) => block_peak(&audio_data.samples[start..frame.start_pos]),
+        None => 0.0,
+    };
+
+    let mut onset = frame.start_pos;
+    let mut max_increase = f64::NEG_INFINITY;
+
+    for (i, block) in frame.samples.chunks_exact(block_width).enumerate() {
+        let peak = block_peak(block);
+
+        if peak - previous_peak > max_increase {
+            max_increase = peak - previous_peak;
+            onset = frame.start_pos + i * block_width;
+        }
+        previous_peak = peak;
+    }
+
+    onset
+}
+
+#[cfg(test)]
+mod tests {
+    mod onset_event_tests {
+        use crate::audio_utils::AudioData;
+        use crate::onset_detection::{
+            ComplexDomainDetector, HfcDetector, OnsetDetector, OnsetEvent, SimpleEnvelopeDetector,
+            SpectralFluxDetector,
+        };
+
+        /// Creates a loud decaying note starting at sample 5000 followed by a
+        /// quiet decaying note starting at sample 25000
+        fn strong_and_weak_notes() -> AudioData {
+            let samples = (0..44100)
+                .map(|i| {
+                    let (amplitude, frequency, start) = match i {
+                        5000..=14999 => (0.9, 440.0, 5000),
+                        25000..=34999 => (0.3, 660.0, 25000),
+                        _ => return 0.0,
+                    };
+                    let t = (i - start) as f64 / 44100.0;
+                    amplitude
+                        * (-t / 0.05).exp()
+                        * (2.0 * std::f64::consts::PI * frequency * t).sin()
+                })
+                .collect();
+
+            AudioData::from_raw_samples(samples, 44100)
+        }
+
+        /// Returns the detected onset closest to a sample
+        fn closest_onset(onsets: &[OnsetEvent], sample: usize) -> OnsetEvent {
+            *onsets
+                .iter()
+                .min_by_key(|onset| onset.sample.abs_diff(sample))
+                .unwrap()
+        }
+
+        #[test]
+        fn strong_onset_has_higher_confidence() {
+            let detectors: Vec<Box<dyn OnsetDetector>> = vec![
+                Box::new(SimpleEnvelopeDetector::default()),
+                Box::new(SpectralFluxDetector::default()),
+                Box::new(HfcDetector::default()),
+                Box::new(ComplexDomainDetector::default()),
+            ];
+            let audio_data = strong_and_weak_notes();
+
+            for detector in detectors {
+                let onsets = detector.get_onsets(&audio_data);
+
+                let strong = closest_onset(&onsets, 5000);
+                let weak = closest_onset(&onsets, 25000);
+
+                assert!(strong.sample.abs_diff(5000) <= 1024);
+                assert!(weak.sample.abs_diff(25000) <= 1024);
+                assert!(strong.confidence > weak.confidence, "onsets {:?}", onsets);
+                assert!(onsets
+                    .iter()
+                    .all(|onset| onset.confidence > 0.0 && onset.confidence <= 1.0));
+            }
+        }
+    }
+
+    mod simple_envelope_detector_tests {
+        use crate::onset_detection::{OnsetDetector, SimpleEnvelopeDetector};
+        use crate::test_utils::sine_burst;
+
+        #[test]
+        fn onset_is_located_within_frame() {
+            // Silence followed by a 440 Hz sine starting at sample 5000, which
+            // is 200 samples into the fourth onset frame
+            let audio_data = sine_burst(440.0, 5000);
+
+            let onsets: Vec<usize> =
+                SimpleEnvelopeDetector::default().get_onset_samples(&audio_data);
+
+            assert_eq!(1, onsets.len());
+            assert!(onsets[0].abs_diff(5000) <= 100, "onset at {}", onsets[0]);
+        }
+    }
+
+    mod complex_domain_detector_tests {
+        use crate::audio_utils::AudioData;
+        use crate::onset_detection::{ComplexDomainDetector, OnsetDetector};
+        use crate::test_utils::legato;
+
+        #[test]
+        fn legato_transition_is_detected() {
+            let detector = ComplexDomainDetector::default();
+
+            let onsets: Vec<usize> = detector.get_onset_samples(&legato(5000, 25000));
+
+            assert_eq!(2, onsets.len(), "onsets at {:?}", onsets);
+            assert!(onsets[0].abs_diff(5000) <= detector.frame_width);
+            assert!(
+                onsets[1].abs_diff(25000) <= detector.frame_width / 2,
+                "onset at {}",
+                onsets[1]
+            );
+        }
+
+        #[test]
+        fn silence_has_no_onsets() {
+            let audio_data = AudioData::from_raw_samples(vec![0.0; 20000], 44100);
+
+            assert!(ComplexDomainDetector::default()
+                .get_onsets(&audio_data)
+                .is_empty());
+        }
+    }
+
+    mod hfc_detector_tests {
+        use crate::audio_utils::AudioData;
+        use crate::onset_detection::{HfcDetector, OnsetDetector};
+        use crate::test_utils::{sine, sine_burst};
+
+        /// Creates silence followed by a drum hit at each of the given
+        /// positions, each of which is an impulse followed by exponentially
+        /// decaying noise
+        fn drum_hits(positions: &[usize]) -> AudioData {
+            let mut samples = vec![0.0; 44100];
+            // Deterministic pseudo-random noise
+            let mut state: u32 = 12345;
+
+            for &position in positions {
+                samples[position] = 1.0;
+                for (i, sample) in samples.iter_mut().enumerate().skip(position + 1) {
+                    state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                    let noise = (state >> 16) as f64 / 32768.0 - 1.0;
+                    let t = (i - position) as f64 / 44100.0;
+                    *sample += 0.5 * noise * (-t / 0.03).exp();
+                }
+            }
+
+            AudioData::from_raw_samples(samples, 44100)
+        }
+
+        #[test]
+        fn drum_hit_is_located_within_one_hop() {
+            let detector = HfcDetector::default();
+
+            let onsets: Vec<usize> = detector.get_onset_samples(&drum_hits(&[10000]));
+
+            assert_eq!(1, onsets.len());
+            assert!(
+                onsets[0].abs_diff(10000) <= detector.frame_width / 2,
+                "onset at {}",
+                onsets[0]
+            );
+        }
+
+        #[test]
+        fn separate_drum_hits_are_detected() {
+            let onsets: Vec<usize> =
+                HfcDetector::default().get_onset_samples(&drum_hits(&[5000, 20000, 30000]));
+
+            assert_eq!(3, onsets.len());
+            for (onset, expected) in onsets.iter().zip([5000, 20000, 30000]) {
+                assert!(onset.abs_diff(expected) <= 512, "onset at {onset}");
+            }
+        }
+
+        #[test]
+        fn sine_is_detected_once_where_it_starts() {
+            let detector = HfcDetector::default();
+            let audio_data = AudioData::from_raw_samples(
+                sine_burst(440.0, 10000)
+                    .samples
+                    .iter()
+                    .map(|x| 0.5 * x)
+                    .collect(),
+                44100,
+            );
+
+            let onsets: Vec<usize> = detector.get_onset_samples(&audio_data);
+
+            assert_eq!(1, onsets.len(), "onsets at {:?}", onsets);
+            assert!(
+                onsets[0].abs_diff(10000) <= detector.frame_width,
+                "onset at {}",
+                onsets[0]
+            );
+        }
+
+        #[test]
+        fn steady_tone_has_one_onset() {
+            let onsets = HfcDetector::default().get_onsets(&sine(440.0, 44100, 44100));
+
+            assert_eq!(1, onsets.len(), "onsets {:?}", onsets);
+        }
+
+        #[test]
+        fn silence_has_no_onsets() {
+            let audio_data = AudioData::from_raw_samples(vec![0.0; 20000], 44100);
+
+            assert!(HfcDetector::default().get_onsets(&audio_data).is_empty());
+        }
+    }
+
+    mod spectral_flux_detector_tests {
+        use crate::audio_utils::AudioData;
+        use crate::onset_detection::{OnsetDetector, SpectralFluxDetector};
+        use crate::test_utils::sine_burst;
+        use crate::transcription::Transcriber;
+        use std::path::PathBuf;
+
+        fn onset_count(filename: &str) -> usize {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test");
+            filepath.push(filename);
+            let audio_data =
+                Transcriber::scale_samples(&AudioData::read_wav_file(filepath).unwrap());
+
+            SpectralFluxDetector::default()
+                .get_onsets(&audio_data)
+                .len()
+        }
+
+        #[test]
+        fn fixtures_have_correct_number_of_onsets() {
+            assert_eq!(3, onset_count("example_audio.wav"));
+            assert_eq!(8, onset_count("piano_C_Major_scale.wav"));
+            assert_eq!(14, onset_count("twinkle_twinkle_little_star.wav"));
+        }
+
+        #[test]
+        fn onset_is_located_within_frame() {
+            let audio_data = sine_burst(440.0, 5000);
+
+            let onsets: Vec<usize> = SpectralFluxDetector::default().get_onset_samples(&audio_data);
+
+            assert_eq!(1, onsets.len());
+            assert!(onsets[0].abs_diff(5000) <= 100, "onset at {}", onsets[0]);
+        }
+
+        #[test]
+        fn silence_has_no_onsets() {
+            let audio_data = AudioData::from_raw_samples(vec![0.0; 20000], 44100);
+
+            assert!(SpectralFluxDetector::default()
+                .get_onsets(&audio_data)
+                .is_empty());
+        }
+    }
+}
@@ -20,12 +20,86 @@
 
 use rustfft::{num_complex::Complex, FftPlanner};
 
+/// Smallest spectrum peak amplitude which the harmonic product spectrum
+/// detector will consider to be a pitched sound
+const HPS_NOISE_FLOOR: f64 = 1e-3;
+
+/// Factor by which frames are zero-padded before calculating the spectrum
+/// used by the harmonic product spectrum detector
+const HPS_ZERO_PADDING: usize = 4;
+
+/// Calculates the discrete Fourier transform of the samples, zero-padded to
+/// the given length
+pub(crate) fn forward_fft(samples: &[f64], fft_length: usize) -> Vec<Complex<f64>> {
+    let mut buffer: Vec<Complex<f64>> = samples
+        .iter()
+        .map(|x| Complex { re: *x, im: 0.0 })
+        .collect();
+    buffer.resize(fft_length, Complex { re: 0.0, im: 0.0 });
+
+    let mut planner = FftPlanner::new();
+    planner.plan_fft_forward(fft_length).process(&mut buffer);
+
+    buffer
+}
+
+/// Calculates the inverse discrete Fourier transform of the spectrum, scaled
+/// by the inverse of its length, and returns the real parts
+pub(crate) fn inverse_fft(spectrum: Vec<Complex<f64>>) -> Vec<f64> {
+    let fft_length = spectrum.len();
+    let mut buffer = spectrum;
+
+    let mut planner = FftPlanner::new();
+    planner.plan_fft_inverse(fft_length).process(&mut buffer);
+
+    buffer.iter().map(|x| x.re / fft_length as f64).collect()
+}
+
+/// Multiplies the samples by a Hann window
+pub(crate) fn hann_window(samples: &[f64]) -> Vec<f64> {
+    let num_samples = samples.len();
+
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            x * (0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / num_samples as f64).cos())
+        })
+        .collect()
+}
+
+/// Struct representing a detected pitch along with the detector's
+/// confidence in it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PitchResult {
+    /// Detected pitch (in Hz)
+    pub frequency: f64,
+
+    /// Confidence in the detected pitch, between 0 and 1
+    pub confidence: f64,
+}
+
+/// Struct representing a pitch which the McLeod Pitch Method considered
+/// when choosing the pitch of a frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PitchCandidate {
+    /// Frequency (in Hz) corresponding to the lag of the NSDF peak
+    pub frequency: f64,
+
+    /// Height of the NSDF peak, at most 1
+    pub nsdf_value: f64,
+}
+
 pub trait PitchDetector {
     /// Attempts to detect the pitch in the input samples
     ///
     /// If a pitch is detected, the frequency is returned, otherwise
     /// None is returned.
-    fn get_pitch(self, samples: Vec<f64>) -> Option<f64>;
+    fn get_pitch(&self, samples: Vec<f64>) -> Option<f64>;
+
+    /// Returns the short name of the pitch detection algorithm, for use in
+    /// logs and when displaying configuration
+    fn name(&self) -> &'static str;
 }
 
 /// Struct for the McLeod Pitch Method pitch detection algorithm
@@ -33,6 +107,9 @@ pub trait PitchDetector {
 pub struct Mpm {
     threshold: f64,
     sample_rate: u32,
+    nsdf_median_window: Option<usize>,
+    octave_correction: bool,
+    frequency_range: Option<(f64, f64)>,
 }
 
 impl PitchDetector for Mpm {
@@ -44,13 +121,13 @@ impl PitchDetector for Mpm {
     ///
     /// If a pitch is detected, the frequency is returned, otherwise
     /// None is returned.
-    fn get_pitch(self, samples: Vec<f64>) -> Option<f64> {
-        let nsdf = Mpm::fast_nsdf(samples);
+    fn get_pitch(&self, samples: Vec<f64>) -> Option<f64> {
+        self.get_pitch_with_confidence(samples)
+            .map(|result| result.frequency)
+    }
 
-        match self.get_mpm_peak(nsdf) {
-            Some(peak) => Some(self.sample_rate as f64 / peak.0),
-            None => None,
-        }
+    fn name(&self) -> &'static str {
+        "MPM"
     }
 }
 
@@ -60,16 +137,225 @@ impl Mpm {
     /// # Arguments
     ///
     /// * `threshold` - A coefficient used when deciding which pitch
-    ///                 candidate to choose as the final estimate
+    ///   candidate to choose as the final estimate
     /// * `sample_rate` - The sample rate of the audio which the detector will
-    ///                   be used on
+    ///   be used on
     pub fn new(threshold: f64, sample_rate: u32) -> Mpm {
         Mpm {
             threshold,
             sample_rate,
+            nsdf_median_window: None,
+            octave_correction: true,
+            frequency_range: None,
+        }
+    }
+
+    /// Attempts to detect the pitch in the input samples using the
+    /// McLeod Pitch Method, and returns it along with a confidence score
+    ///
+    /// The confidence is the height of the chosen normalized square
+    /// difference function (NSDF) peak divided by the height of the largest
+    /// NSDF peak. Values near 1 indicate a highly periodic sound, while values
+    /// near the threshold indicate an ambiguous pitch.
+    ///
+    /// If a pitch is detected, the result is returned, otherwise None is
+    /// returned.
+    pub fn get_pitch_with_confidence(&self, samples: Vec<f64>) -> Option<PitchResult> {
+        self.get_pitch_from_nsdf(&Mpm::fast_nsdf(samples))
+    }
+
+    /// Enables median filtering of the normalized square difference function
+    /// (NSDF) values before peak picking
+    ///
+    /// The median filter suppresses narrow spikes in the NSDF caused by
+    /// noise, which would otherwise be picked as pitch candidates, while
+    /// preserving the broader peaks corresponding to the pitch period.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Width (in samples) of the median filter, typically 3 or 5.
+    ///   Must be odd.
+    pub fn with_nsdf_median_filter(mut self, window: usize) -> Mpm {
+        assert!(window % 2 == 1, "median filter window must be odd");
+
+        self.nsdf_median_window = Some(window);
+        self
+    }
+
+    /// Enables or disables the correction of octave errors, where the
+    /// detected pitch is an octave below the true pitch
+    ///
+    /// Octave correction is enabled by default. See `detect_octave_error`.
+    pub fn with_octave_correction(mut self, enabled: bool) -> Mpm {
+        self.octave_correction = enabled;
+        self
+    }
+
+    /// Restricts the detected pitch to the given frequency range
+    ///
+    /// NSDF peaks at lags corresponding to frequencies outside the range are
+    /// not considered as pitch candidates, which prevents octave errors when
+    /// the range of the instrument is known, for example 80 to 1000 Hz for a
+    /// flute.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_frequency` - The lowest pitch (in Hz) which can be detected.
+    ///   Must be positive.
+    /// * `max_frequency` - The highest pitch (in Hz) which can be detected.
+    ///   Must be greater than `min_frequency`.
+    pub fn with_range(mut self, min_frequency: f64, max_frequency: f64) -> Mpm {
+        assert!(min_frequency > 0.0, "minimum frequency must be positive");
+        assert!(
+            max_frequency > min_frequency,
+            "maximum frequency must be greater than minimum frequency"
+        );
+
+        self.frequency_range = Some((min_frequency, max_frequency));
+        self
+    }
+
+    /// Checks whether a lag (in samples) corresponds to a frequency within
+    /// the detector's frequency range
+    fn lag_in_range(&self, lag: f64) -> bool {
+        match self.frequency_range {
+            Some((min_frequency, max_frequency)) => {
+                let frequency = self.sample_rate as f64 / lag;
+                (min_frequency..=max_frequency).contains(&frequency)
+            }
+            None => true,
         }
     }
 
+    /// Checks whether a detected pitch is an octave below the true pitch of
+    /// the samples, and returns the corrected pitch
+    ///
+    /// For sounds with a strong second harmonic, the NSDF peak at twice the
+    /// true period can be chosen instead of the peak at the true period. If
+    /// the NSDF peak at half the detected period is greater than the
+    /// threshold multiplied by the NSDF value at the detected period, the
+    /// pitch is doubled. Otherwise the detected pitch is returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `detected_hz` - The pitch (in Hz) detected in the samples
+    /// * `samples` - The samples in which the pitch was detected
+    pub fn detect_octave_error(&self, detected_hz: f64, samples: &[f64]) -> f64 {
+        let nsdf = Mpm::fast_nsdf(samples.to_vec());
+        let lag = self.sample_rate as f64 / detected_hz;
+
+        let Some(peak) = Mpm::refine_peak(&nsdf, lag, 1) else {
+            return detected_hz;
+        };
+
+        self.sample_rate as f64 / self.correct_octave_error(&nsdf, peak).0
+    }
+
+    /// Replaces an NSDF peak with the peak at half its lag if the peak at
+    /// half the lag is greater than the threshold multiplied by the value of
+    /// the original peak
+    fn correct_octave_error(&self, nsdf: &[f64], peak: (f64, f64)) -> (f64, f64) {
+        match Mpm::refine_peak(nsdf, peak.0 / 2.0, 1) {
+            Some(half_lag_peak)
+                if half_lag_peak.1 > self.threshold * peak.1
+                    && self.lag_in_range(half_lag_peak.0) =>
+            {
+                half_lag_peak
+            }
+            _ => peak,
+        }
+    }
+
+    /// Estimates the pitch from the normalized square difference function
+    /// values of a frame
+    ///
+    /// If median filtering is enabled, the filtered NSDF is used to choose
+    /// the peak, and the position of the peak is then refined using the
+    /// unfiltered NSDF, since the filter flattens the top of the peak.
+    /// If octave correction is enabled, it is applied to the chosen peak
+    /// before the peak is refined.
+    fn get_pitch_from_nsdf(&self, nsdf: &[f64]) -> Option<PitchResult> {
+        let filtered_nsdf = self.filter_nsdf(nsdf);
+        let (peak, max_peak) = self.get_mpm_peak(filtered_nsdf.clone())?;
+        let peak = self.adjust_peak(nsdf, &filtered_nsdf, peak)?;
+
+        Some(PitchResult {
+            frequency: self.sample_rate as f64 / peak.0,
+            confidence: (peak.1 / max_peak).clamp(0.0, 1.0),
+        })
+    }
+
+    /// Returns the NSDF which peaks are chosen from, which is median filtered
+    /// if median filtering is enabled
+    fn filter_nsdf(&self, nsdf: &[f64]) -> Vec<f64> {
+        match self.nsdf_median_window {
+            Some(window) => Mpm::median_filter(nsdf, window),
+            None => nsdf.to_vec(),
+        }
+    }
+
+    /// Applies octave correction, if it is enabled, to a peak chosen from the
+    /// filtered NSDF, and then refines its position using the unfiltered NSDF
+    /// if median filtering is enabled
+    fn adjust_peak(
+        &self,
+        nsdf: &[f64],
+        filtered_nsdf: &[f64],
+        peak: (f64, f64),
+    ) -> Option<(f64, f64)> {
+        let peak = if self.octave_correction {
+            self.correct_octave_error(filtered_nsdf, peak)
+        } else {
+            peak
+        };
+
+        match self.nsdf_median_window {
+            Some(window) => Mpm::refine_peak(nsdf, peak.0, window / 2),
+            None => Some(peak),
+        }
+    }
+
+    /// Finds the largest value within `radius` samples of an approximate
+    /// peak position, and uses quadratic interpolation to estimate the
+    /// position of the peak
+    fn refine_peak(nsdf: &[f64], position: f64, radius: usize) -> Option<(f64, f64)> {
+        let centre = position.round() as usize;
+        let start = std::cmp::max(centre.saturating_sub(radius), 1);
+        let end = std::cmp::min(centre + radius, nsdf.len().saturating_sub(2));
+
+        let i = (start..=end).max_by(|a, b| nsdf[*a].total_cmp(&nsdf[*b]))?;
+
+        Mpm::quadratic_peak_interp((i - 1, nsdf[i - 1]), (i, nsdf[i]), (i + 1, nsdf[i + 1]))
+            .or(Some((i as f64, nsdf[i])))
+    }
+
+    /// Applies a median filter with the given window width to the values
+    ///
+    /// Near the edges, the window is truncated to the available values.
+    fn median_filter(values: &[f64], window: usize) -> Vec<f64> {
+        let half_window = window / 2;
+
+        (0..values.len())
+            .map(|i| {
+                let start = i.saturating_sub(half_window);
+                let end = std::cmp::min(i + half_window + 1, values.len());
+
+                let mut neighbourhood = values[start..end].to_vec();
+                neighbourhood.sort_by(|a, b| a.total_cmp(b));
+                neighbourhood[neighbourhood.len() / 2]
+            })
+            .collect()
+    }
+
+    /// Calculates the normalized square difference function (NSDF) of the
+    /// samples, which the McLeod Pitch Method searches for peaks
+    ///
+    /// The value at each lag is between -1 and 1, and peaks occur at lags
+    /// which are multiples of the period of the input. See `fast_nsdf`.
+    pub fn get_nsdf(samples: Vec<f64>) -> Vec<f64> {
+        Mpm::fast_nsdf(samples)
+    }
+
     /// Calculates the normalized square difference function (NSDF) values,
     /// as described by Philip McLeod and Geoff Wyvill in
     /// "A Smarter Way to Find Pitch" (2005)
@@ -95,30 +381,15 @@ impl Mpm {
 
     /// Computes the autocorrelation of the input samples
     fn fast_autoc(samples: Vec<f64>) -> Vec<f64> {
-        // Zero-pad the samples
+        // Zero-pad the samples to avoid circular correlation
         let fft_length = 2 * samples.len();
-        let mut padded_samples = samples;
-        padded_samples.resize(fft_length, 0.0);
 
-        let mut planner = FftPlanner::new();
-        let fft_forward = planner.plan_fft_forward(fft_length);
-
-        let mut buffer: Vec<Complex<f64>> = padded_samples
+        let power_spectrum: Vec<Complex<f64>> = forward_fft(&samples, fft_length)
             .iter()
-            .map(|x| Complex { re: *x, im: 0.0 })
+            .map(|x| x * x.conj())
             .collect();
 
-        fft_forward.process(&mut buffer);
-
-        let scale_factor = 1.0 / (fft_length as f64).sqrt();
-        let mut power_spectrum: Vec<Complex<f64>> =
-            buffer.iter().map(|x| scale_factor * x * x.conj()).collect();
-
-        let fft_inverse = planner.plan_fft_inverse(fft_length);
-
-        fft_inverse.process(&mut power_spectrum);
-
-        power_spectrum.iter().map(|x| scale_factor * x.re).collect()
+        inverse_fft(power_spectrum)
     }
 
     /// Calculates the values used in the calculations of the normalized square
@@ -126,14 +397,20 @@ impl Mpm {
     ///
     /// The number of values calculated is equal to the number of samples.
     fn square_sums(samples: Vec<f64>) -> Vec<f64> {
-        let mut sq_sums = vec![0.0; samples.len()];
+        let n = samples.len();
+        let mut sq_sums = vec![0.0; n];
 
-        for tau in 0..samples.len() {
-            let mut sq_sum = 0.0;
-            for i in 0..samples.len() - tau {
-                sq_sum += samples[i] * samples[i] + samples[i + tau] * samples[i + tau];
-            }
-            sq_sums[tau] = sq_sum;
+        if n == 0 {
+            return sq_sums;
+        }
+
+        sq_sums[0] = 2.0 * samples.iter().map(|x| x * x).sum::<f64>();
+
+        // Each increase in lag removes one term from each end of the sum
+        for tau in 1..n {
+            sq_sums[tau] = sq_sums[tau - 1]
+                - samples[n - tau] * samples[n - tau]
+                - samples[tau - 1] * samples[tau - 1];
         }
         sq_sums
     }
@@ -177,9 +454,43 @@ impl Mpm {
         Some((x, a * x * x + b * x + c))
     }
 
-    /// Peak picking algorithm described by Philip McLeod and Geoff Wyvill
-    /// in "A Smarter Way to Find Pitch" (2005)
-    fn get_mpm_peak(&self, nsdf: Vec<f64>) -> Option<(f64, f64)> {
+    /// Finds every pitch candidate in the samples, which are the NSDF key
+    /// maxima above the threshold used to pick the pitch
+    ///
+    /// The key maxima are found, corrected and refined in the same way as the
+    /// peak chosen by `get_pitch_with_confidence`, so median filtering and
+    /// octave correction apply to every candidate. Candidates which are
+    /// corrected to the same peak are only returned once.
+    ///
+    /// Returns the candidates sorted by NSDF value, from highest to lowest.
+    pub fn get_all_peaks(&self, samples: Vec<f64>) -> Vec<PitchCandidate> {
+        let nsdf = Mpm::fast_nsdf(samples);
+        let filtered_nsdf = self.filter_nsdf(&nsdf);
+        let (peaks, max_peak) = self.get_key_maxima(&filtered_nsdf);
+
+        let mut candidates: Vec<PitchCandidate> = peaks
+            .into_iter()
+            .filter(|peak| peak.1 > self.threshold * max_peak)
+            .filter_map(|peak| self.adjust_peak(&nsdf, &filtered_nsdf, peak))
+            .map(|peak| PitchCandidate {
+                frequency: self.sample_rate as f64 / peak.0,
+                nsdf_value: peak.1,
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| b.nsdf_value.total_cmp(&a.nsdf_value));
+        candidates.dedup();
+
+        candidates
+    }
+
+    /// Finds the highest interpolated peak of the NSDF between each pair of
+    /// positive and negative zero crossings, after the first negative zero
+    /// crossing
+    ///
+    /// Returns the peaks in order of lag along with the height of the largest
+    /// peak.
+    fn get_key_maxima(&self, nsdf: &[f64]) -> (Vec<(f64, f64)>, f64) {
         // Find first zero_crossing
         let mut start_index = 0;
 
@@ -208,7 +519,8 @@ impl Mpm {
                     && i > 0
                     && i < nsdf.len() - 1
                     && nsdf[i - 1] < nsdf[i]
-                    && nsdf[i + 1] < nsdf[i]
+                    && nsdf[i + 1] <= nsdf[i]
+                    && self.lag_in_range(i as f64)
                 {
                     local_peak = (i as f64, nsdf[i]);
                     interp_peak = match Mpm::quadratic_peak_interp(
@@ -233,9 +545,843 @@ impl Mpm {
             i += 1;
         }
 
+        (peaks, max_peak.1)
+    }
+
+    /// Peak picking algorithm described by Philip McLeod and Geoff Wyvill
+    /// in "A Smarter Way to Find Pitch" (2005)
+    ///
+    /// Returns the chosen peak along with the height of the largest peak.
+    fn get_mpm_peak(&self, nsdf: Vec<f64>) -> Option<((f64, f64), f64)> {
+        let (peaks, max_peak) = self.get_key_maxima(&nsdf);
+
         // Return the first peak above a certain threshold
         peaks
             .into_iter()
-            .find(|x| x.1 > self.threshold * max_peak.1)
+            .find(|x| x.1 > self.threshold * max_peak)
+            .map(|peak| (peak, max_peak))
+    }
+}
+
+/// Struct for the YIN pitch detection algorithm
+#[derive(Clone)]
+pub struct Yin {
+    threshold: f64,
+    sample_rate: u32,
+}
+
+impl PitchDetector for Yin {
+    /// Attempts to detect the pitch in the input samples using the
+    /// YIN algorithm
+    ///
+    /// The algorithm is described by Alain de Cheveigné and Hideki Kawahara
+    /// in "YIN, a fundamental frequency estimator for speech and music"
+    /// (2002).
+    ///
+    /// If a pitch is detected, the frequency is returned, otherwise
+    /// None is returned.
+    fn get_pitch(&self, samples: Vec<f64>) -> Option<f64> {
+        let cmndf = Yin::cmndf(Yin::difference(&samples));
+        let tau = self.absolute_threshold(&cmndf)?;
+
+        let period = if tau + 1 < cmndf.len() {
+            Mpm::quadratic_peak_interp(
+                (tau - 1, cmndf[tau - 1]),
+                (tau, cmndf[tau]),
+                (tau + 1, cmndf[tau + 1]),
+            )
+            .map_or(tau as f64, |trough| trough.0)
+        } else {
+            tau as f64
+        };
+
+        Some(self.sample_rate as f64 / period)
+    }
+
+    fn name(&self) -> &'static str {
+        "YIN"
+    }
+}
+
+impl Yin {
+    /// Creates a new YIN pitch detector instance
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The largest cumulative mean normalized difference
+    ///   value accepted as a period, typically between 0.1 and 0.2
+    /// * `sample_rate` - The sample rate of the audio which the detector will
+    ///   be used on
+    pub fn new(threshold: f64, sample_rate: u32) -> Yin {
+        Yin {
+            threshold,
+            sample_rate,
+        }
+    }
+
+    /// Calculates the difference function values for lags up to half the
+    /// number of samples, using a window of half the number of samples
+    fn difference(samples: &[f64]) -> Vec<f64> {
+        let window = samples.len() / 2;
+
+        (0..window)
+            .map(|tau| {
+                (0..window)
+                    .map(|j| (samples[j] - samples[j + tau]).powi(2))
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Calculates the cumulative mean normalized difference function (CMNDF)
+    /// values from the difference function values
+    ///
+    /// The value at lag 0 is 1. Lags where the cumulative sum of the
+    /// difference function is zero, such as in silent audio, also have the
+    /// value 1.
+    fn cmndf(difference: Vec<f64>) -> Vec<f64> {
+        let mut cmndf = vec![1.0; difference.len()];
+        let mut running_sum = 0.0;
+
+        for tau in 1..difference.len() {
+            running_sum += difference[tau];
+            if running_sum > f64::EPSILON {
+                cmndf[tau] = difference[tau] * tau as f64 / running_sum;
+            }
+        }
+
+        cmndf
+    }
+
+    /// Finds the first lag where the CMNDF is below the threshold, then
+    /// follows the CMNDF down to the bottom of the trough
+    ///
+    /// Returns None if the CMNDF never drops below the threshold.
+    fn absolute_threshold(&self, cmndf: &[f64]) -> Option<usize> {
+        let mut tau = (2..cmndf.len()).find(|tau| cmndf[*tau] < self.threshold)?;
+
+        while tau + 1 < cmndf.len() && cmndf[tau + 1] < cmndf[tau] {
+            tau += 1;
+        }
+
+        Some(tau)
+    }
+}
+
+/// Struct for the Harmonic Product Spectrum pitch detection algorithm
+#[derive(Clone)]
+pub struct Hps {
+    harmonics: usize,
+    sample_rate: u32,
+}
+
+impl PitchDetector for Hps {
+    /// Attempts to detect the pitch in the input samples using the
+    /// Harmonic Product Spectrum
+    ///
+    /// The magnitude spectrum is multiplied by copies of itself downsampled
+    /// by each factor up to the number of harmonics, so that the harmonics of
+    /// the fundamental line up at the fundamental frequency. This finds the
+    /// fundamental even when the fundamental partial itself is weak.
+    ///
+    /// If a pitch is detected, the frequency is returned, otherwise
+    /// None is returned, including when the largest peak in the spectrum is
+    /// below the noise floor.
+    fn get_pitch(&self, samples: Vec<f64>) -> Option<f64> {
+        let fft_length = samples.len().next_power_of_two() * HPS_ZERO_PADDING;
+        let spectrum = Hps::magnitude_spectrum(samples, fft_length);
+
+        let peak_amplitude = spectrum.iter().fold(0.0, |max: f64, x| max.max(*x));
+        if peak_amplitude < HPS_NOISE_FLOOR {
+            return None;
+        }
+
+        let product = self.harmonic_product(&spectrum);
+
+        // Skip the DC bin
+        let peak = (1..product.len()).max_by(|a, b| product[*a].total_cmp(&product[*b]))?;
+
+        let bin = if peak + 1 < spectrum.len() {
+            Mpm::quadratic_peak_interp(
+                (peak - 1, spectrum[peak - 1]),
+                (peak, spectrum[peak]),
+                (peak + 1, spectrum[peak + 1]),
+            )
+            .map_or(peak as f64, |interp_peak| interp_peak.0)
+        } else {
+            peak as f64
+        };
+
+        Some(bin * self.sample_rate as f64 / fft_length as f64)
+    }
+
+    fn name(&self) -> &'static str {
+        "HPS"
+    }
+}
+
+impl Hps {
+    /// Creates a new Harmonic Product Spectrum pitch detector instance
+    ///
+    /// # Arguments
+    ///
+    /// * `harmonics` - Number of harmonics (including the fundamental) whose
+    ///   spectra are multiplied together, typically 5. Must be non-zero.
+    /// * `sample_rate` - The sample rate of the audio which the detector will
+    ///   be used on
+    pub fn new(harmonics: usize, sample_rate: u32) -> Hps {
+        assert_ne!(0, harmonics, "number of harmonics must be non-zero");
+
+        Hps {
+            harmonics,
+            sample_rate,
+        }
+    }
+
+    /// Calculates the magnitude spectrum of the Hann windowed samples,
+    /// zero-padded to the given length, up to the Nyquist frequency
+    ///
+    /// The magnitudes are divided by the number of samples, so a full-scale
+    /// sine wave has a peak magnitude of roughly 0.25.
+    fn magnitude_spectrum(samples: Vec<f64>, fft_length: usize) -> Vec<f64> {
+        let spectrum = forward_fft(&hann_window(&samples), fft_length);
+
+        spectrum[..=fft_length / 2]
+            .iter()
+            .map(|x| x.norm() / samples.len() as f64)
+            .collect()
+    }
+
+    /// Multiplies the spectrum by copies of itself downsampled by each factor
+    /// from 2 up to the number of harmonics
+    ///
+    /// The returned product only covers the bins where every downsampled
+    /// copy is defined.
+    fn harmonic_product(&self, spectrum: &[f64]) -> Vec<f64> {
+        let length = (spectrum.len() - 1) / self.harmonics + 1;
+
+        (0..length)
+            .map(|bin| {
+                (1..=self.harmonics)
+                    .map(|harmonic| spectrum[bin * harmonic])
+                    .product()
+            })
+            .collect()
+    }
+}
+
+/// Struct for the cepstrum pitch detection algorithm
+#[derive(Clone)]
+pub struct Cepstrum {
+    min_frequency: f64,
+    max_frequency: f64,
+    sample_rate: u32,
+}
+
+impl PitchDetector for Cepstrum {
+    /// Attempts to detect the pitch in the input samples using the
+    /// power cepstrum
+    ///
+    /// The cepstrum is the inverse Fourier transform of the logarithm of the
+    /// power spectrum. The regularly spaced harmonics of a pitched sound
+    /// produce a peak in the cepstrum at the quefrency equal to the period
+    /// of the sound. The largest peak within the detector's frequency range
+    /// is chosen.
+    ///
+    /// If a pitch is detected, the frequency is returned, otherwise
+    /// None is returned, including for silent input and input with fewer
+    /// than 4 samples.
+    fn get_pitch(&self, samples: Vec<f64>) -> Option<f64> {
+        if samples.len() < 4 || samples.iter().map(|x| x * x).sum::<f64>() < f64::EPSILON {
+            return None;
+        }
+
+        let cepstrum = Cepstrum::power_cepstrum(&samples);
+
+        // Quefrency range corresponding to the frequency range, excluding
+        // quefrencies where the neighbouring values are not available
+        let min_quefrency = std::cmp::max(
+            (self.sample_rate as f64 / self.max_frequency).floor() as usize,
+            1,
+        );
+        let max_quefrency = std::cmp::min(
+            (self.sample_rate as f64 / self.min_frequency).ceil() as usize,
+            cepstrum.len() / 2 - 1,
+        );
+
+        let peak =
+            (min_quefrency..=max_quefrency).max_by(|a, b| cepstrum[*a].total_cmp(&cepstrum[*b]))?;
+
+        let quefrency = Mpm::quadratic_peak_interp(
+            (peak - 1, cepstrum[peak - 1]),
+            (peak, cepstrum[peak]),
+            (peak + 1, cepstrum[peak + 1]),
+        )
+        .map_or(peak as f64, |interp_peak| interp_peak.0);
+
+        Some(self.sample_rate as f64 / quefrency)
+    }
+
+    fn name(&self) -> &'static str {
+        "CEPSTRUM"
+    }
+}
+
+impl Cepstrum {
+    /// Creates a new cepstrum pitch detector instance
+    ///
+    /// # Arguments
+    ///
+    /// * `min_frequency` - The lowest pitch (in Hz) which can be detected,
+    ///   for example 60 Hz. Must be positive.
+    /// * `max_frequency` - The highest pitch (in Hz) which can be detected,
+    ///   for example 1000 Hz. Must be greater than `min_frequency`.
+    /// * `sample_rate` - The sample rate of the audio which the detector will
+    ///   be used on
+    pub fn new(min_frequency: f64, max_frequency: f64, sample_rate: u32) -> Cepstrum {
+        assert!(min_frequency > 0.0, "minimum frequency must be positive");
+        assert!(
+            max_frequency > min_frequency,
+            "maximum frequency must be greater than minimum frequency"
+        );
+
+        Cepstrum {
+            min_frequency,
+            max_frequency,
+            sample_rate,
+        }
+    }
+
+    /// Calculates the power cepstrum of the Hann windowed samples
+    ///
+    /// The samples are zero-padded to the next power of two, and the number
+    /// of values returned is equal to the padded length.
+    fn power_cepstrum(samples: &[f64]) -> Vec<f64> {
+        let fft_length = samples.len().next_power_of_two();
+
+        let log_power_spectrum: Vec<Complex<f64>> = forward_fft(&hann_window(samples), fft_length)
+            .iter()
+            .map(|x| Complex {
+                re: (x.norm_sqr() + f64::EPSILON).ln(),
+                im: 0.0,
+            })
+            .collect();
+
+        inverse_fft(log_power_spectrum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod get_all_peaks_tests {
+        use crate::pitch_detection::Mpm;
+
+        /// Decaying sine wave, so that the NSDF falls off at multiples of the
+        /// period instead of staying at 1
+        fn sine(frequency: f64) -> Vec<f64> {
+            (0..4096)
+                .map(|i| {
+                    let t = i as f64 / 44100.0;
+                    (-5.0 * t).exp() * (2.0 * std::f64::consts::PI * frequency * t).sin()
+                })
+                .collect()
+        }
+
+        #[test]
+        fn strongest_candidate_is_pitch_of_sine() {
+            let candidates = Mpm::new(0.7, 44100).get_all_peaks(sine(440.0));
+
+            assert!((candidates[0].frequency - 440.0).abs() < 1.0);
+            assert!(candidates
+                .windows(2)
+                .all(|w| w[0].nsdf_value >= w[1].nsdf_value));
+        }
+
+        #[test]
+        fn subharmonics_are_weaker_candidates() {
+            let candidates = Mpm::new(0.7, 44100).get_all_peaks(sine(440.0));
+
+            // Multiples of the period are also peaks of the NSDF
+            let subharmonic = candidates
+                .iter()
+                .find(|c| (c.frequency - 220.0).abs() < 1.0)
+                .unwrap();
+            assert!(subharmonic.nsdf_value < candidates[0].nsdf_value);
+        }
+
+        #[test]
+        fn candidates_are_refined_like_detected_pitch() {
+            let mpm = Mpm::new(0.7, 44100).with_nsdf_median_filter(5);
+
+            let candidates = mpm.get_all_peaks(sine(440.0));
+            let pitch = mpm.get_pitch_with_confidence(sine(440.0)).unwrap();
+
+            assert_eq!(pitch.frequency, candidates[0].frequency);
+        }
+
+        #[test]
+        fn candidates_respect_frequency_range() {
+            let candidates = Mpm::new(0.7, 44100)
+                .with_range(300.0, 1000.0)
+                .get_all_peaks(sine(440.0));
+
+            assert_eq!(1, candidates.len());
+            assert!((candidates[0].frequency - 440.0).abs() < 1.0);
+        }
+    }
+
+    mod get_nsdf_tests {
+        use crate::pitch_detection::Mpm;
+        use crate::test_utils::sine_samples;
+
+        #[test]
+        fn first_peak_is_at_period_of_sine() {
+            let nsdf = Mpm::get_nsdf(sine_samples(440.0, 44100, 2048));
+
+            assert_eq!(2048, nsdf.len());
+            assert!((nsdf[0] - 1.0).abs() < 1e-9);
+
+            // The first peak after the NSDF first becomes negative
+            let first_negative = nsdf.iter().position(|x| *x < 0.0).unwrap();
+            let peak = (first_negative + 1..nsdf.len() - 1)
+                .find(|&i| nsdf[i] > 0.0 && nsdf[i] > nsdf[i - 1] && nsdf[i] >= nsdf[i + 1])
+                .unwrap();
+
+            // 44100 / 440 = 100.2
+            assert_eq!(100, peak);
+        }
+    }
+
+    mod square_sums_tests {
+        use crate::pitch_detection::Mpm;
+
+        fn brute_force_square_sums(samples: &[f64]) -> Vec<f64> {
+            (0..samples.len())
+                .map(|tau| {
+                    (0..samples.len() - tau)
+                        .map(|i| samples[i] * samples[i] + samples[i + tau] * samples[i + tau])
+                        .sum()
+                })
+                .collect()
+        }
+
+        #[test]
+        fn matches_brute_force_calculation() {
+            let samples: Vec<f64> = (0..4096)
+                .map(|i| {
+                    let t = i as f64 / 44100.0;
+                    (2.0 * std::f64::consts::PI * 440.0 * t).sin()
+                        + 0.5 * (2.0 * std::f64::consts::PI * 1234.5 * t).cos()
+                })
+                .collect();
+
+            let expected = brute_force_square_sums(&samples);
+            let actual = Mpm::square_sums(samples);
+
+            assert_eq!(expected.len(), actual.len());
+            for (e, a) in expected.iter().zip(actual.iter()) {
+                assert!((e - a).abs() < 1e-10, "expected {e}, got {a}");
+            }
+        }
+
+        #[test]
+        fn empty_input_gives_empty_output() {
+            assert!(Mpm::square_sums(vec![]).is_empty());
+        }
+    }
+
+    mod median_filter_tests {
+        use crate::pitch_detection::Mpm;
+
+        /// Creates NSDF values with a broad peak at a lag of 100 samples and a
+        /// narrow spike caused by noise at a lag of 50 samples
+        fn noisy_nsdf() -> Vec<f64> {
+            let mut nsdf: Vec<f64> = (0..400)
+                .map(|tau| (2.0 * std::f64::consts::PI * tau as f64 / 100.0).cos())
+                .collect();
+            nsdf[49] = 0.1;
+            nsdf[50] = 0.95;
+            nsdf[51] = 0.1;
+            nsdf
+        }
+
+        #[test]
+        fn median_filter_removes_spikes() {
+            let values = vec![0.0, 0.0, 1.0, 0.0, 0.0, 2.0, 2.0, 2.0];
+
+            assert_eq!(
+                vec![0.0, 0.0, 0.0, 0.0, 0.0, 2.0, 2.0, 2.0],
+                Mpm::median_filter(&values, 3)
+            );
+        }
+
+        #[test]
+        fn noisy_nsdf_gives_correct_pitch_with_median_filter() {
+            let mpm = Mpm::new(0.7, 44100).with_nsdf_median_filter(5);
+            let pitch = mpm.get_pitch_from_nsdf(&noisy_nsdf()).unwrap().frequency;
+
+            assert!((pitch - 441.0).abs() < 1.0, "detected {pitch} Hz");
+        }
+
+        #[test]
+        fn noisy_nsdf_gives_wrong_pitch_without_median_filter() {
+            let mpm = Mpm::new(0.7, 44100);
+            let pitch = mpm.get_pitch_from_nsdf(&noisy_nsdf()).unwrap().frequency;
+
+            assert!((pitch - 441.0).abs() > 100.0, "detected {pitch} Hz");
+        }
+
+        #[test]
+        #[should_panic(expected = "median filter window must be odd")]
+        fn even_window_should_panic() {
+            Mpm::new(0.7, 44100).with_nsdf_median_filter(4);
+        }
+    }
+
+    mod octave_correction_tests {
+        use crate::pitch_detection::Mpm;
+        use crate::test_utils::sine_samples;
+
+        /// Creates NSDF values for a sound with a period of 100 samples, which
+        /// stay positive up to a lag of 100 samples so that the peak at a lag
+        /// of 200 samples is chosen by the peak picking algorithm
+        fn subharmonic_nsdf() -> Vec<f64> {
+            (0..800)
+                .map(|tau| {
+                    let value = (2.0 * std::f64::consts::PI * tau as f64 / 100.0).cos();
+                    if tau <= 100 {
+                        value.max(0.05)
+                    } else {
+                        value
+                    }
+                })
+                .collect()
+        }
+
+        #[test]
+        fn octave_error_is_corrected() {
+            let mpm = Mpm::new(0.7, 44100);
+            let pitch = mpm
+                .get_pitch_from_nsdf(&subharmonic_nsdf())
+                .unwrap()
+                .frequency;
+
+            assert!((pitch - 441.0).abs() < 1.0, "detected {pitch} Hz");
+        }
+
+        #[test]
+        fn octave_error_is_not_corrected_when_disabled() {
+            let mpm = Mpm::new(0.7, 44100).with_octave_correction(false);
+            let pitch = mpm
+                .get_pitch_from_nsdf(&subharmonic_nsdf())
+                .unwrap()
+                .frequency;
+
+            assert!((pitch - 220.5).abs() < 1.0, "detected {pitch} Hz");
+        }
+
+        #[test]
+        fn detect_octave_error_doubles_subharmonic_pitch() {
+            let mpm = Mpm::new(0.7, 44100);
+            let pitch = mpm.detect_octave_error(220.5, &sine_samples(441.0, 44100, 2048));
+
+            assert!((pitch - 441.0).abs() < 1.0, "corrected to {pitch} Hz");
+        }
+
+        #[test]
+        fn detect_octave_error_keeps_correct_pitch() {
+            let mpm = Mpm::new(0.7, 44100);
+            let pitch = mpm.detect_octave_error(441.0, &sine_samples(441.0, 44100, 2048));
+
+            assert!((pitch - 441.0).abs() < 1.0, "corrected to {pitch} Hz");
+        }
+    }
+
+    mod frequency_range_tests {
+        use crate::pitch_detection::{Mpm, PitchDetector};
+
+        /// Creates a 220 Hz signal with a weak fundamental and a strong
+        /// second harmonic, which MPM detects an octave too high
+        fn weak_fundamental() -> Vec<f64> {
+            (0..4096)
+                .map(|i| {
+                    let t = i as f64 / 44100.0;
+                    0.3 * (2.0 * std::f64::consts::PI * 220.0 * t).sin()
+                        + (2.0 * std::f64::consts::PI * 440.0 * t).sin()
+                })
+                .collect()
+        }
+
+        #[test]
+        fn unrestricted_detector_makes_octave_error() {
+            let pitch = Mpm::new(0.7, 44100).get_pitch(weak_fundamental()).unwrap();
+
+            assert!((pitch - 440.0).abs() < 2.0, "detected {pitch} Hz");
+        }
+
+        #[test]
+        fn max_frequency_prevents_octave_error() {
+            let pitch = Mpm::new(0.7, 44100)
+                .with_range(80.0, 300.0)
+                .get_pitch(weak_fundamental())
+                .unwrap();
+
+            assert!((pitch - 220.0).abs() < 1.0, "detected {pitch} Hz");
+        }
+
+        #[test]
+        fn pitch_outside_range_is_not_detected() {
+            let pitch = Mpm::new(0.7, 44100)
+                .with_range(500.0, 1000.0)
+                .get_pitch(weak_fundamental());
+
+            assert_eq!(None, pitch);
+        }
+
+        #[test]
+        #[should_panic(expected = "maximum frequency must be greater than minimum frequency")]
+        fn empty_range_should_panic() {
+            Mpm::new(0.7, 44100).with_range(300.0, 80.0);
+        }
+    }
+
+    mod confidence_tests {
+        use crate::pitch_detection::{Mpm, PitchDetector};
+        use crate::test_utils::sine_samples;
+
+        #[test]
+        fn pure_tone_has_high_confidence() {
+            let result = Mpm::new(0.7, 44100)
+                .get_pitch_with_confidence(sine_samples(440.0, 44100, 2048))
+                .unwrap();
+
+            assert!((result.frequency - 440.0).abs() < 1.0);
+            assert!(result.confidence > 0.9 && result.confidence <= 1.0);
+        }
+
+        #[test]
+        fn frequency_matches_get_pitch() {
+            let mpm = Mpm::new(0.7, 44100);
+
+            assert_eq!(
+                mpm.get_pitch(sine_samples(261.63, 44100, 2048)),
+                mpm.get_pitch_with_confidence(sine_samples(261.63, 44100, 2048))
+                    .map(|result| result.frequency)
+            );
+        }
+
+        #[test]
+        fn silence_has_no_result() {
+            assert_eq!(
+                None,
+                Mpm::new(0.7, 44100).get_pitch_with_confidence(vec![0.0; 2048])
+            );
+        }
+    }
+
+    mod yin_tests {
+        use crate::pitch_detection::{Mpm, PitchDetector, Yin};
+        use crate::test_utils::sine_samples;
+
+        #[test]
+        fn yin_and_mpm_agree_on_sine_wave() {
+            let samples = sine_samples(440.0, 44100, 4096);
+
+            let yin_pitch = Yin::new(0.1, 44100).get_pitch(samples.clone()).unwrap();
+            let mpm_pitch = Mpm::new(0.7, 44100).get_pitch(samples).unwrap();
+
+            assert!(
+                (yin_pitch - 440.0).abs() < 1.0,
+                "YIN detected {yin_pitch} Hz"
+            );
+            assert!(
+                (yin_pitch - mpm_pitch).abs() < 1.0,
+                "YIN detected {yin_pitch} Hz, MPM detected {mpm_pitch} Hz"
+            );
+        }
+
+        #[test]
+        fn low_pitch_detected_correctly() {
+            let pitch = Yin::new(0.1, 44100)
+                .get_pitch(sine_samples(110.0, 44100, 4096))
+                .unwrap();
+
+            assert!((pitch - 110.0).abs() < 1.0, "detected {pitch} Hz");
+        }
+
+        #[test]
+        fn silence_has_no_pitch() {
+            assert_eq!(None, Yin::new(0.1, 44100).get_pitch(vec![0.0; 4096]));
+        }
+
+        #[test]
+        fn name_is_yin() {
+            assert_eq!("YIN", Yin::new(0.1, 44100).name());
+        }
+    }
+
+    mod hps_tests {
+        use crate::pitch_detection::{Hps, PitchDetector};
+
+        /// Creates a harmonic series with six equal partials, with the
+        /// fundamental attenuated by the given number of decibels
+        fn harmonic_series(fundamental: f64, attenuation_db: f64) -> Vec<f64> {
+            let fundamental_amplitude = 10.0f64.powf(-attenuation_db / 20.0);
+
+            (0..8192)
+                .map(|i| {
+                    let t = i as f64 / 44100.0;
+                    (1..=6)
+                        .map(|harmonic| {
+                            let amplitude = if harmonic == 1 {
+                                fundamental_amplitude
+                            } else {
+                                1.0
+                            };
+                            amplitude
+                                * (2.0 * std::f64::consts::PI * fundamental * harmonic as f64 * t)
+                                    .sin()
+                        })
+                        .sum::<f64>()
+                        / 6.0
+                })
+                .collect()
+        }
+
+        #[test]
+        fn attenuated_fundamental_detected_correctly() {
+            let pitch = Hps::new(5, 44100)
+                .get_pitch(harmonic_series(110.0, 20.0))
+                .unwrap();
+
+            assert!((pitch - 110.0).abs() < 2.0, "detected {pitch} Hz");
+        }
+
+        #[test]
+        fn pure_harmonic_series_detected_correctly() {
+            let pitch = Hps::new(5, 44100)
+                .get_pitch(harmonic_series(261.63, 0.0))
+                .unwrap();
+
+            assert!((pitch - 261.63).abs() < 2.0, "detected {pitch} Hz");
+        }
+
+        #[test]
+        fn silence_has_no_pitch() {
+            assert_eq!(None, Hps::new(5, 44100).get_pitch(vec![0.0; 4096]));
+        }
+
+        #[test]
+        #[should_panic(expected = "number of harmonics must be non-zero")]
+        fn zero_harmonics_should_panic() {
+            Hps::new(0, 44100);
+        }
+    }
+
+    mod cepstrum_tests {
+        use crate::pitch_detection::{Cepstrum, PitchDetector};
+
+        /// Creates a voice-like signal with 20 harmonics whose amplitudes
+        /// decrease with harmonic number, and a formant-like boost around
+        /// 800 Hz
+        fn voice_like(fundamental: f64) -> Vec<f64> {
+            (0..4096)
+                .map(|i| {
+                    let t = i as f64 / 44100.0;
+                    (1..=20)
+                        .map(|harmonic| {
+                            let frequency = fundamental * harmonic as f64;
+                            let formant =
+                                1.0 + 2.0 * (-((frequency - 800.0) / 300.0).powi(2)).exp();
+                            formant / harmonic as f64
+                                * (2.0 * std::f64::consts::PI * frequency * t).sin()
+                        })
+                        .sum::<f64>()
+                        / 10.0
+                })
+                .collect()
+        }
+
+        #[test]
+        fn voice_like_signal_detected_correctly() {
+            for fundamental in [110.0, 150.0, 220.0] {
+                let pitch = Cepstrum::new(60.0, 1000.0, 44100)
+                    .get_pitch(voice_like(fundamental))
+                    .unwrap();
+
+                assert!(
+                    (pitch - fundamental).abs() < 2.0,
+                    "detected {pitch} Hz for {fundamental} Hz"
+                );
+            }
+        }
+
+        #[test]
+        fn silence_has_no_pitch() {
+            assert_eq!(
+                None,
+                Cepstrum::new(60.0, 1000.0, 44100).get_pitch(vec![0.0; 4096])
+            );
+        }
+
+        #[test]
+        fn too_few_samples_have_no_pitch() {
+            let detector = Cepstrum::new(60.0, 1000.0, 44100);
+
+            for num_samples in 1..4 {
+                assert_eq!(None, detector.get_pitch(vec![0.5; num_samples]));
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "maximum frequency must be greater than minimum frequency")]
+        fn empty_frequency_range_should_panic() {
+            Cepstrum::new(1000.0, 60.0, 44100);
+        }
+    }
+
+    mod trait_object_tests {
+        use crate::pitch_detection::{Cepstrum, Hps, Mpm, PitchDetector, Yin};
+        use crate::test_utils::harmonic_tone;
+
+        #[test]
+        fn boxed_detector_detects_pitch() {
+            let detector: Box<dyn PitchDetector> = Box::new(Mpm::new(0.7, 44100));
+
+            // Every detector can find the pitch of a tone with three harmonics
+            let pitch = detector.get_pitch(harmonic_tone(440.0, 3, 4096)).unwrap();
+
+            assert!((pitch - 440.0).abs() < 1.0);
+        }
+
+        #[test]
+        fn detector_can_be_reused() {
+            let detector: Box<dyn PitchDetector> = Box::new(Mpm::new(0.7, 44100));
+
+            let first = detector.get_pitch(harmonic_tone(440.0, 3, 4096)).unwrap();
+            let second = detector.get_pitch(harmonic_tone(440.0, 3, 4096)).unwrap();
+
+            assert_eq!(first, second);
+        }
+
+        #[test]
+        fn every_detector_can_be_selected_at_runtime() {
+            let detectors: Vec<Box<dyn PitchDetector>> = vec![
+                Box::new(Mpm::new(0.7, 44100)),
+                Box::new(Yin::new(0.1, 44100)),
+                Box::new(Hps::new(3, 44100)),
+                Box::new(Cepstrum::new(60.0, 1000.0, 44100)),
+            ];
+
+            for detector in &detectors {
+                let pitch = detector.get_pitch(harmonic_tone(440.0, 3, 4096)).unwrap();
+
+                assert!(
+                    (pitch - 440.0).abs() < 5.0,
+                    "{} detected {pitch} Hz",
+                    detector.name()
+                );
+            }
+        }
     }
 }
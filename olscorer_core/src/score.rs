@@ -0,0 +1,246 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::transcription::{Note, NoteName};
+
+/// Parameters for quantising note positions and durations to a rhythmic grid
+pub struct QuantisationConfig {
+    /// Number of grid positions in a whole note, which is the length of the
+    /// shortest note, for example 16 for semiquavers. Must be a power of two.
+    pub subdivisions: u8,
+}
+
+impl Default for QuantisationConfig {
+    fn default() -> Self {
+        QuantisationConfig { subdivisions: 16 }
+    }
+}
+
+/// Struct representing a note or rest in a score, with its position and
+/// length in divisions
+pub(crate) struct ScoreEvent {
+    pub(crate) start: u32,
+    pub(crate) length: u32,
+    pub(crate) name: Option<NoteName>,
+    /// Note value of the event, where 1 is a whole note, 2 is a half note,
+    /// 4 is a quarter note, and so on
+    pub(crate) note_value: u32,
+    pub(crate) tied_from_previous: bool,
+    pub(crate) tied_to_next: bool,
+}
+
+/// Struct representing a monophonic score, which is a sequence of notes and
+/// rests filling a whole number of measures, where every event is a single
+/// note value within one measure
+pub(crate) struct Score {
+    /// Number of divisions in a quarter note
+    pub(crate) divisions: u32,
+    /// Length (in divisions) of each measure
+    pub(crate) measure_length: u32,
+    pub(crate) events: Vec<ScoreEvent>,
+}
+
+impl Score {
+    /// Arranges the notes into a score
+    ///
+    /// The start and end of each note are moved to the nearest grid position,
+    /// and notes are at least one grid position long. Notes are treated as a
+    /// single melody, so a note is cut short where the next note starts, and
+    /// the gaps between notes are filled with rests. Notes which cross a
+    /// barline or whose length is not a single note value are split into
+    /// tied notes.
+    ///
+    /// # Arguments
+    ///
+    /// * `notes` - Notes to arrange, with start positions and durations in
+    ///   samples
+    /// * `sample_rate` - Sample rate (in Hz) of the audio the notes were
+    ///   transcribed from
+    /// * `time_signature` - Number of beats in each measure, and the note
+    ///   value of each beat
+    /// * `bpm` - Tempo of the audio (in quarter notes per minute)
+    /// * `config` - Quantisation parameters
+    pub(crate) fn new(
+        notes: &[Note],
+        sample_rate: u32,
+        time_signature: (u8, u8),
+        bpm: f64,
+        config: &QuantisationConfig,
+    ) -> Score {
+        let (beats, beat_type) = time_signature;
+        let subdivisions = config.subdivisions;
+
+        assert_ne!(0, sample_rate, "sample rate must be non-zero");
+        assert!(bpm > 0.0, "tempo must be positive");
+        assert!(
+            subdivisions.is_power_of_two(),
+            "number of subdivisions must be a power of two"
+        );
+        assert_ne!(0, beats, "number of beats in a measure must be non-zero");
+        assert!(
+            beat_type.is_power_of_two(),
+            "beat type must be a power of two"
+        );
+
+        // Number of the shortest note value in a whole note. Both a grid
+        // position and a beat are a whole number of these notes, so every
+        // barline and grid position falls on one of them.
+        let smallest_note_value = lcm(subdivisions as u32, beat_type as u32);
+
+        // Divisions per quarter note, chosen so that the shortest note value
+        // is a whole number of divisions
+        let divisions = std::cmp::max(smallest_note_value, 4) / 4;
+        let grid_length = 4 * divisions / subdivisions as u32;
+        let measure_length = beats as u32 * 4 * divisions / beat_type as u32;
+        let smallest_length = 4 * divisions / smallest_note_value;
+
+        let grid_positions_per_sample = bpm / 60.0 * subdivisions as f64 / 4.0 / sample_rate as f64;
+        let quantise = |samples: usize| {
+            (samples as f64 * grid_positions_per_sample).round() as u32 * grid_length
+        };
+
+        Score {
+            divisions,
+            measure_length,
+            events: score_events(
+                notes,
+                quantise,
+                divisions,
+                grid_length,
+                smallest_length,
+                measure_length,
+            ),
+        }
+    }
+
+    /// Returns the number of measures in the score
+    pub(crate) fn num_measures(&self) -> u32 {
+        self.events
+            .last()
+            .map_or(1, |e| (e.start + e.length) / self.measure_length)
+    }
+}
+
+/// Returns the least common multiple of two positive numbers
+fn lcm(a: u32, b: u32) -> u32 {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+
+    a / x * b
+}
+
+/// Arranges the notes into a sequence of notes and rests which fills a whole
+/// number of measures, where every event is a single note value within one
+/// measure
+///
+/// Every note and rest is a multiple of `smallest_length` long, which may be
+/// shorter than a grid position when a measure is not a whole number of grid
+/// positions long.
+fn score_events(
+    notes: &[Note],
+    quantise: impl Fn(usize) -> u32,
+    divisions: u32,
+    grid_length: u32,
+    smallest_length: u32,
+    measure_length: u32,
+) -> Vec<ScoreEvent> {
+    let mut sorted_notes: Vec<&Note> = notes.iter().collect();
+    sorted_notes.sort_by_key(|note| note.start);
+
+    // Quantised (start, end, name) of each note, with overlaps removed
+    let mut segments: Vec<(u32, u32, Option<NoteName>)> = vec![];
+
+    for note in sorted_notes {
+        let start = quantise(note.start);
+        let end = std::cmp::max(quantise(note.start + note.duration), start + grid_length);
+
+        if let Some(previous) = segments.last_mut() {
+            if start <= previous.0 {
+                continue;
+            }
+            previous.1 = std::cmp::min(previous.1, start);
+        }
+        segments.push((start, end, Some(note.name)));
+    }
+
+    // Fill the gaps between notes, and the end of the last measure, with rests
+    let mut filled_segments = vec![];
+    let mut position = 0;
+
+    for segment in segments {
+        if segment.0 > position {
+            filled_segments.push((position, segment.0, None));
+        }
+        position = segment.1;
+        filled_segments.push(segment);
+    }
+
+    let num_measures = std::cmp::max(position.div_ceil(measure_length), 1);
+    let total_length = num_measures * measure_length;
+    if total_length > position {
+        filled_segments.push((position, total_length, None));
+    }
+
+    // Split segments at barlines and into single note values
+    // Length (in divisions) of each note value, starting with the whole note
+    let note_values: Vec<(u32, u32)> = (0..)
+        .map(|i| ((4 * divisions) >> i, 1 << i))
+        .take_while(|(length, _)| *length >= smallest_length)
+        .collect();
+
+    let mut events = vec![];
+
+    for (start, end, name) in filled_segments {
+        let first_event = events.len();
+        let mut position = start;
+
+        while position < end {
+            let measure_end = (position / measure_length + 1) * measure_length;
+            let remaining = std::cmp::min(end, measure_end) - position;
+
+            let (length, note_value) = *note_values
+                .iter()
+                .find(|(length, _)| *length <= remaining)
+                .expect("smallest length should divide every segment length");
+
+            events.push(ScoreEvent {
+                start: position,
+                length,
+                name,
+                note_value,
+                tied_from_previous: false,
+                tied_to_next: false,
+            });
+            position += length;
+        }
+
+        if name.is_some() {
+            let last_event = events.len() - 1;
+            for (i, event) in events[first_event..].iter_mut().enumerate() {
+                event.tied_from_previous = i > 0;
+                event.tied_to_next = first_event + i < last_event;
+            }
+        }
+    }
+
+    events
+}
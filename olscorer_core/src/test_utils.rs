@@ -0,0 +1,193 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::audio_utils::AudioData;
+use crate::transcription::{Note, NoteName};
+use hound::{SampleFormat, WavSpec, WavWriter};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Creates a sine with an amplitude of 1
+///
+/// # Arguments
+///
+/// * `frequency` - Frequency (in Hz) of the sine
+/// * `sample_rate` - Sample rate (in Hz) of the samples
+/// * `num_samples` - Number of samples to create
+pub(crate) fn sine_samples(frequency: f64, sample_rate: u32, num_samples: usize) -> Vec<f64> {
+    (0..num_samples)
+        .map(|i| (2.0 * std::f64::consts::PI * frequency * i as f64 / sample_rate as f64).sin())
+        .collect()
+}
+
+/// Creates audio data containing a sine with an amplitude of 1
+///
+/// # Arguments
+///
+/// * `frequency` - Frequency (in Hz) of the sine
+/// * `sample_rate` - Sample rate (in Hz) of the audio
+/// * `num_samples` - Number of samples to create
+pub(crate) fn sine(frequency: f64, sample_rate: u32, num_samples: usize) -> AudioData {
+    AudioData::from_raw_samples(
+        sine_samples(frequency, sample_rate, num_samples),
+        sample_rate,
+    )
+}
+
+/// Creates a tone sampled at 44100 Hz made of the first `num_harmonics`
+/// harmonics of `frequency`, where harmonic `k` has an amplitude of `1 / k`
+pub(crate) fn harmonic_tone(frequency: f64, num_harmonics: usize, num_samples: usize) -> Vec<f64> {
+    (0..num_samples)
+        .map(|i| {
+            let phase = 2.0 * std::f64::consts::PI * frequency * i as f64 / 44100.0;
+            (1..=num_harmonics)
+                .map(|k| (k as f64 * phase).sin() / k as f64)
+                .sum()
+        })
+        .collect()
+}
+
+/// Creates one second of audio sampled at 44100 Hz containing silence
+/// followed by a sine with an amplitude of 1 which starts at `start_pos`
+pub(crate) fn sine_burst(frequency: f64, start_pos: usize) -> AudioData {
+    let mut samples = vec![0.0; start_pos];
+    samples.extend(sine_samples(frequency, 44100, 44100 - start_pos));
+
+    AudioData::from_raw_samples(samples, 44100)
+}
+
+/// Creates one second of audio sampled at 44100 Hz containing silence
+/// followed by a sine with an amplitude of 0.5 which starts at `start_pos`
+/// and changes from 440 Hz to 660 Hz at `change_pos` without a break in its
+/// phase or amplitude
+pub(crate) fn legato(start_pos: usize, change_pos: usize) -> AudioData {
+    let mut phase = 0.0;
+    let samples = (0..44100)
+        .map(|i| {
+            if i < start_pos {
+                return 0.0;
+            }
+            let frequency = if i < change_pos { 440.0 } else { 660.0 };
+            phase += 2.0 * std::f64::consts::PI * frequency / 44100.0;
+            0.5 * phase.sin()
+        })
+        .collect();
+
+    AudioData::from_raw_samples(samples, 44100)
+}
+
+/// Creates one second of audio sampled at 44100 Hz containing a quarter of a
+/// second of silence followed by the notes played together and decaying,
+/// each with 4 harmonics of decreasing amplitude
+pub(crate) fn decaying_chord(frequencies: &[f64], amplitude: f64) -> AudioData {
+    let samples = (0..44100)
+        .map(|i| {
+            if i < 11025 {
+                return 0.0;
+            }
+            let t = i as f64 / 44100.0;
+            let decay = amplitude * (-3.0 * (t - 0.25)).exp();
+            decay
+                * frequencies
+                    .iter()
+                    .flat_map(|f| {
+                        (1..=4).map(move |h| {
+                            (2.0 * std::f64::consts::PI * f * h as f64 * t).sin() / h as f64
+                        })
+                    })
+                    .sum::<f64>()
+        })
+        .collect();
+
+    AudioData::from_raw_samples(samples, 44100)
+}
+
+/// Creates a click track sampled at 44100 Hz with the given tempo (in beats
+/// per minute)
+///
+/// The position of each click is shifted by up to `max_jitter` beat periods,
+/// so that the clicks are not perfectly regular.
+pub(crate) fn click_track(bpm: f64, num_clicks: usize, max_jitter: f64) -> AudioData {
+    let sample_rate = 44100;
+    let period = 60.0 / bpm * sample_rate as f64;
+    let mut samples = vec![0.0; (period * (num_clicks + 1) as f64) as usize];
+
+    for i in 0..num_clicks {
+        // Deterministic jitter in the range [-1, 1]
+        let jitter = ((i * 7919) % 21) as f64 / 10.0 - 1.0;
+        let click_start = ((i as f64 + 0.5 + max_jitter * jitter) * period) as usize;
+
+        for j in 0..400 {
+            let t = j as f64 / sample_rate as f64;
+            samples[click_start + j] =
+                (-t * 200.0).exp() * (2.0 * std::f64::consts::PI * 1000.0 * t).sin();
+        }
+    }
+
+    AudioData::from_raw_samples(samples, sample_rate)
+}
+
+/// Writes a wav file named `test.wav` to the directory and returns its path
+///
+/// # Arguments
+///
+/// * `dir` - Directory to write the file to
+/// * `spec` - Format of the file
+/// * `samples` - Interleaved samples of every channel
+pub(crate) fn write_wav<S: hound::Sample + Copy>(
+    dir: &TempDir,
+    spec: WavSpec,
+    samples: &[S],
+) -> PathBuf {
+    let filepath = dir.path().join("test.wav");
+
+    let mut writer = WavWriter::create(&filepath, spec).unwrap();
+    for sample in samples {
+        writer.write_sample(*sample).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    filepath
+}
+
+/// Writes a 32-bit float stereo wav file sampled at 44100 Hz with the given
+/// left and right channel samples to the directory, and returns its path
+pub(crate) fn write_stereo_wav(dir: &TempDir, left: &[f32], right: &[f32]) -> PathBuf {
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+    let samples: Vec<f32> = left.iter().zip(right).flat_map(|(l, r)| [*l, *r]).collect();
+
+    write_wav(dir, spec, &samples)
+}
+
+/// Creates a note with a confidence of 1 and no cents deviation
+pub(crate) fn note(name: NoteName, start: usize, duration: usize) -> Note {
+    Note {
+        name,
+        start,
+        duration,
+        confidence: 1.0,
+        cents_deviation: 0.0,
+    }
+}
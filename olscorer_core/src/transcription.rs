@@ -18,11 +18,26 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use crate::abc_export::to_abc;
 use crate::audio_utils::{AudioData, Frame};
-use crate::pitch_detection::{Mpm, PitchDetector};
+use crate::errors::{NoteParseError, OlscorerError};
+use crate::lilypond_export::to_lilypond;
+use crate::midi_export::{write_midi_with_config, MidiExportConfig};
+use crate::musicxml_export::to_musicxml;
+use crate::onset_detection::{OnsetDetector, SpectralFluxDetector};
+use crate::pitch_detection::{forward_fft, hann_window, inverse_fft, Mpm, PitchResult};
+use crate::score::{QuantisationConfig, Score};
+use rayon::prelude::*;
+use rustfft::num_complex::Complex;
 use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::mpsc::Sender;
 
 /// Struct representing a musical note
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Note {
     /// Name of the note, including its pitch and octave
     pub name: NoteName,
@@ -32,6 +47,101 @@ pub struct Note {
 
     /// Duration of the note (in samples)
     pub duration: usize,
+
+    /// Confidence of the pitch detector in the detected pitch, between 0
+    /// and 1, where values near 1 indicate a highly periodic sound
+    pub confidence: f64,
+
+    /// Difference (in cents) between the detected pitch and the equal
+    /// temperament frequency of the note name. Positive values mean the
+    /// detected pitch was sharp.
+    pub cents_deviation: f64,
+}
+
+impl Note {
+    /// Returns the time (in seconds) at which the note starts playing
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate` - Sample rate (in Hz) of the audio the note was
+    ///   detected in
+    pub fn start_seconds(&self, sample_rate: u32) -> f64 {
+        self.start as f64 / sample_rate as f64
+    }
+
+    /// Returns the duration (in seconds) of the note
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate` - Sample rate (in Hz) of the audio the note was
+    ///   detected in
+    pub fn duration_seconds(&self, sample_rate: u32) -> f64 {
+        self.duration as f64 / sample_rate as f64
+    }
+
+    /// Returns the time range (in seconds) during which the note is sounding
+    ///
+    /// The range starts at the note's onset and ends at, but does not
+    /// include, the end of the note.
+    pub fn time_range(&self, sample_rate: u32) -> Range<f64> {
+        let start_seconds = self.start_seconds(sample_rate);
+        let end_seconds = (self.start + self.duration) as f64 / sample_rate as f64;
+
+        start_seconds..end_seconds
+    }
+
+    /// Checks whether the note is sounding at the given time (in seconds)
+    pub fn contains_time(&self, t_seconds: f64, sample_rate: u32) -> bool {
+        self.time_range(sample_rate).contains(&t_seconds)
+    }
+
+    /// Returns the movable-do solfège syllable of the note in the given key
+    ///
+    /// Minor keys use la-based minor, so the root of a minor key is La and
+    /// Do is the root of its relative major. The major seventh is Ti.
+    /// Chromatic notes use raised syllables (Di, Ri, Fi, Si, Li), except in
+    /// keys whose signature has flats, which use lowered syllables (Ra, Me,
+    /// Se, Le, Te). The raised sixth and seventh of a minor key are always
+    /// Fi and Si.
+    pub fn to_solfege(&self, key: &Scale) -> String {
+        // Number of semitones from Do up to the note
+        let degree = match key.mode {
+            Mode::Major => key.root.interval_to(&self.name),
+            Mode::Minor => (key.root.interval_to(&self.name) + 9) % 12,
+        };
+
+        let do_pitch_class = match key.mode {
+            Mode::Major => key.root.pitch_class(),
+            Mode::Minor => (key.root.pitch_class() + 3) % 12,
+        };
+
+        // F, Bb, Eb, Ab and Db major
+        let flat_key = matches!(do_pitch_class, 1 | 3 | 5 | 8 | 10);
+        let minor = key.mode == Mode::Minor;
+
+        let syllable = match degree {
+            0 => "Do",
+            1 if flat_key => "Ra",
+            1 => "Di",
+            2 => "Re",
+            3 if flat_key => "Me",
+            3 => "Ri",
+            4 => "Mi",
+            5 => "Fa",
+            6 if flat_key && !minor => "Se",
+            6 => "Fi",
+            7 => "Sol",
+            8 if flat_key && !minor => "Le",
+            8 => "Si",
+            9 => "La",
+            10 if flat_key => "Te",
+            10 => "Li",
+            11 => "Ti",
+            _ => unreachable!(),
+        };
+
+        syllable.to_string()
+    }
 }
 
 impl fmt::Display for Note {
@@ -44,9 +154,357 @@ impl fmt::Display for Note {
     }
 }
 
+/// Struct representing several notes which start at the same time
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chord {
+    /// Sample at which the chord starts playing
+    pub start: usize,
+
+    /// Duration of the chord (in samples)
+    pub duration: usize,
+
+    /// Notes in the chord, from lowest to highest
+    pub notes: Vec<Note>,
+}
+
+/// Enum representing the quality of a chord, which is determined by the
+/// intervals of its notes above the root
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChordQuality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    SuspendedSecond,
+    SuspendedFourth,
+    DominantSeventh,
+    MajorSeventh,
+    MinorSeventh,
+    HalfDiminishedSeventh,
+    DiminishedSeventh,
+}
+
+/// Struct representing a chord whose root and quality have been identified
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NamedChord {
+    /// Chord whose notes were identified
+    pub chord: Chord,
+
+    /// Root of the chord. The octave is always 0.
+    pub root: NoteName,
+
+    /// Quality of the chord, given by the intervals of its notes above the
+    /// root
+    pub quality: ChordQuality,
+}
+
+impl NamedChord {
+    /// Returns the chord symbol of the chord, for example `Am` or `G7`
+    fn symbol(&self) -> String {
+        let suffix = match self.quality {
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Augmented => "aug",
+            ChordQuality::SuspendedSecond => "sus2",
+            ChordQuality::SuspendedFourth => "sus4",
+            ChordQuality::DominantSeventh => "7",
+            ChordQuality::MajorSeventh => "maj7",
+            ChordQuality::MinorSeventh => "m7",
+            ChordQuality::HalfDiminishedSeventh => "m7b5",
+            ChordQuality::DiminishedSeventh => "dim7",
+        };
+
+        // The root is written without its octave
+        let root = self.root.to_string();
+        format!(
+            "{}{}",
+            root.trim_end_matches(|c: char| c.is_ascii_digit()),
+            suffix
+        )
+    }
+}
+
+/// Struct representing a note along with the times (in seconds) at which it
+/// starts and stops playing
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedNote {
+    pub note: Note,
+
+    /// Time (in seconds) at which the note starts playing
+    pub start_seconds: f64,
+
+    /// Time (in seconds) at which the note stops playing
+    pub end_seconds: f64,
+}
+
+impl TimedNote {
+    /// Creates a timed note from a note detected in audio with the given
+    /// sample rate (in Hz)
+    pub fn new(note: Note, sample_rate: u32) -> Self {
+        let time_range = note.time_range(sample_rate);
+
+        TimedNote {
+            note,
+            start_seconds: time_range.start,
+            end_seconds: time_range.end,
+        }
+    }
+}
+
+/// Struct representing the notes, tempo and chords transcribed from a piece
+/// of audio
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TranscriptionResult {
+    /// Notes of the melody, in ascending order of start position
+    pub notes: Vec<Note>,
+
+    /// (Optional) Chords with their root and quality identified, or None if
+    /// chord detection was not run
+    pub chords: Option<Vec<NamedChord>>,
+
+    /// Sample rate (in Hz) of the audio the notes were transcribed from
+    pub sample_rate: u32,
+
+    /// Tempo (in quarter notes per minute) of the audio
+    pub bpm: f64,
+}
+
+/// Enum representing the file formats a transcription can be exported to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    MusicXml,
+    Midi,
+    Abc,
+    Lilypond,
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    /// Returns the export format of files with the given extension, or None
+    /// if the extension is not recognised
+    ///
+    /// Extensions are matched case-insensitively, and do not include the
+    /// leading `.`.
+    pub fn from_extension(extension: &str) -> Option<ExportFormat> {
+        match extension.to_ascii_lowercase().as_str() {
+            "musicxml" | "xml" => Some(ExportFormat::MusicXml),
+            "mid" | "midi" => Some(ExportFormat::Midi),
+            "abc" => Some(ExportFormat::Abc),
+            "ly" => Some(ExportFormat::Lilypond),
+            "json" => Some(ExportFormat::Json),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+impl TranscriptionResult {
+    /// Formats the transcription as a plain text lead sheet in
+    /// `beats_per_measure`/4 time
+    ///
+    /// The notes are quantised to semiquavers and arranged into measures in
+    /// the same way as for `musicxml_export::to_musicxml`. Each note is
+    /// written as its name and note value, for example `A4/4` for a quarter
+    /// note or `F#4/8` for an eighth note, rests are written as `r`, and
+    /// notes which are tied to the next note end with `~`. There are four
+    /// measures on each line, separated by barlines.
+    ///
+    /// If chord detection was run, the symbols of the chords which start in
+    /// each measure are written on a line above it. Chords which start after
+    /// the last measure of the melody are left out.
+    pub fn to_lead_sheet(&self, beats_per_measure: u8) -> String {
+        let config = QuantisationConfig::default();
+        let score = Score::new(
+            &self.notes,
+            self.sample_rate,
+            (beats_per_measure, 4),
+            self.bpm,
+            &config,
+        );
+
+        let mut events = score.events.iter().peekable();
+        let measures: Vec<String> = (0..score.num_measures())
+            .map(|measure| {
+                let measure_end = (measure + 1) * score.measure_length;
+                let mut words = vec![];
+
+                while let Some(event) = events.next_if(|e| e.start < measure_end) {
+                    let name = event.name.map_or("r".to_string(), |name| name.to_string());
+                    let tie = if event.tied_to_next { "~" } else { "" };
+                    words.push(format!("{}/{}{}", name, event.note_value, tie));
+                }
+
+                words.join(" ")
+            })
+            .collect();
+
+        // Chords are placed in measures by rounding their start positions to
+        // the quantisation grid, in the same way as the notes
+        let grid_positions_per_sample =
+            self.bpm / 60.0 * config.subdivisions as f64 / 4.0 / self.sample_rate as f64;
+        let grid_positions_per_measure =
+            beats_per_measure as usize * config.subdivisions as usize / 4;
+
+        let mut chord_symbols = vec![vec![]; measures.len()];
+        for chord in self.chords.iter().flatten() {
+            let grid_position = (chord.chord.start as f64 * grid_positions_per_sample).round();
+            let measure = grid_position as usize / grid_positions_per_measure;
+
+            if let Some(symbols) = chord_symbols.get_mut(measure) {
+                symbols.push(chord.symbol());
+            }
+        }
+
+        let mut lines = vec![];
+
+        for (measures, chord_symbols) in measures
+            .chunks(LEAD_SHEET_MEASURES_PER_LINE)
+            .zip(chord_symbols.chunks(LEAD_SHEET_MEASURES_PER_LINE))
+        {
+            let chord_symbols: Vec<String> = chord_symbols
+                .iter()
+                .map(|symbols| symbols.join(" "))
+                .collect();
+
+            // Each measure is padded so that its chord symbols line up with it
+            let widths: Vec<usize> = measures
+                .iter()
+                .zip(&chord_symbols)
+                .map(|(measure, symbols)| std::cmp::max(measure.len(), symbols.len()))
+                .collect();
+            let pad = |cells: &[String]| -> Vec<String> {
+                cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{cell:width$}"))
+                    .collect()
+            };
+
+            if self.chords.is_some() {
+                lines.push(pad(&chord_symbols).join("   ").trim_end().to_string());
+            }
+            lines.push(format!("{} |", pad(measures).join(" | ")));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Formats the notes as CSV, with a header row followed by one row for
+    /// each note
+    ///
+    /// The columns are the note name, start position (in samples), duration
+    /// (in samples), confidence and cents deviation of each note.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("name,start,duration,confidence,cents_deviation\n");
+
+        for note in &self.notes {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                note.name, note.start, note.duration, note.confidence, note.cents_deviation
+            ));
+        }
+
+        csv
+    }
+
+    /// Formats the transcription as a JSON object with its sample rate, tempo
+    /// and notes
+    ///
+    /// Each note is an object with the same fields as the columns of
+    /// `to_csv`. Chords are not included.
+    pub fn to_json(&self) -> String {
+        // Floats are written with their debug format, which always includes
+        // a decimal point or exponent, so that they are read back as floats
+        let notes: Vec<String> = self
+            .notes
+            .iter()
+            .map(|note| {
+                format!(
+                    "    {{\"name\": \"{}\", \"start\": {}, \"duration\": {}, \
+                     \"confidence\": {:?}, \"cents_deviation\": {:?}}}",
+                    note.name, note.start, note.duration, note.confidence, note.cents_deviation
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\n  \"sample_rate\": {},\n  \"bpm\": {:?},\n  \"notes\": [\n{}\n  ]\n}}\n",
+            self.sample_rate,
+            self.bpm,
+            notes.join(",\n")
+        )
+    }
+
+    /// Writes the notes to a file in the given format, using the tempo of the
+    /// transcription
+    ///
+    /// MusicXML and ABC files are written in 4/4 time. Returns an
+    /// `UnknownFormat` error if `format` is None and the format cannot be
+    /// told from the extension of the path, and an Error if exporting or
+    /// writing the file fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file to create
+    /// * `format` - (Optional) Format of the file. If this is None, the format
+    ///   is chosen from the extension of the path.
+    pub fn export_to_file(
+        &self,
+        path: &Path,
+        format: Option<ExportFormat>,
+    ) -> Result<(), OlscorerError> {
+        let format = format
+            .or_else(|| {
+                path.extension()
+                    .and_then(|extension| extension.to_str())
+                    .and_then(ExportFormat::from_extension)
+            })
+            .ok_or_else(|| OlscorerError::UnknownFormat(path.display().to_string()))?;
+
+        match format {
+            ExportFormat::MusicXml => std::fs::write(
+                path,
+                to_musicxml(&self.notes, self.sample_rate, (4, 4), self.bpm),
+            )?,
+            ExportFormat::Midi => {
+                let config = MidiExportConfig {
+                    bpm: self.bpm,
+                    ..MidiExportConfig::default()
+                };
+                let file = std::fs::File::create(path)?;
+                write_midi_with_config(
+                    &self.notes,
+                    self.sample_rate,
+                    config,
+                    std::io::BufWriter::new(file),
+                )?
+            }
+            ExportFormat::Abc => {
+                std::fs::write(path, to_abc(&self.notes, self.sample_rate, self.bpm))?
+            }
+            ExportFormat::Lilypond => {
+                std::fs::write(path, to_lilypond(&self.notes, self.sample_rate, self.bpm))?
+            }
+            ExportFormat::Json => std::fs::write(path, self.to_json())?,
+            ExportFormat::Csv => std::fs::write(path, self.to_csv())?,
+        }
+
+        Ok(())
+    }
+}
+
 /// Enum representing the name of a musical note, consisting of the note's
 /// letter name and octave
-#[derive(PartialEq)]
+///
+/// Note names are ordered by pitch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum NoteName {
     A(u8),
     ASharp(u8),
@@ -69,7 +527,7 @@ impl NoteName {
         let note_num = (12.0 * (pitch / 440.0).log(2.0) + 48.5).floor() as i32;
         let mut note_index = note_num % 12;
         if note_index < 0 {
-            note_index = 12 + note_index;
+            note_index += 12;
         }
         // Add 9 to the note number to account for the fact that the octave
         // changes at C notes.
@@ -93,190 +551,2806 @@ impl NoteName {
             _ => unreachable!(),
         }
     }
+
+    /// Returns the equal temperament frequency (in Hz) of the note, where A4
+    /// is 440 Hz
+    pub fn frequency(&self) -> f64 {
+        // A4 is 57 semitones above C0
+        440.0 * 2.0f64.powf((self.semitone_index() - 57) as f64 / 12.0)
+    }
+
+    /// Returns the number of semitones from the note's pitch class up to
+    /// the pitch class of `other`, ignoring octaves
+    ///
+    /// The result is in the range 0 to 11. For example, the interval from
+    /// C to G is 7, and the interval from G to C is 5.
+    pub fn interval_to(&self, other: &NoteName) -> u8 {
+        (other.pitch_class() + 12 - self.pitch_class()) % 12
+    }
+
+    /// Returns the number of semitones from the note up to `other`
+    ///
+    /// The result is negative if `other` is lower than the note. For
+    /// example, the distance from C4 to G4 is 7, and the distance from C4
+    /// to G3 is -5.
+    pub fn semitone_distance(&self, other: &NoteName) -> i32 {
+        other.semitone_index() - self.semitone_index()
+    }
+
+    /// Returns the note which is the given number of semitones above the
+    /// note, or below it if `semitones` is negative
+    ///
+    /// Accidentals in the result are spelled as sharps. Panics if the result
+    /// is below C0 or its octave does not fit in a `u8`.
+    pub fn transpose(&self, semitones: i32) -> NoteName {
+        NoteName::from_semitone_index(self.semitone_index() + semitones)
+    }
+
+    /// Returns the pitch class of the note, where C is 0 and B is 11
+    pub(crate) fn pitch_class(&self) -> u8 {
+        match *self {
+            NoteName::C(_) => 0,
+            NoteName::CSharp(_) => 1,
+            NoteName::D(_) => 2,
+            NoteName::DSharp(_) => 3,
+            NoteName::E(_) => 4,
+            NoteName::F(_) => 5,
+            NoteName::FSharp(_) => 6,
+            NoteName::G(_) => 7,
+            NoteName::GSharp(_) => 8,
+            NoteName::A(_) => 9,
+            NoteName::ASharp(_) => 10,
+            NoteName::B(_) => 11,
+        }
+    }
+
+    /// Returns the octave of the note
+    pub(crate) fn octave(&self) -> u8 {
+        match *self {
+            NoteName::A(octave)
+            | NoteName::ASharp(octave)
+            | NoteName::B(octave)
+            | NoteName::C(octave)
+            | NoteName::CSharp(octave)
+            | NoteName::D(octave)
+            | NoteName::DSharp(octave)
+            | NoteName::E(octave)
+            | NoteName::F(octave)
+            | NoteName::FSharp(octave)
+            | NoteName::G(octave)
+            | NoteName::GSharp(octave) => octave,
+        }
+    }
+
+    /// Returns the name of the note, including its octave, with accidentals
+    /// spelled in the given style
+    ///
+    /// Notes without accidentals are spelled the same way in every style.
+    pub fn display_with_style(&self, style: DisplayStyle) -> String {
+        const SHARP_NAMES: [&str; 12] = [
+            "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+        ];
+        const FLAT_NAMES: [&str; 12] = [
+            "C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B",
+        ];
+
+        let names = match style {
+            DisplayStyle::Sharp => SHARP_NAMES,
+            DisplayStyle::Flat => FLAT_NAMES,
+        };
+
+        format!("{}{}", names[self.pitch_class() as usize], self.octave())
+    }
+
+    /// Returns the name of the note, including its octave, with accidentals
+    /// spelled as flats, for example Bb4 instead of A#4
+    pub fn to_flat_string(&self) -> String {
+        self.display_with_style(DisplayStyle::Flat)
+    }
+
+    /// Returns the MIDI note number of the note, where C4 is 60 and A4 is 69
+    ///
+    /// Panics if the note is above G9, the highest MIDI note.
+    pub fn to_midi_number(&self) -> u8 {
+        let midi_number = self.semitone_index() + 12;
+        assert!(
+            midi_number <= 127,
+            "notes above G9 have no MIDI note number"
+        );
+
+        midi_number as u8
+    }
+
+    /// Returns the note with the given MIDI note number, where C4 is 60 and
+    /// A4 is 69
+    ///
+    /// Panics if the number is below 12 (C0) or above 127 (G9), since notes in
+    /// octave -1 cannot be represented.
+    pub fn from_midi_number(n: u8) -> NoteName {
+        assert!(n >= 12, "MIDI notes below C0 are not supported");
+        assert!(n <= 127, "MIDI note numbers must not be greater than 127");
+
+        NoteName::from_semitone_index(n as i32 - 12)
+    }
+
+    /// Returns the number of semitones between C0 and the note
+    fn semitone_index(&self) -> i32 {
+        12 * self.octave() as i32 + self.pitch_class() as i32
+    }
+
+    /// Returns the note which is the given number of semitones above C0
+    fn from_semitone_index(index: i32) -> NoteName {
+        assert!(index >= 0, "notes below C0 are not supported");
+
+        let octave = u8::try_from(index / 12).expect("octave must fit in a u8");
+
+        match index % 12 {
+            0 => NoteName::C(octave),
+            1 => NoteName::CSharp(octave),
+            2 => NoteName::D(octave),
+            3 => NoteName::DSharp(octave),
+            4 => NoteName::E(octave),
+            5 => NoteName::F(octave),
+            6 => NoteName::FSharp(octave),
+            7 => NoteName::G(octave),
+            8 => NoteName::GSharp(octave),
+            9 => NoteName::A(octave),
+            10 => NoteName::ASharp(octave),
+            11 => NoteName::B(octave),
+            _ => unreachable!(),
+        }
+    }
 }
 
 impl fmt::Display for NoteName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            NoteName::A(octave) => write!(f, "A{}", octave),
-            NoteName::ASharp(octave) => write!(f, "A#{}", octave),
-            NoteName::B(octave) => write!(f, "B{}", octave),
-            NoteName::C(octave) => write!(f, "C{}", octave),
-            NoteName::CSharp(octave) => write!(f, "C#{}", octave),
-            NoteName::D(octave) => write!(f, "D{}", octave),
-            NoteName::DSharp(octave) => write!(f, "D#{}", octave),
-            NoteName::E(octave) => write!(f, "E{}", octave),
-            NoteName::F(octave) => write!(f, "F{}", octave),
-            NoteName::FSharp(octave) => write!(f, "F#{}", octave),
-            NoteName::G(octave) => write!(f, "G{}", octave),
-            NoteName::GSharp(octave) => write!(f, "G#{}", octave),
-        }
+        write!(f, "{}", self.display_with_style(DisplayStyle::Sharp))
     }
 }
 
-struct PitchFrame {
-    start_pos: usize,
-    frame_width: usize,
-    pitch: Option<f64>,
+impl Ord for NoteName {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.semitone_index().cmp(&other.semitone_index())
+    }
 }
 
-/// Struct for music transcription functionalities
-pub struct Transcriber;
+impl PartialOrd for NoteName {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-impl Transcriber {
-    /// Finds the musical notes in the audio data
+/// Note names are serialized as strings such as `A#4`
+#[cfg(feature = "serde")]
+impl serde::Serialize for NoteName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NoteName {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl FromStr for NoteName {
+    type Err = NoteParseError;
+
+    /// Parses a note name such as `C#4` or `Bb3`
     ///
-    /// Returns a vector of the notes detected in the audio.
-    pub fn get_notes(audio_data: AudioData) -> Vec<Note> {
-        // Scale samples
-        let abs_max_value = match audio_data
-            .samples
-            .iter()
-            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
-        {
-            Some(value) => *value,
-            None => return vec![],
-        }
-        .abs();
+    /// The letter is case-insensitive and may be followed by `#` (sharp) or
+    /// `b` (flat). The octave must be a single digit.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || NoteParseError(s.to_string());
 
-        let samples: Vec<f64> = audio_data
-            .samples
-            .iter()
-            .map(|x| {
-                if abs_max_value >= f64::EPSILON {
-                    x / abs_max_value
-                } else {
-                    0.0
-                }
-            })
-            .collect();
+        let mut chars = s.chars();
 
-        let audio_data = AudioData {
-            sample_rate: audio_data.sample_rate,
-            duration: audio_data.duration,
-            samples: samples.clone(),
+        let pitch_class: i32 = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('C') => 0,
+            Some('D') => 2,
+            Some('E') => 4,
+            Some('F') => 5,
+            Some('G') => 7,
+            Some('A') => 9,
+            Some('B') => 11,
+            _ => return Err(error()),
         };
 
-        let onsets = Transcriber::get_onsets(&audio_data);
+        let rest = chars.as_str();
+        let (accidental, octave) = if let Some(octave) = rest.strip_prefix('#') {
+            (1, octave)
+        } else if let Some(octave) = rest.strip_prefix('b') {
+            (-1, octave)
+        } else {
+            (0, rest)
+        };
 
-        let frames = audio_data
-            .get_frames_by_index(onsets)
-            .expect("Error getting frames");
+        let octave = match octave.as_bytes() {
+            [digit @ b'0'..=b'9'] => (digit - b'0') as i32,
+            _ => return Err(error()),
+        };
 
-        // Limit frame width to 8192 samples
-        let frames = frames.iter().map(|f| {
-            let frame_width = if f.samples.len() < 8192 {
-                f.samples.len()
-            } else {
-                8192
-            };
-            let samples: Vec<f64> = f.samples[0..frame_width].to_vec();
-            Frame {
-                start_pos: f.start_pos,
-                samples: samples,
-            }
-        });
+        // Notes such as Cb4 and B#3 belong to the neighbouring octave
+        let index = 12 * octave + pitch_class + accidental;
+        if index < 0 {
+            return Err(error());
+        }
 
-        // Filter out silent frames by removing frames where the RMS is less
-        // than 20% of the RMS of the entire audio
-        // TODO: Implement a more sophisticated algorithm for filtering out
-        // silent frames
-        let audio_rms =
-            AudioData::root_mean_square(samples.into_iter().map(|s| s as f64).collect())
-                .unwrap_or(0.0);
+        Ok(NoteName::from_semitone_index(index))
+    }
+}
 
-        let frames: Vec<Frame> = frames
-            .into_iter()
-            .filter(|f| {
-                AudioData::root_mean_square(f.samples.clone()).unwrap_or(0.0) >= 0.2 * audio_rms
-            })
-            .collect();
+/// Enum representing the ways in which the names of notes with accidentals
+/// can be spelled
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DisplayStyle {
+    /// Accidentals are spelled as sharps, for example A#4
+    Sharp,
 
-        // Get the pitch in each frame
-        let mpm = Mpm::new(0.7, audio_data.sample_rate);
-        let pitches = frames
-            .into_iter()
-            .map(|frame| PitchFrame {
-                start_pos: frame.start_pos,
-                frame_width: frame.samples.len(),
-                pitch: mpm
-                    .clone()
-                    .get_pitch(frame.samples.into_iter().map(|x| x as f64).collect()),
-            })
-            .collect::<Vec<PitchFrame>>();
+    /// Accidentals are spelled as flats, for example Bb4
+    Flat,
+}
 
-        // Exclude frames where no pitch was detected
-        let pitch_frames: Vec<PitchFrame> =
-            pitches.into_iter().filter(|p| p.pitch.is_some()).collect();
+/// Enum representing the mode of a scale or key
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Major,
+    Minor,
+}
 
-        let notes: Vec<Note> = pitch_frames
-            .iter()
-            .map(|pitch_frame| Note {
-                name: NoteName::from_pitch(pitch_frame.pitch.unwrap()),
-                start: pitch_frame.start_pos,
-                duration: pitch_frame.frame_width,
-            })
-            .collect();
+/// Struct representing a diatonic scale, consisting of the scale's root note
+/// and mode
+///
+/// The octave of the root note is ignored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scale {
+    pub root: NoteName,
+    pub mode: Mode,
+}
 
-        notes
+impl Scale {
+    /// Creates a new scale with the given root note and mode
+    pub fn new(root: NoteName, mode: Mode) -> Scale {
+        Scale { root, mode }
     }
 
-    /// Finds the onsets of notes in the audio
-    ///
-    /// Returns a vector of indices at which note onsets were detected.
-    fn get_onsets(audio_data: &AudioData) -> Vec<usize> {
-        // Calculate envelope
-        let onset_frame_width = 1600;
-        let onset_frames = audio_data.get_frames(onset_frame_width, onset_frame_width, None, None);
+    /// Returns the intervals (in semitones) of each scale degree above the
+    /// root note
+    pub fn intervals(&self) -> [u8; 7] {
+        match self.mode {
+            Mode::Major => [0, 2, 4, 5, 7, 9, 11],
+            Mode::Minor => [0, 2, 3, 5, 7, 8, 10],
+        }
+    }
 
-        // Make all samples positive
-        let abs_frames: Vec<Vec<f64>> = onset_frames
-            .iter()
-            .map(|f| f.samples.iter().map(|x| x.abs()).collect::<Vec<f64>>())
-            .collect();
+    /// Checks whether the pitch class of the note belongs to the scale
+    pub fn contains(&self, name: &NoteName) -> bool {
+        self.intervals().contains(&self.root.interval_to(name))
+    }
+}
+
+/// Struct for mapping notes onto the nearest note of a scale
+pub struct ScaleQuantizer {
+    scale: Scale,
+}
 
-        let envelope: Vec<f64> = abs_frames
+impl ScaleQuantizer {
+    /// Creates a new scale quantizer for the given scale
+    pub fn new(scale: Scale) -> ScaleQuantizer {
+        ScaleQuantizer { scale }
+    }
+
+    /// Replaces the name of each note with the nearest note name which
+    /// belongs to the scale
+    ///
+    /// Notes already in the scale are unchanged. If two scale notes are
+    /// equally close, the lower one is chosen.
+    pub fn quantize(&self, notes: Vec<Note>) -> Vec<Note> {
+        notes
             .into_iter()
-            .map(|samples| {
-                samples
-                    .into_iter()
-                    .max_by(|a, b| a.total_cmp(b))
-                    .unwrap_or(0.0)
+            .map(|note| Note {
+                name: self.nearest_scale_note(&note.name),
+                ..note
             })
-            .collect();
+            .collect()
+    }
 
-        let mut indices = vec![];
+    /// Finds the note in the scale which is closest to the given note
+    fn nearest_scale_note(&self, name: &NoteName) -> NoteName {
+        let index = name.semitone_index();
 
-        // The index of each envelope value should be at the centre of its corresponding frame
-        for frame in onset_frames {
-            indices.push(frame.start_pos + onset_frame_width / 2);
+        // Every pitch class is at most 6 semitones away from any scale note
+        for distance in 0..=6 {
+            for candidate in [index - distance, index + distance] {
+                if candidate >= 0 {
+                    let candidate = NoteName::from_semitone_index(candidate);
+                    if self.scale.contains(&candidate) {
+                        return candidate;
+                    }
+                }
+            }
         }
 
-        // Get differences between consecutive elements of the envelope
-        let mut differences = vec![0.0];
+        unreachable!("every note is within 6 semitones of a scale note")
+    }
+}
 
-        for i in 1..envelope.len() {
-            differences.push(envelope[i] - envelope[i - 1]);
-        }
+/// Krumhansl-Kessler probe tone profile for major keys, starting at the tonic
+const MAJOR_KEY_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
 
-        // Get the onsets
-        let mut onsets = vec![];
-        let mut add_onset = true;
-        let difference_threshold = 0.125;
+/// Krumhansl-Kessler probe tone profile for minor keys, starting at the tonic
+const MINOR_KEY_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
 
-        for i in 0..indices.len() {
-            if i < differences.len()
-                && differences[(indices[i] - onset_frame_width / 2) / onset_frame_width]
-                    > difference_threshold
-                && add_onset
-            {
-                onsets.push(onset_frame_width * i + onset_frame_width / 2);
-                add_onset = false;
-            } else {
-                add_onset = true;
-            }
-        }
+/// Intervals (in semitones) above the root of the pitch classes of each chord
+/// quality
+const CHORD_TEMPLATES: [(ChordQuality, &[u8]); 11] = [
+    (ChordQuality::Major, &[0, 4, 7]),
+    (ChordQuality::Minor, &[0, 3, 7]),
+    (ChordQuality::Diminished, &[0, 3, 6]),
+    (ChordQuality::Augmented, &[0, 4, 8]),
+    (ChordQuality::SuspendedSecond, &[0, 2, 7]),
+    (ChordQuality::SuspendedFourth, &[0, 5, 7]),
+    (ChordQuality::DominantSeventh, &[0, 4, 7, 10]),
+    (ChordQuality::MajorSeventh, &[0, 4, 7, 11]),
+    (ChordQuality::MinorSeventh, &[0, 3, 7, 10]),
+    (ChordQuality::HalfDiminishedSeventh, &[0, 3, 6, 10]),
+    (ChordQuality::DiminishedSeventh, &[0, 3, 6, 9]),
+];
+
+/// Minimum number of notes needed to detect the key of a transcription
+const MIN_KEY_NOTES: usize = 8;
+
+/// Number of frequency bins on either side of each harmonic of a detected
+/// note which are removed from the spectrum before detecting the next note
+/// of a chord
+const HARMONIC_SUPPRESSION_BINS: usize = 3;
+
+/// Notes of a chord are no longer detected once the energy remaining in the
+/// spectrum falls below this fraction of the energy of the frame
+const MIN_VOICE_ENERGY_RATIO: f64 = 0.05;
+
+/// Minimum magnitude of a spectral peak which bounds the pitch of the next
+/// note of a chord, as a fraction of the largest magnitude in the spectrum
+const MIN_PEAK_MAGNITUDE_RATIO: f64 = 0.1;
+
+/// Slowest tempo (in beats per minute) which can be estimated
+const MIN_TEMPO_BPM: f64 = 40.0;
+
+/// Fastest tempo (in beats per minute) which can be estimated
+const MAX_TEMPO_BPM: f64 = 200.0;
+
+/// Tempo (in beats per minute) of a transcription whose tempo cannot be
+/// estimated
+const DEFAULT_TEMPO_BPM: f64 = 120.0;
+
+/// Number of measures on each line of a lead sheet
+const LEAD_SHEET_MEASURES_PER_LINE: usize = 4;
+
+/// Lengths (in beats) of the note values which note durations are quantised
+/// to, from a whole note to a sixteenth note
+const NOTE_VALUE_BEATS: [f64; 7] = [4.0, 3.0, 2.0, 1.5, 1.0, 0.5, 0.25];
 
-        onsets
+/// Parameters for the transcription pipeline
+pub struct TranscriptionConfig {
+    /// Detector used to find the onsets of notes, which is a
+    /// `SpectralFluxDetector` by default
+    pub onset_detector: Box<dyn OnsetDetector>,
+
+    /// Maximum number of samples after each onset which are used to detect
+    /// the pitch of the note
+    pub max_pitch_frame_width: usize,
+
+    /// Notes whose RMS is less than this fraction of the RMS of the entire
+    /// audio are treated as silence and discarded
+    pub silence_rms_ratio: f64,
+
+    /// Threshold used by the McLeod Pitch Method pitch detector
+    pub mpm_threshold: f64,
+
+    /// (Optional) Width of the median filter applied to the NSDF by the
+    /// McLeod Pitch Method pitch detector. Must be odd.
+    pub mpm_nsdf_median_window: Option<usize>,
+
+    /// (Optional) Lowest pitch (in Hz) which can be detected
+    pub min_frequency: Option<f64>,
+
+    /// (Optional) Highest pitch (in Hz) which can be detected
+    pub max_frequency: Option<f64>,
+
+    /// Maximum number of simultaneous notes detected by `get_chords`
+    pub max_voices: usize,
+
+    /// (Optional) Notes shorter than this number of samples are discarded
+    pub min_duration_samples: Option<usize>,
+
+    /// (Optional) Notes longer than this number of samples are shortened to
+    /// this duration
+    pub max_duration_samples: Option<usize>,
+
+    /// (Optional) Quantizer which moves each detected note onto the nearest
+    /// note of a known scale
+    pub scale_quantizer: Option<ScaleQuantizer>,
+}
+
+impl Default for TranscriptionConfig {
+    fn default() -> Self {
+        TranscriptionConfig {
+            onset_detector: Box::new(SpectralFluxDetector::default()),
+            max_pitch_frame_width: 8192,
+            silence_rms_ratio: 0.2,
+            mpm_threshold: 0.7,
+            mpm_nsdf_median_window: None,
+            min_frequency: None,
+            max_frequency: None,
+            max_voices: 3,
+            min_duration_samples: None,
+            max_duration_samples: None,
+            scale_quantizer: None,
+        }
+    }
+}
+
+impl TranscriptionConfig {
+    /// Creates the pitch detector described by the configuration
+    fn pitch_detector(&self, sample_rate: u32) -> Mpm {
+        let mut mpm = Mpm::new(self.mpm_threshold, sample_rate);
+
+        if let Some(window) = self.mpm_nsdf_median_window {
+            mpm = mpm.with_nsdf_median_filter(window);
+        }
+
+        match (self.min_frequency, self.max_frequency) {
+            (None, None) => mpm,
+            (min_frequency, max_frequency) => mpm.with_range(
+                min_frequency.unwrap_or(f64::MIN_POSITIVE),
+                max_frequency.unwrap_or(f64::INFINITY),
+            ),
+        }
+    }
+}
+
+struct PitchFrame {
+    start_pos: usize,
+    frame_width: usize,
+    pitch: Option<PitchResult>,
+}
+
+/// Struct for music transcription functionalities
+pub struct Transcriber;
+
+impl Transcriber {
+    /// Finds the musical notes in the audio data
+    ///
+    /// Returns a vector of the notes detected in the audio, in ascending order
+    /// of start position. Notes with the same start position keep the order
+    /// in which they were detected.
+    ///
+    /// The pitch of each frame is detected in parallel.
+    pub fn get_notes(audio_data: AudioData) -> Vec<Note> {
+        Transcriber::get_notes_with_config(audio_data, TranscriptionConfig::default())
+    }
+
+    /// Finds the musical notes in the audio data, along with the times (in
+    /// seconds) at which they start and stop playing
+    ///
+    /// Returns the notes in the same order as `get_notes`.
+    pub fn get_notes_with_timestamps(audio_data: AudioData) -> Vec<TimedNote> {
+        let sample_rate = audio_data.sample_rate;
+
+        Transcriber::get_notes(audio_data)
+            .into_iter()
+            .map(|note| TimedNote::new(note, sample_rate))
+            .collect()
+    }
+
+    /// Transcribes the notes and tempo of the audio data, and its chords if
+    /// `with_chords` is true
+    ///
+    /// The notes are found with `get_notes`, and the tempo with
+    /// `estimate_tempo`, falling back to 120 BPM if it cannot be estimated.
+    /// Chords are found with `get_chords` and named with `detect_chords`.
+    pub fn transcribe(audio_data: AudioData, with_chords: bool) -> TranscriptionResult {
+        let sample_rate = audio_data.sample_rate;
+        let bpm = Transcriber::estimate_tempo(&audio_data).unwrap_or(DEFAULT_TEMPO_BPM);
+        let chords = with_chords
+            .then(|| Transcriber::detect_chords(&Transcriber::get_chords(audio_data.clone())));
+
+        TranscriptionResult {
+            notes: Transcriber::get_notes(audio_data),
+            chords,
+            sample_rate,
+            bpm,
+        }
+    }
+
+    /// Finds the musical notes in the audio data, using the given parameters
+    /// for each stage of the transcription
+    ///
+    /// Returns the notes in the same order as `get_notes`.
+    pub fn get_notes_with_config(audio_data: AudioData, config: TranscriptionConfig) -> Vec<Note> {
+        let mut notes = vec![];
+        Transcriber::detect_notes(&audio_data, &config, true, |note| notes.push(note), |_| {});
+
+        Transcriber::post_process(notes, &config)
+    }
+
+    /// Finds the musical notes in the audio data, calling `progress` with the
+    /// fraction of the audio which has been transcribed
+    ///
+    /// `progress` is called once after each onset frame is processed, with
+    /// values increasing from 0 to 1. Frames are processed one at a time, so
+    /// this is slower than `get_notes_with_config` on multi-core machines.
+    ///
+    /// Returns the notes in the same order as `get_notes`.
+    pub fn get_notes_with_progress(
+        audio_data: AudioData,
+        config: TranscriptionConfig,
+        progress: impl Fn(f32),
+    ) -> Vec<Note> {
+        let mut notes = vec![];
+        Transcriber::detect_notes(
+            &audio_data,
+            &config,
+            false,
+            |note| notes.push(note),
+            progress,
+        );
+
+        Transcriber::post_process(notes, &config)
+    }
+
+    /// Sorts the detected notes by start position, then applies the
+    /// configured duration limits and scale quantization
+    fn post_process(mut notes: Vec<Note>, config: &TranscriptionConfig) -> Vec<Note> {
+        // Stable sort, so simultaneous notes keep their relative order
+        notes.sort_by_key(|n| n.start);
+
+        let notes = Transcriber::filter_durations(notes, config);
+
+        match &config.scale_quantizer {
+            Some(scale_quantizer) => scale_quantizer.quantize(notes),
+            None => notes,
+        }
+    }
+
+    /// Discards notes shorter than the configured minimum duration and
+    /// shortens notes longer than the configured maximum duration
+    fn filter_durations(notes: Vec<Note>, config: &TranscriptionConfig) -> Vec<Note> {
+        notes
+            .into_iter()
+            .filter(|note| {
+                config
+                    .min_duration_samples
+                    .is_none_or(|min_duration| note.duration >= min_duration)
+            })
+            .map(|note| match config.max_duration_samples {
+                Some(max_duration) => Note {
+                    duration: std::cmp::min(note.duration, max_duration),
+                    ..note
+                },
+                None => note,
+            })
+            .collect()
+    }
+
+    /// Finds the musical notes in the audio data, using the given parameters
+    /// for each stage of the transcription, and sends each note through the
+    /// channel as soon as it is detected
+    ///
+    /// Notes are sent in the order in which they are detected, which is the
+    /// order of their onsets, and are the same notes as those returned by
+    /// `get_notes_with_config`. The sender is dropped once the whole audio
+    /// has been processed, closing the channel. If the receiver is dropped
+    /// before then, the remaining notes are discarded.
+    pub fn transcribe_to_channel(
+        audio_data: AudioData,
+        config: TranscriptionConfig,
+        tx: Sender<Note>,
+    ) {
+        Transcriber::transcribe_to_channel_with_progress(audio_data, config, tx, |_| {});
+    }
+
+    /// Finds the musical notes in the audio data, sending each note through
+    /// the channel as soon as it is detected and calling `progress` as in
+    /// `get_notes_with_progress`
+    pub fn transcribe_to_channel_with_progress(
+        audio_data: AudioData,
+        config: TranscriptionConfig,
+        tx: Sender<Note>,
+        progress: impl Fn(f32),
+    ) {
+        Transcriber::detect_notes(
+            &audio_data,
+            &config,
+            false,
+            |note| {
+                // Each note is post-processed on its own, since notes are
+                // detected in order of their onsets
+                for note in Transcriber::post_process(vec![note], &config) {
+                    let _ = tx.send(note);
+                }
+            },
+            progress,
+        );
+    }
+
+    /// Finds the musical notes in the audio data, calling `on_note` with each
+    /// note as it is detected
+    ///
+    /// If `parallel` is true, the pitches of all frames are detected in
+    /// parallel before any notes are passed to `on_note`. Otherwise each
+    /// frame is processed in turn, so the first notes are available sooner.
+    /// Notes are passed to `on_note` in the same order either way.
+    ///
+    /// `on_progress` is called with the fraction of onset frames which have
+    /// been processed after each frame, and with 1 if there are no frames.
+    fn detect_notes(
+        audio_data: &AudioData,
+        config: &TranscriptionConfig,
+        parallel: bool,
+        mut on_note: impl FnMut(Note),
+        mut on_progress: impl FnMut(f32),
+    ) {
+        if audio_data.samples.is_empty() {
+            on_progress(1.0);
+            return;
+        }
+
+        let audio_data = Transcriber::scale_samples(audio_data);
+
+        let frames = Transcriber::onset_frames(&audio_data, config);
+
+        if frames.is_empty() {
+            on_progress(1.0);
+            return;
+        }
+
+        let num_frames = frames.len();
+
+        // Get the pitch in each frame. Silent frames are kept so that
+        // progress can be reported for them, but no pitch is detected in them.
+        let mpm = config.pitch_detector(audio_data.sample_rate);
+        let detect_pitch = |(frame, is_silent): (Frame, bool)| PitchFrame {
+            start_pos: frame.start_pos,
+            frame_width: frame.samples.len(),
+            pitch: if is_silent {
+                None
+            } else {
+                mpm.get_pitch_with_confidence(frame.samples)
+            },
+        };
+
+        // Parallel iterators preserve the order of the frames when collected
+        let pitch_frames: Box<dyn Iterator<Item = PitchFrame>> = if parallel {
+            let pitch_frames: Vec<PitchFrame> = frames.into_par_iter().map(detect_pitch).collect();
+            Box::new(pitch_frames.into_iter())
+        } else {
+            Box::new(frames.into_iter().map(detect_pitch))
+        };
+
+        // Exclude frames where no pitch was detected
+        for (i, pitch_frame) in pitch_frames.enumerate() {
+            if let Some(pitch) = pitch_frame.pitch {
+                let name = NoteName::from_pitch(pitch.frequency);
+                on_note(Note {
+                    name,
+                    start: pitch_frame.start_pos,
+                    duration: pitch_frame.frame_width,
+                    confidence: pitch.confidence,
+                    cents_deviation: 1200.0 * (pitch.frequency / name.frequency()).log2(),
+                });
+            }
+
+            on_progress((i + 1) as f32 / num_frames as f32);
+        }
+    }
+
+    /// Splits the audio into one frame for each onset found by the configured
+    /// onset detector, along with whether each frame is silent
+    ///
+    /// Each frame runs from its onset to the next onset, and is cut short to
+    /// at most `max_pitch_frame_width` samples. Silent frames are frames
+    /// where the RMS is less than `silence_rms_ratio` times the RMS of the
+    /// entire audio.
+    fn onset_frames(audio_data: &AudioData, config: &TranscriptionConfig) -> Vec<(Frame, bool)> {
+        // Custom onset detectors may find onsets out of order or more than
+        // once, but frames must start at distinct, ascending positions
+        let mut onsets = config.onset_detector.get_onset_samples(audio_data);
+        onsets.sort_unstable();
+        onsets.dedup();
+
+        let frames = audio_data
+            .get_frames_by_index(onsets)
+            .expect("Error getting frames");
+
+        // TODO: Implement a more sophisticated algorithm for filtering out
+        // silent frames
+        let audio_rms = audio_data.rms().unwrap_or(0.0);
+
+        frames
+            .into_iter()
+            .map(|mut frame| {
+                let frame_width = std::cmp::min(frame.samples.len(), config.max_pitch_frame_width);
+                frame.samples.truncate(frame_width);
+
+                let is_silent = frame.rms().unwrap_or(0.0) < config.silence_rms_ratio * audio_rms;
+                (frame, is_silent)
+            })
+            .collect()
+    }
+
+    /// Finds the chords in the audio data, where each chord is the set of
+    /// notes which start at an onset
+    ///
+    /// Up to 3 notes are detected in each onset frame. Returns the chords in
+    /// order of start position. Onset frames in which no pitch was detected
+    /// are skipped.
+    pub fn get_chords(audio_data: AudioData) -> Vec<Chord> {
+        Transcriber::get_chords_with_config(audio_data, TranscriptionConfig::default())
+    }
+
+    /// Finds the chords in the audio data, using the given parameters for each
+    /// stage of the transcription
+    ///
+    /// Onsets and silent frames are found in the same way as in
+    /// `get_notes_with_config`. The notes of each chord are found by iterative
+    /// subtraction: the dominant pitch of the frame is detected, its harmonics
+    /// are removed from the spectrum of the frame, and the process is repeated
+    /// on what remains. This stops after `max_voices` notes, when no pitch is
+    /// detected, when a note is detected twice, or when little energy remains.
+    pub fn get_chords_with_config(
+        audio_data: AudioData,
+        config: TranscriptionConfig,
+    ) -> Vec<Chord> {
+        if audio_data.samples.is_empty() {
+            return vec![];
+        }
+
+        let audio_data = Transcriber::scale_samples(&audio_data);
+
+        Transcriber::onset_frames(&audio_data, &config)
+            .into_par_iter()
+            .filter_map(|(frame, is_silent)| {
+                if is_silent {
+                    return None;
+                }
+                let frame_width = frame.samples.len();
+
+                let mut notes: Vec<Note> =
+                    Transcriber::detect_voices(&frame.samples, audio_data.sample_rate, &config)
+                        .into_iter()
+                        .map(|pitch| {
+                            let name = NoteName::from_pitch(pitch.frequency);
+                            Note {
+                                name,
+                                start: frame.start_pos,
+                                duration: frame_width,
+                                confidence: pitch.confidence,
+                                cents_deviation: 1200.0
+                                    * (pitch.frequency / name.frequency()).log2(),
+                            }
+                        })
+                        .collect();
+
+                if notes.is_empty() {
+                    return None;
+                }
+                notes.sort_by_key(|note| note.name);
+
+                Some(Chord {
+                    start: frame.start_pos,
+                    duration: frame_width,
+                    notes,
+                })
+            })
+            .collect()
+    }
+
+    /// Identifies the root and quality of each chord
+    ///
+    /// The set of pitch classes in each chord is compared with the chord
+    /// templates, taking each note of the chord as the root in turn, starting
+    /// from the lowest. Trying the lowest note first means that chords whose
+    /// pitch classes match more than one template, such as Csus2 and Gsus4,
+    /// are named after their bass note. Chords which do not match any
+    /// template are left out of the result.
+    pub fn detect_chords(chords: &[Chord]) -> Vec<NamedChord> {
+        chords
+            .iter()
+            .filter_map(|chord| {
+                let mut notes: Vec<NoteName> = chord.notes.iter().map(|note| note.name).collect();
+                notes.sort();
+
+                notes.iter().find_map(|root| {
+                    let mut intervals: Vec<u8> =
+                        notes.iter().map(|name| root.interval_to(name)).collect();
+                    intervals.sort();
+                    intervals.dedup();
+
+                    CHORD_TEMPLATES
+                        .iter()
+                        .find(|(_, template)| *template == intervals.as_slice())
+                        .map(|(quality, _)| NamedChord {
+                            chord: chord.clone(),
+                            root: NoteName::from_semitone_index(root.pitch_class() as i32),
+                            quality: *quality,
+                        })
+                })
+            })
+            .collect()
+    }
+
+    /// Detects up to `max_voices` simultaneous pitches in the samples by
+    /// iteratively detecting the dominant pitch and removing its harmonics
+    /// from the spectrum
+    ///
+    /// The notes of a chord share a common subharmonic, which the McLeod Pitch
+    /// Method would detect as the pitch of the whole chord. Instead, the
+    /// lowest significant peak in the remaining spectrum is taken to be the
+    /// fundamental of the lowest remaining note, and the pitch is detected
+    /// within a semitone of the peak in a signal containing only the
+    /// harmonics of the peak.
+    ///
+    /// Returns the pitches in the order in which they were detected.
+    fn detect_voices(
+        samples: &[f64],
+        sample_rate: u32,
+        config: &TranscriptionConfig,
+    ) -> Vec<PitchResult> {
+        let fft_length = samples.len();
+        let bin_width = sample_rate as f64 / fft_length as f64;
+
+        let mut spectrum = forward_fft(&hann_window(samples), fft_length);
+        let energy = |spectrum: &[Complex<f64>]| spectrum.iter().map(|x| x.norm_sqr()).sum::<f64>();
+        let frame_energy = energy(&spectrum);
+
+        // Indices of the bins within a few bins of each harmonic of the
+        // frequency, along with the mirrored negative frequency bins
+        let harmonic_bins = |frequency: f64| {
+            let num_harmonics = (sample_rate as f64 / 2.0 / frequency) as usize;
+            (1..=num_harmonics).flat_map(move |harmonic| {
+                let centre = (harmonic as f64 * frequency / bin_width).round() as usize;
+                let start = centre.saturating_sub(HARMONIC_SUPPRESSION_BINS).max(1);
+                let end = std::cmp::min(centre + HARMONIC_SUPPRESSION_BINS, fft_length / 2);
+                (start..=end).flat_map(move |k| [k, fft_length - k])
+            })
+        };
+
+        // Ratio between the frequency of the lowest spectral peak and the
+        // lowest pitch which is searched for as the next note, which is a
+        // semitone below the peak. The highest pitch searched for is a
+        // semitone above the peak.
+        let lowest_peak_tolerance = 2f64.powf(-1.0 / 12.0);
+
+        let mut pitches: Vec<PitchResult> = vec![];
+
+        while pitches.len() < config.max_voices
+            && energy(&spectrum) > MIN_VOICE_ENERGY_RATIO * frame_energy
+        {
+            let lowest_peak = match Transcriber::lowest_spectral_peak(&spectrum, bin_width) {
+                Some(peak) => peak,
+                None => break,
+            };
+
+            let min_frequency = config
+                .min_frequency
+                .unwrap_or(0.0)
+                .max(lowest_peak * lowest_peak_tolerance);
+            let max_frequency = config
+                .max_frequency
+                .unwrap_or(sample_rate as f64 / 2.0)
+                .min(lowest_peak / lowest_peak_tolerance);
+            if min_frequency >= max_frequency {
+                break;
+            }
+
+            let mut isolated = vec![Complex { re: 0.0, im: 0.0 }; fft_length];
+            for k in harmonic_bins(lowest_peak) {
+                isolated[k] = spectrum[k];
+            }
+
+            let pitch = match Mpm::new(config.mpm_threshold, sample_rate)
+                .with_range(min_frequency, max_frequency)
+                .get_pitch_with_confidence(inverse_fft(isolated))
+            {
+                Some(pitch) => pitch,
+                None => break,
+            };
+
+            let name = NoteName::from_pitch(pitch.frequency);
+            if pitches
+                .iter()
+                .any(|p| NoteName::from_pitch(p.frequency) == name)
+            {
+                break;
+            }
+            pitches.push(pitch);
+
+            for k in harmonic_bins(pitch.frequency) {
+                spectrum[k] = Complex { re: 0.0, im: 0.0 };
+            }
+        }
+
+        pitches
+    }
+
+    /// Finds the frequency (in Hz) of the lowest peak in the magnitude
+    /// spectrum which is at least a tenth of the largest magnitude
+    ///
+    /// The frequency is refined by fitting a parabola to the magnitudes of
+    /// the peak bin and its neighbours.
+    fn lowest_spectral_peak(spectrum: &[Complex<f64>], bin_width: f64) -> Option<f64> {
+        let magnitudes: Vec<f64> = spectrum[..=spectrum.len() / 2]
+            .iter()
+            .map(|x| x.norm())
+            .collect();
+        let max_magnitude = magnitudes.iter().fold(0.0, |max: f64, x| max.max(*x));
+
+        (1..magnitudes.len() - 1)
+            .find(|&k| {
+                magnitudes[k] >= MIN_PEAK_MAGNITUDE_RATIO * max_magnitude
+                    && magnitudes[k] > magnitudes[k - 1]
+                    && magnitudes[k] >= magnitudes[k + 1]
+            })
+            .map(|k| {
+                let (left, centre, right) = (magnitudes[k - 1], magnitudes[k], magnitudes[k + 1]);
+                let curvature = left - 2.0 * centre + right;
+                let offset = if curvature == 0.0 {
+                    0.0
+                } else {
+                    0.5 * (left - right) / curvature
+                };
+                (k as f64 + offset) * bin_width
+            })
+    }
+
+    /// Finds the total duration (in samples) of the notes detected in the
+    /// audio data for each pitch class, where C is 0 and B is 11
+    ///
+    /// The notes are the same as those found by `get_notes_with_config`, but
+    /// are added to the histogram as they are detected rather than collected.
+    pub(crate) fn pitch_class_histogram(
+        audio_data: &AudioData,
+        config: &TranscriptionConfig,
+    ) -> [f64; 12] {
+        let mut histogram = [0.0; 12];
+
+        Transcriber::detect_notes(
+            audio_data,
+            config,
+            true,
+            |note| {
+                for note in Transcriber::post_process(vec![note], config) {
+                    histogram[note.name.pitch_class() as usize] += note.duration as f64;
+                }
+            },
+            |_| {},
+        );
+
+        histogram
+    }
+
+    /// Detects the key of a transcription
+    ///
+    /// The number of notes in each pitch class is compared with the major and
+    /// minor key profiles in the same way as `AudioData::detect_key`, but
+    /// without weighting by note duration. The octave of the returned tonic
+    /// is always 0. Returns None if there are fewer than 8 notes.
+    pub fn detect_key(notes: &[Note]) -> Option<(NoteName, Mode)> {
+        if notes.len() < MIN_KEY_NOTES {
+            return None;
+        }
+
+        let mut histogram = [0.0; 12];
+        for note in notes {
+            histogram[note.name.pitch_class() as usize] += 1.0;
+        }
+
+        Transcriber::estimate_key(&histogram)
+    }
+
+    /// Estimates the key from a pitch class histogram using the
+    /// Krumhansl-Schmuckler key-finding algorithm
+    ///
+    /// The histogram is correlated with the major and minor key profiles
+    /// rotated to each of the 12 possible tonics, and the key with the
+    /// highest correlation is returned. The octave of the returned tonic is
+    /// always 0. Returns None if the histogram is empty.
+    pub(crate) fn estimate_key(histogram: &[f64; 12]) -> Option<(NoteName, Mode)> {
+        if histogram.iter().all(|x| *x == 0.0) {
+            return None;
+        }
+
+        let mut best_key = None;
+        let mut best_correlation = f64::NEG_INFINITY;
+
+        for (mode, profile) in [
+            (Mode::Major, MAJOR_KEY_PROFILE),
+            (Mode::Minor, MINOR_KEY_PROFILE),
+        ] {
+            for tonic in 0..12 {
+                let rotated: Vec<f64> = (0..12).map(|i| profile[(i + 12 - tonic) % 12]).collect();
+                let correlation = Transcriber::correlation(histogram, &rotated);
+
+                if correlation > best_correlation {
+                    best_correlation = correlation;
+                    best_key = Some((NoteName::from_semitone_index(tonic as i32), mode));
+                }
+            }
+        }
+
+        best_key
+    }
+
+    /// Calculates the Pearson correlation coefficient of two equal length
+    /// slices
+    fn correlation(x: &[f64], y: &[f64]) -> f64 {
+        let n = x.len() as f64;
+        let mean_x = x.iter().sum::<f64>() / n;
+        let mean_y = y.iter().sum::<f64>() / n;
+
+        let covariance: f64 = x
+            .iter()
+            .zip(y)
+            .map(|(a, b)| (a - mean_x) * (b - mean_y))
+            .sum();
+        let variance_x: f64 = x.iter().map(|a| (a - mean_x).powi(2)).sum();
+        let variance_y: f64 = y.iter().map(|b| (b - mean_y).powi(2)).sum();
+
+        covariance / (variance_x * variance_y).sqrt()
+    }
+
+    /// Scales the samples in the audio data so that the largest absolute
+    /// sample value is 1.0
+    ///
+    /// If the audio is silent, all samples in the returned audio data are 0.0.
+    pub(crate) fn scale_samples(audio_data: &AudioData) -> AudioData {
+        let abs_max_value = audio_data
+            .samples
+            .iter()
+            .fold(0.0, |max: f64, x| max.max(x.abs()));
+
+        let samples = audio_data
+            .samples
+            .iter()
+            .map(|x| {
+                if abs_max_value >= f64::EPSILON {
+                    x / abs_max_value
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        AudioData {
+            sample_rate: audio_data.sample_rate,
+            duration: audio_data.duration,
+            samples,
+        }
+    }
+
+    /// Estimates the tempo of the audio (in beats per minute) from the
+    /// intervals between the note onsets
+    ///
+    /// The interval between each onset and every later onset within the
+    /// longest beat period is converted to a tempo, and the tempos between 40
+    /// and 200 BPM are counted in a histogram with 1 BPM wide bins. The mean
+    /// of the tempos in the most common bin is returned.
+    ///
+    /// Returns the tempo wrapped in Some, or None if fewer than 4 onsets were
+    /// detected.
+    pub fn estimate_tempo(audio_data: &AudioData) -> Option<f64> {
+        let onsets: Vec<usize> = SpectralFluxDetector::default()
+            .get_onset_samples(&Transcriber::scale_samples(audio_data));
+
+        Transcriber::tempo_from_onsets(&onsets, audio_data.sample_rate)
+    }
+
+    /// Estimates the tempo (in beats per minute) from sorted onset positions
+    /// in the same way as `estimate_tempo`
+    fn tempo_from_onsets(onsets: &[usize], sample_rate: u32) -> Option<f64> {
+        if onsets.len() < 4 {
+            return None;
+        }
+
+        let num_bins = (MAX_TEMPO_BPM - MIN_TEMPO_BPM) as usize + 1;
+        let mut histogram: Vec<Vec<f64>> = vec![vec![]; num_bins];
+
+        for (i, start) in onsets.iter().enumerate() {
+            for end in onsets[i + 1..].iter() {
+                let interval = (end - start) as f64 / sample_rate as f64;
+                let bpm = 60.0 / interval;
+
+                if bpm < MIN_TEMPO_BPM {
+                    break;
+                }
+                if bpm <= MAX_TEMPO_BPM {
+                    histogram[(bpm - MIN_TEMPO_BPM).round() as usize].push(bpm);
+                }
+            }
+        }
+
+        // The first of several equally common bins is used
+        let mode = histogram
+            .iter()
+            .rev()
+            .max_by_key(|bin| bin.len())
+            .filter(|bin| !bin.is_empty())?;
+
+        Some(mode.iter().sum::<f64>() / mode.len() as f64)
+    }
+
+    /// Transposes each note by the given number of semitones, up if
+    /// `semitones` is positive and down if it is negative
+    ///
+    /// Only the names of the notes are changed. Panics if any note would be
+    /// transposed below C0.
+    pub fn transpose_notes(notes: Vec<Note>, semitones: i32) -> Vec<Note> {
+        notes
+            .into_iter()
+            .map(|note| Note {
+                name: note.name.transpose(semitones),
+                ..note
+            })
+            .collect()
+    }
+
+    /// Merges runs of consecutive notes with the same name into single notes
+    ///
+    /// A note is merged into the previous note if they have the same name and
+    /// the gap between the end of the previous note and the start of the note
+    /// is less than `max_gap_samples`. The merged note keeps the start,
+    /// confidence and cents deviation of the first note of the run, and lasts
+    /// until the end of the last note of the run.
+    ///
+    /// # Arguments
+    ///
+    /// * `notes` - Notes sorted by start position
+    /// * `max_gap_samples` - Notes are only merged if the gap (in samples)
+    ///   between them is less than this, so it must be positive for any notes
+    ///   to be merged
+    pub fn merge_repeated_notes(notes: Vec<Note>, max_gap_samples: usize) -> Vec<Note> {
+        let mut merged: Vec<Note> = vec![];
+
+        for note in notes {
+            match merged.last_mut() {
+                Some(previous)
+                    if previous.name == note.name
+                        && note
+                            .start
+                            .saturating_sub(previous.start + previous.duration)
+                            < max_gap_samples =>
+                {
+                    let end = std::cmp::max(
+                        previous.start + previous.duration,
+                        note.start + note.duration,
+                    );
+                    previous.duration = end - previous.start;
+                }
+                _ => merged.push(note),
+            }
+        }
+
+        merged
+    }
+
+    /// Replaces the duration of each note with the nearest of the note values
+    /// whole, dotted half, half, dotted quarter, quarter, eighth and
+    /// sixteenth, where a quarter note lasts one beat
+    ///
+    /// If `bpm` is None, the tempo is estimated from the start positions of
+    /// the notes as in `estimate_tempo`, and the notes are returned unchanged
+    /// if it cannot be estimated.
+    ///
+    /// # Arguments
+    ///
+    /// * `notes` - Notes to quantise, in any order
+    /// * `sample_rate` - Sample rate (in Hz) of the audio the notes were detected in
+    /// * `bpm` - (Optional) Tempo (in beats per minute)
+    pub fn quantise_durations(notes: Vec<Note>, sample_rate: u32, bpm: Option<f64>) -> Vec<Note> {
+        let bpm = match bpm {
+            Some(bpm) => bpm,
+            None => {
+                let mut onsets: Vec<usize> = notes.iter().map(|note| note.start).collect();
+                onsets.sort_unstable();
+                match Transcriber::tempo_from_onsets(&onsets, sample_rate) {
+                    Some(bpm) => bpm,
+                    None => return notes,
+                }
+            }
+        };
+        assert!(bpm > 0.0, "tempo must be positive");
+
+        let beat_length = 60.0 / bpm * sample_rate as f64;
+
+        notes
+            .into_iter()
+            .map(|note| {
+                let duration = NOTE_VALUE_BEATS
+                    .iter()
+                    .map(|beats| (beats * beat_length).round() as usize)
+                    .min_by_key(|duration| duration.abs_diff(note.duration))
+                    .unwrap_or(note.duration);
+                Note { duration, ..note }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod note_tests {
+        use crate::transcription::{Mode, Note, NoteName, Scale};
+
+        #[test]
+        fn times_are_converted_to_seconds() {
+            let note = Note {
+                name: NoteName::A(4),
+                start: 44100,
+                duration: 22050,
+                confidence: 1.0,
+                cents_deviation: 0.0,
+            };
+
+            assert_eq!(1.0, note.start_seconds(44100));
+            assert_eq!(0.5, note.duration_seconds(44100));
+            assert_eq!(2.0, note.start_seconds(22050));
+            assert_eq!(1.0, note.duration_seconds(22050));
+        }
+
+        #[test]
+        fn time_range_works_correctly() {
+            let note = Note {
+                name: NoteName::A(4),
+                start: 44100,
+                duration: 22050,
+                confidence: 1.0,
+                cents_deviation: 0.0,
+            };
+
+            assert_eq!(1.0..1.5, note.time_range(44100));
+            assert_eq!(2.0..3.0, note.time_range(22050));
+        }
+
+        #[test]
+        fn contains_time_includes_start_but_not_end() {
+            let note = Note {
+                name: NoteName::A(4),
+                start: 44100,
+                duration: 22050,
+                confidence: 1.0,
+                cents_deviation: 0.0,
+            };
+
+            assert!(note.contains_time(1.0, 44100));
+            assert!(note.contains_time(1.25, 44100));
+            assert!(!note.contains_time(1.5, 44100));
+            assert!(!note.contains_time(0.999, 44100));
+        }
+
+        fn solfege(name: NoteName, root: NoteName, mode: Mode) -> String {
+            let note = Note {
+                name,
+                start: 0,
+                duration: 1,
+                confidence: 1.0,
+                cents_deviation: 0.0,
+            };
+
+            note.to_solfege(&Scale::new(root, mode))
+        }
+
+        #[test]
+        fn major_scale_degrees_are_named_correctly() {
+            let c_major = [
+                NoteName::C(4),
+                NoteName::D(4),
+                NoteName::E(4),
+                NoteName::F(4),
+                NoteName::G(4),
+                NoteName::A(4),
+                NoteName::B(4),
+            ];
+            let syllables: Vec<String> = c_major
+                .into_iter()
+                .map(|name| solfege(name, NoteName::C(0), Mode::Major))
+                .collect();
+
+            assert_eq!(vec!["Do", "Re", "Mi", "Fa", "Sol", "La", "Ti"], syllables);
+            assert_eq!(
+                "Mi",
+                solfege(NoteName::FSharp(2), NoteName::D(0), Mode::Major)
+            );
+        }
+
+        #[test]
+        fn minor_keys_are_la_based() {
+            assert_eq!("Do", solfege(NoteName::C(4), NoteName::A(0), Mode::Minor));
+            assert_eq!("La", solfege(NoteName::A(4), NoteName::A(0), Mode::Minor));
+            assert_eq!(
+                "Si",
+                solfege(NoteName::GSharp(4), NoteName::A(0), Mode::Minor)
+            );
+            assert_eq!(
+                "Si",
+                solfege(NoteName::CSharp(4), NoteName::D(0), Mode::Minor)
+            );
+        }
+
+        #[test]
+        fn chromatic_notes_are_raised_or_lowered() {
+            assert_eq!(
+                "Fi",
+                solfege(NoteName::FSharp(4), NoteName::C(0), Mode::Major)
+            );
+            assert_eq!(
+                "Di",
+                solfege(NoteName::CSharp(4), NoteName::C(0), Mode::Major)
+            );
+            assert_eq!(
+                "Te",
+                solfege(NoteName::DSharp(4), NoteName::F(0), Mode::Major)
+            );
+            assert_eq!(
+                "Me",
+                solfege(NoteName::GSharp(4), NoteName::F(0), Mode::Major)
+            );
+        }
+    }
+
+    mod display_style_tests {
+        use crate::transcription::{DisplayStyle, NoteName};
+
+        #[test]
+        fn sharp_notes_have_correct_flat_names() {
+            assert_eq!("Bb4", NoteName::ASharp(4).to_flat_string());
+            assert_eq!("Db3", NoteName::CSharp(3).to_flat_string());
+            assert_eq!("Eb4", NoteName::DSharp(4).to_flat_string());
+            assert_eq!("Gb0", NoteName::FSharp(0).to_flat_string());
+            assert_eq!("Ab7", NoteName::GSharp(7).to_flat_string());
+        }
+
+        #[test]
+        fn natural_notes_are_unaffected() {
+            for name in [
+                NoteName::A(4),
+                NoteName::B(2),
+                NoteName::C(5),
+                NoteName::D(1),
+                NoteName::E(3),
+                NoteName::F(6),
+                NoteName::G(0),
+            ] {
+                assert_eq!(name.to_string(), name.to_flat_string());
+                assert_eq!(
+                    name.to_string(),
+                    name.display_with_style(DisplayStyle::Sharp)
+                );
+            }
+        }
+
+        #[test]
+        fn sharp_style_matches_display() {
+            assert_eq!(
+                "A#4",
+                NoteName::ASharp(4).display_with_style(DisplayStyle::Sharp)
+            );
+            assert_eq!("A#4", NoteName::ASharp(4).to_string());
+            assert_eq!("G#2", NoteName::GSharp(2).to_string());
+        }
+    }
+
+    mod ordering_tests {
+        use crate::transcription::NoteName;
+        use std::collections::{BTreeMap, HashSet};
+
+        #[test]
+        fn notes_are_sorted_by_pitch() {
+            let mut names = [NoteName::G(4), NoteName::C(4), NoteName::E(4)];
+            names.sort();
+
+            assert_eq!([NoteName::C(4), NoteName::E(4), NoteName::G(4)], names);
+        }
+
+        #[test]
+        fn octave_changes_between_b_and_c() {
+            assert!(NoteName::A(0) < NoteName::ASharp(0));
+            assert!(NoteName::ASharp(0) < NoteName::B(0));
+            assert!(NoteName::B(0) < NoteName::C(1));
+            assert!(NoteName::C(4) > NoteName::B(3));
+        }
+
+        #[test]
+        fn hash_set_holds_unique_notes() {
+            let names: HashSet<NoteName> = [
+                NoteName::C(4),
+                NoteName::E(4),
+                NoteName::C(4),
+                NoteName::C(5),
+            ]
+            .into_iter()
+            .collect();
+
+            assert_eq!(3, names.len());
+            assert!(names.contains(&NoteName::C(4)));
+            assert!(names.contains(&NoteName::C(5)));
+        }
+
+        #[test]
+        fn btree_map_counts_note_occurrences() {
+            // First phrase of "Twinkle Twinkle Little Star"
+            let names = [
+                NoteName::C(4),
+                NoteName::C(4),
+                NoteName::G(4),
+                NoteName::G(4),
+                NoteName::A(4),
+                NoteName::A(4),
+                NoteName::G(4),
+            ];
+
+            let mut counts = BTreeMap::<NoteName, usize>::new();
+            for name in names {
+                *counts.entry(name).or_default() += 1;
+            }
+
+            assert_eq!(
+                vec![
+                    (NoteName::C(4), 2),
+                    (NoteName::G(4), 3),
+                    (NoteName::A(4), 2)
+                ],
+                counts.into_iter().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    mod frequency_tests {
+        use crate::transcription::NoteName;
+
+        #[test]
+        fn reference_notes_have_correct_frequencies() {
+            assert_eq!(440.0, NoteName::A(4).frequency());
+            assert_eq!(220.0, NoteName::A(3).frequency());
+            assert!((NoteName::C(4).frequency() - 261.6256).abs() < 1e-4);
+        }
+
+        #[test]
+        fn frequency_round_trips_through_from_pitch() {
+            for n in 36..=95 {
+                let name = NoteName::from_midi_number(n);
+
+                assert_eq!(name, NoteName::from_pitch(name.frequency()));
+            }
+        }
+    }
+
+    mod midi_number_tests {
+        use crate::transcription::NoteName;
+
+        #[test]
+        fn reference_notes_have_correct_midi_numbers() {
+            assert_eq!(60, NoteName::C(4).to_midi_number());
+            assert_eq!(69, NoteName::A(4).to_midi_number());
+            assert_eq!(70, NoteName::ASharp(4).to_midi_number());
+            assert_eq!(12, NoteName::C(0).to_midi_number());
+            assert_eq!(127, NoteName::G(9).to_midi_number());
+        }
+
+        #[test]
+        fn octave_changes_between_b_and_c() {
+            assert_eq!(59, NoteName::B(3).to_midi_number());
+            assert_eq!(60, NoteName::C(4).to_midi_number());
+            assert_eq!(NoteName::B(3), NoteName::from_midi_number(59));
+            assert_eq!(NoteName::C(4), NoteName::from_midi_number(60));
+        }
+
+        #[test]
+        fn midi_numbers_round_trip() {
+            for n in 12..=127 {
+                assert_eq!(n, NoteName::from_midi_number(n).to_midi_number());
+            }
+        }
+
+        #[test]
+        fn midi_numbers_in_octave_minus_one_should_panic() {
+            for n in 0..12 {
+                assert!(std::panic::catch_unwind(|| NoteName::from_midi_number(n)).is_err());
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "MIDI note numbers must not be greater than 127")]
+        fn midi_number_above_127_should_panic() {
+            NoteName::from_midi_number(128);
+        }
+
+        #[test]
+        #[should_panic(expected = "notes above G9 have no MIDI note number")]
+        fn note_above_g9_should_panic() {
+            NoteName::GSharp(9).to_midi_number();
+        }
+    }
+
+    mod transpose_tests {
+        use crate::transcription::{Note, NoteName, Transcriber};
+
+        #[test]
+        fn semitone_distance_is_signed() {
+            assert_eq!(7, NoteName::C(4).semitone_distance(&NoteName::G(4)));
+            assert_eq!(-5, NoteName::C(4).semitone_distance(&NoteName::G(3)));
+            assert_eq!(12, NoteName::A(4).semitone_distance(&NoteName::A(5)));
+            assert_eq!(0, NoteName::E(2).semitone_distance(&NoteName::E(2)));
+        }
+
+        #[test]
+        fn transpose_crosses_octaves() {
+            assert_eq!(NoteName::C(5), NoteName::A(4).transpose(3));
+            assert_eq!(NoteName::ASharp(3), NoteName::C(4).transpose(-2));
+            assert_eq!(NoteName::A(4), NoteName::A(4).transpose(0));
+        }
+
+        #[test]
+        fn transpose_reverses_semitone_distance() {
+            let note = NoteName::D(3);
+            let other = NoteName::FSharp(5);
+
+            assert_eq!(other, note.transpose(note.semitone_distance(&other)));
+        }
+
+        #[test]
+        #[should_panic(expected = "notes below C0 are not supported")]
+        fn transpose_below_c0_should_panic() {
+            NoteName::D(0).transpose(-3);
+        }
+
+        #[test]
+        #[should_panic(expected = "octave must fit in a u8")]
+        fn transpose_above_octave_255_should_panic() {
+            NoteName::C(255).transpose(12);
+        }
+
+        #[test]
+        fn transpose_notes_only_changes_names() {
+            let notes = vec![
+                Note {
+                    name: NoteName::C(4),
+                    start: 0,
+                    duration: 22050,
+                    confidence: 0.9,
+                    cents_deviation: 5.0,
+                },
+                Note {
+                    name: NoteName::E(4),
+                    start: 22050,
+                    duration: 11025,
+                    confidence: 0.8,
+                    cents_deviation: -3.0,
+                },
+            ];
+
+            let transposed = Transcriber::transpose_notes(notes.clone(), 7);
+
+            assert_eq!(NoteName::G(4), transposed[0].name);
+            assert_eq!(NoteName::B(4), transposed[1].name);
+            for (note, transposed) in notes.iter().zip(&transposed) {
+                assert_eq!(note.start, transposed.start);
+                assert_eq!(note.duration, transposed.duration);
+                assert_eq!(note.confidence, transposed.confidence);
+                assert_eq!(note.cents_deviation, transposed.cents_deviation);
+            }
+        }
+    }
+
+    mod from_str_tests {
+        use crate::errors::NoteParseError;
+        use crate::transcription::NoteName;
+
+        #[test]
+        fn sharp_and_flat_spellings_are_parsed() {
+            let expected = [
+                ("C4", "C4", NoteName::C(4)),
+                ("C#4", "Db4", NoteName::CSharp(4)),
+                ("D4", "D4", NoteName::D(4)),
+                ("D#4", "Eb4", NoteName::DSharp(4)),
+                ("E4", "E4", NoteName::E(4)),
+                ("F4", "F4", NoteName::F(4)),
+                ("F#4", "Gb4", NoteName::FSharp(4)),
+                ("G4", "G4", NoteName::G(4)),
+                ("G#4", "Ab4", NoteName::GSharp(4)),
+                ("A4", "A4", NoteName::A(4)),
+                ("A#4", "Bb4", NoteName::ASharp(4)),
+                ("B4", "B4", NoteName::B(4)),
+            ];
+
+            for (sharp, flat, name) in expected {
+                assert_eq!(Ok(name), sharp.parse::<NoteName>());
+                assert_eq!(Ok(name), flat.parse::<NoteName>());
+            }
+        }
+
+        #[test]
+        fn letter_is_case_insensitive() {
+            assert_eq!(Ok(NoteName::CSharp(3)), "c#3".parse::<NoteName>());
+            assert_eq!(Ok(NoteName::ASharp(3)), "bb3".parse::<NoteName>());
+        }
+
+        #[test]
+        fn octaves_0_and_8_are_parsed() {
+            assert_eq!(Ok(NoteName::A(0)), "A0".parse::<NoteName>());
+            assert_eq!(Ok(NoteName::C(8)), "C8".parse::<NoteName>());
+        }
+
+        #[test]
+        fn accidentals_can_cross_octave_boundaries() {
+            assert_eq!(Ok(NoteName::B(3)), "Cb4".parse::<NoteName>());
+            assert_eq!(Ok(NoteName::C(4)), "B#3".parse::<NoteName>());
+            assert_eq!(Ok(NoteName::F(2)), "E#2".parse::<NoteName>());
+        }
+
+        #[test]
+        fn display_output_round_trips() {
+            for n in 12..=127 {
+                let name = NoteName::from_midi_number(n);
+
+                assert_eq!(Ok(name), name.to_string().parse::<NoteName>());
+                assert_eq!(Ok(name), name.to_flat_string().parse::<NoteName>());
+            }
+        }
+
+        #[test]
+        fn invalid_names_return_error() {
+            for s in [
+                "", "H4", "C", "C#", "C10", "C-1", "C##4", "CB4", "Cb0", " C4", "C4 ",
+            ] {
+                assert_eq!(Err(NoteParseError(s.to_string())), s.parse::<NoteName>());
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod serde_tests {
+        use crate::transcription::{Note, NoteName};
+
+        #[test]
+        fn note_round_trips_through_json() {
+            let note = Note {
+                name: NoteName::CSharp(3),
+                start: 44100,
+                duration: 22050,
+                confidence: 0.9,
+                cents_deviation: -3.5,
+            };
+
+            let json = serde_json::to_string(&note).unwrap();
+            let parsed: Note = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(note.name, parsed.name);
+            assert_eq!(note.start, parsed.start);
+            assert_eq!(note.duration, parsed.duration);
+            assert_eq!(note.confidence, parsed.confidence);
+            assert_eq!(note.cents_deviation, parsed.cents_deviation);
+        }
+
+        #[test]
+        fn note_name_serializes_as_string() {
+            assert_eq!(
+                "\"A#4\"",
+                serde_json::to_string(&NoteName::ASharp(4)).unwrap()
+            );
+            assert_eq!(
+                NoteName::ASharp(4),
+                serde_json::from_str::<NoteName>("\"Bb4\"").unwrap()
+            );
+            assert!(serde_json::from_str::<NoteName>("\"H4\"").is_err());
+        }
+    }
+
+    mod scale_quantizer_tests {
+        use crate::transcription::{Mode, Note, NoteName, Scale, ScaleQuantizer};
+
+        fn notes(names: Vec<NoteName>) -> Vec<Note> {
+            names
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| Note {
+                    name,
+                    start: 1000 * i,
+                    duration: 1000,
+                    confidence: 1.0,
+                    cents_deviation: 0.0,
+                })
+                .collect()
+        }
+
+        fn quantized_names(scale: Scale, names: Vec<NoteName>) -> Vec<NoteName> {
+            ScaleQuantizer::new(scale)
+                .quantize(notes(names))
+                .into_iter()
+                .map(|note| note.name)
+                .collect()
+        }
+
+        #[test]
+        fn interval_to_works_correctly() {
+            assert_eq!(7, NoteName::C(4).interval_to(&NoteName::G(4)));
+            assert_eq!(5, NoteName::G(4).interval_to(&NoteName::C(5)));
+            assert_eq!(0, NoteName::A(2).interval_to(&NoteName::A(5)));
+            assert_eq!(11, NoteName::C(4).interval_to(&NoteName::B(3)));
+        }
+
+        #[test]
+        fn notes_in_scale_are_unchanged() {
+            let names = vec![
+                NoteName::C(4),
+                NoteName::D(4),
+                NoteName::E(4),
+                NoteName::F(4),
+                NoteName::G(4),
+                NoteName::A(4),
+                NoteName::B(4),
+            ];
+
+            assert_eq!(
+                names,
+                quantized_names(Scale::new(NoteName::C(0), Mode::Major), names.clone())
+            );
+        }
+
+        #[test]
+        fn equidistant_notes_are_quantized_down() {
+            assert_eq!(
+                vec![NoteName::C(4), NoteName::F(4), NoteName::A(4)],
+                quantized_names(
+                    Scale::new(NoteName::C(0), Mode::Major),
+                    vec![
+                        NoteName::CSharp(4),
+                        NoteName::FSharp(4),
+                        NoteName::ASharp(4)
+                    ]
+                )
+            );
+            assert_eq!(
+                vec![NoteName::G(3)],
+                quantized_names(
+                    Scale::new(NoteName::A(0), Mode::Minor),
+                    vec![NoteName::GSharp(3)]
+                )
+            );
+        }
+
+        #[test]
+        fn quantization_crosses_octave_boundaries() {
+            // C is not in D major, and the B below it is chosen over the C#
+            assert_eq!(
+                vec![NoteName::B(3)],
+                quantized_names(
+                    Scale::new(NoteName::D(0), Mode::Major),
+                    vec![NoteName::C(4)]
+                )
+            );
+        }
+
+        #[test]
+        fn note_timing_is_unchanged() {
+            let quantized = ScaleQuantizer::new(Scale::new(NoteName::C(0), Mode::Major))
+                .quantize(notes(vec![NoteName::CSharp(4), NoteName::E(4)]));
+
+            assert_eq!(0, quantized[0].start);
+            assert_eq!(1000, quantized[1].start);
+            assert_eq!(1000, quantized[1].duration);
+        }
+    }
+
+    mod estimate_key_tests {
+        use crate::transcription::{Mode, NoteName, Transcriber, MAJOR_KEY_PROFILE};
+
+        #[test]
+        fn profiles_are_detected_as_their_own_keys() {
+            assert_eq!(
+                Some((NoteName::C(0), Mode::Major)),
+                Transcriber::estimate_key(&MAJOR_KEY_PROFILE)
+            );
+        }
+
+        #[test]
+        fn transposed_scale_is_detected() {
+            // Pitch classes of an A minor scale, with extra weight on the tonic
+            // and dominant
+            let mut histogram = [0.0; 12];
+            for (pitch_class, weight) in [
+                (9, 3.0),
+                (11, 1.0),
+                (0, 1.0),
+                (2, 1.0),
+                (4, 2.0),
+                (5, 1.0),
+                (7, 1.0),
+            ] {
+                histogram[pitch_class] = weight;
+            }
+
+            assert_eq!(
+                Some((NoteName::A(0), Mode::Minor)),
+                Transcriber::estimate_key(&histogram)
+            );
+        }
+
+        #[test]
+        fn empty_histogram_has_no_key() {
+            assert_eq!(None, Transcriber::estimate_key(&[0.0; 12]));
+        }
+    }
+
+    mod detect_key_tests {
+        use crate::audio_utils::AudioData;
+        use crate::transcription::{Mode, Note, NoteName, Transcriber};
+        use std::path::PathBuf;
+
+        #[test]
+        fn detects_key_of_c_major_scale_transcription() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/piano_C_Major_scale.wav");
+            let notes = Transcriber::get_notes(AudioData::read_wav_file(filepath).unwrap());
+
+            assert_eq!(
+                Some((NoteName::C(0), Mode::Major)),
+                Transcriber::detect_key(&notes)
+            );
+        }
+
+        #[test]
+        fn fewer_than_eight_notes_has_no_key() {
+            let notes: Vec<Note> = [NoteName::C(4), NoteName::E(4), NoteName::G(4)]
+                .into_iter()
+                .map(|name| Note {
+                    name,
+                    start: 0,
+                    duration: 1,
+                    confidence: 1.0,
+                    cents_deviation: 0.0,
+                })
+                .collect();
+
+            assert_eq!(None, Transcriber::detect_key(&notes));
+        }
+    }
+
+    mod get_chords_tests {
+        use crate::audio_utils::AudioData;
+        use crate::test_utils::decaying_chord;
+        use crate::transcription::{NoteName, Transcriber, TranscriptionConfig};
+        use std::path::PathBuf;
+
+        #[test]
+        fn c_major_triad_is_detected() {
+            let chords = Transcriber::get_chords(decaying_chord(&[261.63, 329.63, 392.0], 1.0));
+
+            assert_eq!(1, chords.len());
+            let names: Vec<NoteName> = chords[0].notes.iter().map(|n| n.name).collect();
+            assert_eq!(vec![NoteName::C(4), NoteName::E(4), NoteName::G(4)], names);
+        }
+
+        #[test]
+        fn notes_sharing_harmonics_are_detected() {
+            // The fourth harmonic of E4 is almost the same as the third
+            // harmonic of A4
+            let chords = Transcriber::get_chords(decaying_chord(&[329.63, 440.0], 1.0));
+
+            assert_eq!(1, chords.len());
+            let names: Vec<NoteName> = chords[0].notes.iter().map(|n| n.name).collect();
+            assert_eq!(vec![NoteName::E(4), NoteName::A(4)], names);
+        }
+
+        #[test]
+        fn max_voices_limits_notes_per_chord() {
+            let chords = Transcriber::get_chords_with_config(
+                decaying_chord(&[261.63, 329.63, 392.0], 1.0),
+                TranscriptionConfig {
+                    max_voices: 2,
+                    ..TranscriptionConfig::default()
+                },
+            );
+
+            assert_eq!(1, chords.len());
+            assert_eq!(2, chords[0].notes.len());
+        }
+
+        #[test]
+        fn monophonic_audio_gives_single_note_chords() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/piano_C_Major_scale.wav");
+
+            let notes = Transcriber::get_notes(AudioData::read_wav_file(filepath.clone()).unwrap());
+            let chords = Transcriber::get_chords(AudioData::read_wav_file(filepath).unwrap());
+
+            assert_eq!(notes.len(), chords.len());
+            for (note, chord) in notes.iter().zip(chords.iter()) {
+                assert_eq!(note.start, chord.start);
+                assert!(chord.notes.iter().any(|n| n.name == note.name));
+            }
+        }
+    }
+
+    mod detect_chords_tests {
+        use crate::transcription::{Chord, ChordQuality, Note, NoteName, Transcriber};
+
+        fn chord(names: &[NoteName]) -> Chord {
+            Chord {
+                start: 0,
+                duration: 22050,
+                notes: names
+                    .iter()
+                    .map(|name| Note {
+                        name: *name,
+                        start: 0,
+                        duration: 22050,
+                        confidence: 1.0,
+                        cents_deviation: 0.0,
+                    })
+                    .collect(),
+            }
+        }
+
+        fn root_and_quality(names: &[NoteName]) -> Option<(NoteName, ChordQuality)> {
+            Transcriber::detect_chords(&[chord(names)])
+                .first()
+                .map(|named| (named.root, named.quality))
+        }
+
+        #[test]
+        fn triads_are_identified() {
+            assert_eq!(
+                Some((NoteName::C(0), ChordQuality::Major)),
+                root_and_quality(&[NoteName::C(4), NoteName::E(4), NoteName::G(4)])
+            );
+            assert_eq!(
+                Some((NoteName::C(0), ChordQuality::Diminished)),
+                root_and_quality(&[NoteName::C(4), NoteName::DSharp(4), NoteName::FSharp(4)])
+            );
+            assert_eq!(
+                Some((NoteName::A(0), ChordQuality::Minor)),
+                root_and_quality(&[NoteName::A(3), NoteName::C(4), NoteName::E(4)])
+            );
+        }
+
+        #[test]
+        fn inversions_are_identified_by_root() {
+            assert_eq!(
+                Some((NoteName::C(0), ChordQuality::Major)),
+                root_and_quality(&[NoteName::E(3), NoteName::G(3), NoteName::C(4)])
+            );
+        }
+
+        #[test]
+        fn seventh_chords_are_identified() {
+            assert_eq!(
+                Some((NoteName::G(0), ChordQuality::DominantSeventh)),
+                root_and_quality(&[
+                    NoteName::G(3),
+                    NoteName::B(3),
+                    NoteName::D(4),
+                    NoteName::F(4)
+                ])
+            );
+        }
+
+        #[test]
+        fn unknown_chords_are_left_out() {
+            assert_eq!(
+                None,
+                root_and_quality(&[NoteName::C(4), NoteName::CSharp(4), NoteName::D(4)])
+            );
+        }
+    }
+
+    mod estimate_tempo_tests {
+        use crate::test_utils::click_track;
+        use crate::transcription::Transcriber;
+
+        #[test]
+        fn click_track_tempo_is_estimated() {
+            let bpm = Transcriber::estimate_tempo(&click_track(120.0, 10, 0.0)).unwrap();
+
+            assert!((bpm - 120.0).abs() <= 2.0, "estimated {bpm} BPM");
+        }
+
+        #[test]
+        fn fewer_than_four_onsets_gives_none() {
+            assert_eq!(
+                None,
+                Transcriber::estimate_tempo(&click_track(120.0, 3, 0.0))
+            );
+        }
+    }
+
+    mod filter_durations_tests {
+        use crate::transcription::{Note, NoteName, Transcriber, TranscriptionConfig};
+
+        fn notes() -> Vec<Note> {
+            [(0, 10), (10, 100)]
+                .into_iter()
+                .map(|(start, duration)| Note {
+                    name: NoteName::C(4),
+                    start,
+                    duration,
+                    confidence: 1.0,
+                    cents_deviation: 0.0,
+                })
+                .collect()
+        }
+
+        #[test]
+        fn short_notes_are_discarded() {
+            let filtered = Transcriber::filter_durations(
+                notes(),
+                &TranscriptionConfig {
+                    min_duration_samples: Some(50),
+                    ..TranscriptionConfig::default()
+                },
+            );
+
+            assert_eq!(1, filtered.len());
+            assert_eq!(100, filtered[0].duration);
+        }
+
+        #[test]
+        fn long_notes_are_truncated() {
+            let filtered = Transcriber::filter_durations(
+                notes(),
+                &TranscriptionConfig {
+                    max_duration_samples: Some(50),
+                    ..TranscriptionConfig::default()
+                },
+            );
+
+            let durations: Vec<usize> = filtered.iter().map(|note| note.duration).collect();
+            assert_eq!(vec![10, 50], durations);
+            assert_eq!(10, filtered[1].start);
+        }
+
+        #[test]
+        fn default_config_keeps_all_notes() {
+            assert_eq!(
+                notes(),
+                Transcriber::filter_durations(notes(), &TranscriptionConfig::default())
+            );
+        }
+    }
+
+    mod merge_repeated_notes_tests {
+        use crate::test_utils::note;
+        use crate::transcription::{NoteName, Transcriber};
+
+        #[test]
+        fn adjacent_repeated_notes_are_merged() {
+            let notes = vec![
+                note(NoteName::C(4), 0, 1000),
+                note(NoteName::C(4), 1000, 2000),
+                note(NoteName::C(4), 3000, 500),
+            ];
+
+            assert_eq!(
+                vec![note(NoteName::C(4), 0, 3500)],
+                Transcriber::merge_repeated_notes(notes, 1)
+            );
+        }
+
+        #[test]
+        fn notes_further_apart_than_max_gap_are_not_merged() {
+            let notes = vec![
+                note(NoteName::C(4), 0, 1000),
+                note(NoteName::C(4), 1099, 1000),
+                note(NoteName::C(4), 2300, 1000),
+            ];
+
+            assert_eq!(
+                vec![
+                    note(NoteName::C(4), 0, 2099),
+                    note(NoteName::C(4), 2300, 1000)
+                ],
+                Transcriber::merge_repeated_notes(notes, 100)
+            );
+        }
+
+        #[test]
+        fn notes_exactly_max_gap_apart_are_not_merged() {
+            let notes = vec![
+                note(NoteName::C(4), 0, 1000),
+                note(NoteName::C(4), 1100, 1000),
+            ];
+
+            assert_eq!(2, Transcriber::merge_repeated_notes(notes, 100).len());
+        }
+
+        #[test]
+        fn different_notes_are_not_merged() {
+            let notes = vec![
+                note(NoteName::C(4), 0, 1000),
+                note(NoteName::D(4), 1000, 1000),
+                note(NoteName::C(4), 2000, 1000),
+            ];
+
+            assert_eq!(3, Transcriber::merge_repeated_notes(notes, 1).len());
+        }
+    }
+
+    mod quantise_durations_tests {
+        use crate::test_utils::note;
+        use crate::transcription::{Note, NoteName, Transcriber};
+
+        fn durations(notes: Vec<Note>) -> Vec<usize> {
+            notes.iter().map(|note| note.duration).collect()
+        }
+
+        #[test]
+        fn durations_are_quantised_to_nearest_note_value() {
+            let notes = vec![
+                note(NoteName::C(4), 0, 22050),
+                note(NoteName::C(4), 22050, 23000),
+                note(NoteName::C(4), 44100, 30000),
+                note(NoteName::C(4), 88200, 5000),
+                note(NoteName::C(4), 132300, 80000),
+            ];
+
+            assert_eq!(
+                vec![22050, 22050, 33075, 5513, 88200],
+                durations(Transcriber::quantise_durations(notes, 44100, Some(120.0)))
+            );
+        }
+
+        #[test]
+        fn tempo_is_estimated_when_not_given() {
+            // Notes a beat apart at 60 BPM
+            let notes = (0..8)
+                .map(|i| note(NoteName::C(4), i * 44100, 40000))
+                .collect();
+
+            assert_eq!(
+                vec![44100; 8],
+                durations(Transcriber::quantise_durations(notes, 44100, None))
+            );
+        }
+
+        #[test]
+        fn tempo_is_estimated_from_unsorted_notes() {
+            // Notes a beat apart at 60 BPM, in reverse order
+            let notes = (0..8)
+                .rev()
+                .map(|i| note(NoteName::C(4), i * 44100, 40000))
+                .collect();
+
+            assert_eq!(
+                vec![44100; 8],
+                durations(Transcriber::quantise_durations(notes, 44100, None))
+            );
+        }
+
+        #[test]
+        fn notes_are_unchanged_when_tempo_cannot_be_estimated() {
+            let notes = vec![
+                note(NoteName::C(4), 0, 1000),
+                note(NoteName::C(4), 44100, 2000),
+            ];
+
+            assert_eq!(
+                vec![1000, 2000],
+                durations(Transcriber::quantise_durations(notes, 44100, None))
+            );
+        }
+    }
+
+    mod get_notes_with_timestamps_tests {
+        use crate::audio_utils::AudioData;
+        use crate::transcription::{Note, NoteName, TimedNote, Transcriber};
+        use std::path::PathBuf;
+
+        #[test]
+        fn note_at_one_second_starts_at_one_second() {
+            let note = Note {
+                name: NoteName::A(4),
+                start: 44100,
+                duration: 22050,
+                confidence: 1.0,
+                cents_deviation: 0.0,
+            };
+
+            let timed_note = TimedNote::new(note.clone(), 44100);
+
+            assert_eq!(note, timed_note.note);
+            assert_eq!(1.0, timed_note.start_seconds);
+            assert_eq!(1.5, timed_note.end_seconds);
+        }
+
+        #[test]
+        fn timestamps_match_notes() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+            let sample_rate = audio_data.sample_rate;
+
+            let notes = Transcriber::get_notes(audio_data.clone());
+            let timed_notes = Transcriber::get_notes_with_timestamps(audio_data);
+
+            assert_eq!(notes.len(), timed_notes.len());
+            for (note, timed_note) in notes.iter().zip(&timed_notes) {
+                assert_eq!(note, &timed_note.note);
+                assert_eq!(note.start_seconds(sample_rate), timed_note.start_seconds);
+                assert_eq!(
+                    (note.start + note.duration) as f64 / sample_rate as f64,
+                    timed_note.end_seconds
+                );
+            }
+        }
+    }
+
+    mod transcription_result_tests {
+        use crate::audio_utils::AudioData;
+        use crate::errors::OlscorerError;
+        use crate::test_utils::note;
+        use crate::transcription::{
+            Chord, ChordQuality, ExportFormat, NamedChord, Note, NoteName, Transcriber,
+            TranscriptionResult,
+        };
+        use std::path::PathBuf;
+        use tempfile::TempDir;
+
+        /// Creates a transcription of audio at 44100 Hz and 120 BPM, where a
+        /// quarter note is 22050 samples
+        fn transcription(notes: Vec<Note>, chords: Option<Vec<NamedChord>>) -> TranscriptionResult {
+            TranscriptionResult {
+                notes,
+                chords,
+                sample_rate: 44100,
+                bpm: 120.0,
+            }
+        }
+
+        fn named_chord(root: NoteName, quality: ChordQuality, start: usize) -> NamedChord {
+            NamedChord {
+                chord: Chord {
+                    start,
+                    duration: 22050,
+                    notes: vec![],
+                },
+                root,
+                quality,
+            }
+        }
+
+        #[test]
+        fn transcription_contains_notes_and_chords() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+
+            let result = Transcriber::transcribe(audio_data.clone(), true);
+
+            assert_eq!(Transcriber::get_notes(audio_data.clone()), result.notes);
+            assert_eq!(audio_data.sample_rate, result.sample_rate);
+            assert!(result.bpm > 0.0);
+            assert!(result.chords.is_some());
+            assert!(Transcriber::transcribe(audio_data, false).chords.is_none());
+        }
+
+        #[test]
+        fn notes_are_written_with_their_note_values() {
+            let notes = vec![
+                note(NoteName::C(4), 0, 22050),
+                note(NoteName::E(4), 22050, 22050),
+                note(NoteName::G(4), 44100, 44100),
+                note(NoteName::A(4), 88200, 88200),
+            ];
+
+            assert_eq!(
+                "C4/4 E4/4 G4/2 | A4/1 |\n",
+                transcription(notes, None).to_lead_sheet(4)
+            );
+        }
+
+        #[test]
+        fn rests_and_ties_are_written() {
+            // A rest, then a half note starting on the fourth beat, which is
+            // tied across the barline
+            let notes = vec![note(NoteName::FSharp(4), 66150, 44100)];
+
+            assert_eq!(
+                "r/2 r/4 F#4/4~ | F#4/4 r/2 r/4 |\n",
+                transcription(notes, None).to_lead_sheet(4)
+            );
+        }
+
+        #[test]
+        fn chord_symbols_are_written_above_their_measures() {
+            let notes = vec![
+                note(NoteName::C(4), 0, 44100),
+                note(NoteName::E(4), 44100, 44100),
+                note(NoteName::G(4), 88200, 88200),
+            ];
+            let chords = vec![
+                named_chord(NoteName::C(0), ChordQuality::Major, 0),
+                named_chord(NoteName::G(0), ChordQuality::DominantSeventh, 88200),
+            ];
+
+            assert_eq!(
+                "C           G7\nC4/2 E4/2 | G4/1 |\n",
+                transcription(notes, Some(chords)).to_lead_sheet(4)
+            );
+        }
+
+        #[test]
+        fn format_is_chosen_from_extension() {
+            let dir = TempDir::new().unwrap();
+            let result = transcription(vec![note(NoteName::C(4), 0, 22050)], None);
+
+            for (name, header) in [
+                ("score.musicxml", b"<?xml".as_slice()),
+                ("score.MID", b"MThd".as_slice()),
+                ("score.abc", b"X:1".as_slice()),
+                ("score.ly", b"\\version".as_slice()),
+                ("score.json", b"{".as_slice()),
+                ("score.csv", b"name,".as_slice()),
+            ] {
+                let path = dir.path().join(name);
+                result.export_to_file(&path, None).unwrap();
+
+                assert!(std::fs::read(path).unwrap().starts_with(header), "{name}");
+            }
+        }
+
+        #[test]
+        fn csv_has_a_row_for_each_note() {
+            let notes = vec![
+                note(NoteName::C(4), 0, 22050),
+                note(NoteName::FSharp(4), 22050, 11025),
+            ];
+
+            assert_eq!(
+                "name,start,duration,confidence,cents_deviation\n\
+                 C4,0,22050,1,0\n\
+                 F#4,22050,11025,1,0\n",
+                transcription(notes, None).to_csv()
+            );
+        }
+
+        #[test]
+        fn json_parses_correctly() {
+            let notes = vec![
+                note(NoteName::C(4), 0, 22050),
+                note(NoteName::FSharp(4), 22050, 11025),
+            ];
+
+            let json = transcription(notes, None).to_json();
+            let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(
+                serde_json::json!({
+                    "sample_rate": 44100,
+                    "bpm": 120.0,
+                    "notes": [
+                        {
+                            "name": "C4",
+                            "start": 0,
+                            "duration": 22050,
+                            "confidence": 1.0,
+                            "cents_deviation": 0.0
+                        },
+                        {
+                            "name": "F#4",
+                            "start": 22050,
+                            "duration": 11025,
+                            "confidence": 1.0,
+                            "cents_deviation": 0.0
+                        },
+                    ]
+                }),
+                parsed
+            );
+        }
+
+        #[test]
+        fn json_without_notes_parses_correctly() {
+            let json = transcription(vec![], None).to_json();
+
+            let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(Some(0), parsed["notes"].as_array().map(Vec::len));
+        }
+
+        #[test]
+        fn given_format_overrides_extension() {
+            let dir = TempDir::new().unwrap();
+            let path = dir.path().join("score.txt");
+            let result = transcription(vec![note(NoteName::C(4), 0, 22050)], None);
+
+            result
+                .export_to_file(&path, Some(ExportFormat::Midi))
+                .unwrap();
+
+            assert!(std::fs::read(path).unwrap().starts_with(b"MThd"));
+        }
+
+        #[test]
+        fn unknown_extension_returns_error() {
+            let dir = TempDir::new().unwrap();
+            let path = dir.path().join("score.txt");
+            let result = transcription(vec![note(NoteName::C(4), 0, 22050)], None);
+
+            assert!(matches!(
+                result.export_to_file(&path, None),
+                Err(OlscorerError::UnknownFormat(_))
+            ));
+            assert!(!path.exists());
+        }
+
+        #[test]
+        fn lines_have_four_measures() {
+            let notes = (0..5)
+                .map(|i| note(NoteName::C(4), i * 88200, 88200))
+                .collect();
+
+            assert_eq!(
+                "C4/1 | C4/1 | C4/1 | C4/1 |\nC4/1 |\n",
+                transcription(notes, None).to_lead_sheet(4)
+            );
+        }
+    }
+
+    mod get_notes_tests {
+        use crate::audio_utils::AudioData;
+        use crate::onset_detection::{OnsetDetector, OnsetEvent};
+        use crate::test_utils::sine_burst;
+        use crate::transcription::{
+            Mode, Note, NoteName, Scale, ScaleQuantizer, Transcriber, TranscriptionConfig,
+        };
+        use std::path::PathBuf;
+        use std::sync::{mpsc, Mutex};
+
+        #[test]
+        fn progress_increases_from_zero_to_one() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/twinkle_twinkle_little_star.wav");
+            let audio_data = AudioData::read_wav_file(filepath.clone()).unwrap();
+
+            let progress = Mutex::new(vec![]);
+            let notes = Transcriber::get_notes_with_progress(
+                audio_data,
+                TranscriptionConfig::default(),
+                |value| progress.lock().unwrap().push(value),
+            );
+            let progress = progress.into_inner().unwrap();
+
+            assert!(progress.len() >= notes.len());
+            assert!(progress[0] > 0.0);
+            assert!(progress.windows(2).all(|w| w[0] < w[1]));
+            assert_eq!(Some(&1.0), progress.last());
+
+            let expected_notes =
+                Transcriber::get_notes(AudioData::read_wav_file(filepath).unwrap());
+            assert_eq!(expected_notes.len(), notes.len());
+            for (a, b) in expected_notes.iter().zip(notes.iter()) {
+                assert_eq!(a.name, b.name);
+                assert_eq!(a.start, b.start);
+            }
+        }
+
+        #[test]
+        fn progress_is_complete_for_silent_audio() {
+            let progress = Mutex::new(vec![]);
+            let notes = Transcriber::get_notes_with_progress(
+                AudioData::from_raw_samples(vec![0.0; 44100], 44100),
+                TranscriptionConfig::default(),
+                |value| progress.lock().unwrap().push(value),
+            );
+
+            assert!(notes.is_empty());
+            assert_eq!(Some(&1.0), progress.into_inner().unwrap().last());
+        }
+
+        #[test]
+        fn parallel_detection_matches_sequential_detection() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/piano_C_Major_scale.wav");
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+
+            let mut parallel = vec![];
+            let config = TranscriptionConfig::default();
+            Transcriber::detect_notes(
+                &audio_data,
+                &config,
+                true,
+                |note| parallel.push(note),
+                |_| {},
+            );
+            let mut sequential = vec![];
+            Transcriber::detect_notes(
+                &audio_data,
+                &config,
+                false,
+                |note| sequential.push(note),
+                |_| {},
+            );
+
+            assert_eq!(8, sequential.len());
+            assert_eq!(sequential.len(), parallel.len());
+            for (a, b) in sequential.iter().zip(parallel.iter()) {
+                assert_eq!(a.name, b.name);
+                assert_eq!(a.start, b.start);
+                assert_eq!(a.duration, b.duration);
+                assert_eq!(a.confidence, b.confidence);
+            }
+        }
+
+        #[test]
+        fn default_config_matches_get_notes() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+
+            let notes = Transcriber::get_notes(AudioData::read_wav_file(filepath.clone()).unwrap());
+            let configured_notes = Transcriber::get_notes_with_config(
+                AudioData::read_wav_file(filepath).unwrap(),
+                TranscriptionConfig::default(),
+            );
+
+            assert_eq!(notes.len(), configured_notes.len());
+            for (a, b) in notes.iter().zip(configured_notes.iter()) {
+                assert_eq!(a.name, b.name);
+                assert_eq!(a.start, b.start);
+                assert_eq!(a.duration, b.duration);
+            }
+        }
+
+        /// Onset detector which always finds the same onsets
+        struct FixedOnsets(Vec<usize>);
+
+        impl OnsetDetector for FixedOnsets {
+            fn get_onsets(&self, _: &AudioData) -> Vec<OnsetEvent> {
+                self.0
+                    .iter()
+                    .map(|&sample| OnsetEvent {
+                        sample,
+                        confidence: 1.0,
+                    })
+                    .collect()
+            }
+        }
+
+        #[test]
+        fn configured_onset_detector_is_used() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let notes = Transcriber::get_notes_with_config(
+                AudioData::read_wav_file(filepath).unwrap(),
+                TranscriptionConfig {
+                    onset_detector: Box::new(FixedOnsets(vec![0, 22050])),
+                    ..TranscriptionConfig::default()
+                },
+            );
+
+            let starts: Vec<usize> = notes.iter().map(|note| note.start).collect();
+            assert_eq!(vec![0, 22050], starts);
+            assert!(notes.iter().all(|note| note.name == NoteName::A(4)));
+        }
+
+        #[test]
+        fn notes_from_unsorted_onsets_are_sorted_by_start_position() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let notes = Transcriber::get_notes_with_config(
+                AudioData::read_wav_file(filepath).unwrap(),
+                TranscriptionConfig {
+                    onset_detector: Box::new(FixedOnsets(vec![22050, 0])),
+                    ..TranscriptionConfig::default()
+                },
+            );
+
+            let starts: Vec<usize> = notes.iter().map(|note| note.start).collect();
+            assert_eq!(vec![0, 22050], starts);
+        }
+
+        #[test]
+        fn duplicate_onsets_give_one_note() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/sine_440Hz_44100samples_s16bit_44100Hz_mono.wav");
+
+            let notes = Transcriber::get_notes_with_config(
+                AudioData::read_wav_file(filepath).unwrap(),
+                TranscriptionConfig {
+                    onset_detector: Box::new(FixedOnsets(vec![0, 22050, 0])),
+                    ..TranscriptionConfig::default()
+                },
+            );
+
+            let starts: Vec<usize> = notes.iter().map(|note| note.start).collect();
+            assert_eq!(vec![0, 22050], starts);
+        }
+
+        #[test]
+        fn scale_quantizer_moves_notes_into_scale() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+
+            // C is not in D major, and the B below it is chosen over the C#
+            let notes = Transcriber::get_notes_with_config(
+                AudioData::read_wav_file(filepath).unwrap(),
+                TranscriptionConfig {
+                    scale_quantizer: Some(ScaleQuantizer::new(Scale::new(
+                        NoteName::D(0),
+                        Mode::Major,
+                    ))),
+                    ..TranscriptionConfig::default()
+                },
+            );
+
+            let names: Vec<NoteName> = notes.iter().map(|note| note.name).collect();
+            assert_eq!(vec![NoteName::B(4), NoteName::E(5), NoteName::G(5)], names);
+        }
+
+        #[test]
+        fn nsdf_median_filter_keeps_pitches() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+
+            let notes = Transcriber::get_notes_with_config(
+                AudioData::read_wav_file(filepath).unwrap(),
+                TranscriptionConfig {
+                    mpm_nsdf_median_window: Some(5),
+                    ..TranscriptionConfig::default()
+                },
+            );
+
+            let names: Vec<NoteName> = notes.iter().map(|note| note.name).collect();
+            assert_eq!(vec![NoteName::C(5), NoteName::E(5), NoteName::G(5)], names);
+        }
+
+        #[test]
+        #[should_panic(expected = "median filter window must be odd")]
+        fn even_nsdf_median_window_should_panic() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+
+            Transcriber::get_notes_with_config(
+                AudioData::read_wav_file(filepath).unwrap(),
+                TranscriptionConfig {
+                    mpm_nsdf_median_window: Some(4),
+                    ..TranscriptionConfig::default()
+                },
+            );
+        }
+
+        #[test]
+        fn max_pitch_frame_width_limits_note_duration() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+
+            let notes = Transcriber::get_notes_with_config(
+                AudioData::read_wav_file(filepath).unwrap(),
+                TranscriptionConfig {
+                    max_pitch_frame_width: 2048,
+                    ..TranscriptionConfig::default()
+                },
+            );
+
+            assert_eq!(3, notes.len());
+            assert!(notes.iter().all(|note| note.duration <= 2048));
+        }
+
+        #[test]
+        fn high_silence_ratio_discards_all_notes() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+
+            let notes = Transcriber::get_notes_with_config(
+                AudioData::read_wav_file(filepath).unwrap(),
+                TranscriptionConfig {
+                    silence_rms_ratio: 100.0,
+                    ..TranscriptionConfig::default()
+                },
+            );
+
+            assert!(notes.is_empty());
+        }
+
+        #[test]
+        fn sharp_tone_has_positive_cents_deviation() {
+            // Silence followed by a 445 Hz tone, which is 19.6 cents above A4
+            let notes = Transcriber::get_notes(sine_burst(445.0, 11025));
+
+            assert_eq!(1, notes.len());
+            assert_eq!(NoteName::A(4), notes[0].name);
+            assert!(
+                (notes[0].cents_deviation - 19.56).abs() < 1.0,
+                "deviation of {} cents",
+                notes[0].cents_deviation
+            );
+        }
+
+        #[test]
+        fn notes_have_confidence_between_zero_and_one() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+            let audio_data = AudioData::read_wav_file(filepath).unwrap();
+
+            let notes = Transcriber::get_notes(audio_data);
+
+            assert!(!notes.is_empty());
+            assert!(notes
+                .iter()
+                .all(|note| note.confidence > 0.0 && note.confidence <= 1.0));
+        }
+
+        #[test]
+        fn streamed_notes_match_collected_notes() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+
+            let (tx, rx) = mpsc::channel();
+            Transcriber::transcribe_to_channel(
+                AudioData::read_wav_file(filepath.clone()).unwrap(),
+                TranscriptionConfig::default(),
+                tx,
+            );
+
+            // The channel is closed once transcription is finished, so this
+            // doesn't block
+            let streamed: Vec<Note> = rx.iter().collect();
+            let collected = Transcriber::get_notes(AudioData::read_wav_file(filepath).unwrap());
+
+            assert_eq!(collected.len(), streamed.len());
+            for (a, b) in collected.iter().zip(streamed.iter()) {
+                assert_eq!(a.name, b.name);
+                assert_eq!(a.start, b.start);
+                assert_eq!(a.duration, b.duration);
+                assert_eq!(a.confidence, b.confidence);
+            }
+        }
+
+        #[test]
+        fn streamed_notes_use_given_config() {
+            let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            filepath.push("../resources/test/example_audio.wav");
+            let config = || TranscriptionConfig {
+                max_duration_samples: Some(4096),
+                scale_quantizer: Some(ScaleQuantizer::new(Scale::new(NoteName::D(0), Mode::Major))),
+                ..TranscriptionConfig::default()
+            };
+
+            let (tx, rx) = mpsc::channel();
+            Transcriber::transcribe_to_channel(
+                AudioData::read_wav_file(filepath.clone()).unwrap(),
+                config(),
+                tx,
+            );
+
+            let streamed: Vec<Note> = rx.iter().collect();
+            let collected = Transcriber::get_notes_with_config(
+                AudioData::read_wav_file(filepath).unwrap(),
+                config(),
+            );
+
+            assert_eq!(collected, streamed);
+            assert_eq!(NoteName::B(4), streamed[0].name);
+            assert!(streamed.iter().all(|note| note.duration <= 4096));
+        }
+
+        #[test]
+        fn silent_audio_has_no_notes() {
+            let audio_data = AudioData {
+                sample_rate: 44100,
+                duration: 0,
+                samples: vec![],
+            };
+
+            assert!(Transcriber::get_notes(audio_data).is_empty());
+        }
     }
 }
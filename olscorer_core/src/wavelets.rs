@@ -0,0 +1,100 @@
+/*
+ * Olscorer
+ * Automatic Music Transcription Software
+ *
+ * Copyright (C) 2023  Oleg Tretieu
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Calculates the detail coefficients of a stationary (undecimated) Haar
+/// wavelet transform of the samples
+///
+/// Unlike the standard Haar wavelet transform, a detail coefficient is
+/// calculated at every sample for every level, so that the coefficients do
+/// not depend on how the features in the signal line up with the wavelet
+/// blocks. The detail coefficient at sample `n` on level `l` is half the
+/// difference between the mean of the `2^(l - 1)` samples before `n` and the
+/// mean of the `2^(l - 1)` samples starting at `n`. Coefficients for which
+/// the wavelet would extend past either end of the samples are 0.
+///
+/// Returns one vector of detail coefficients per level, starting with the
+/// finest level (level 1).
+pub fn stationary_haar_details(samples: &[f64], levels: u8) -> Vec<Vec<f64>> {
+    // Prefix sums allow the mean of any block of samples to be calculated in
+    // constant time
+    let mut prefix_sums = Vec::with_capacity(samples.len() + 1);
+    prefix_sums.push(0.0);
+    for sample in samples {
+        prefix_sums.push(prefix_sums[prefix_sums.len() - 1] + sample);
+    }
+
+    (1..=levels)
+        .map(|level| {
+            let half_width = 1usize << (level - 1);
+            let mut details = vec![0.0; samples.len()];
+
+            if samples.len() >= 2 * half_width {
+                for (n, detail) in details
+                    .iter_mut()
+                    .enumerate()
+                    .take(samples.len() - half_width + 1)
+                    .skip(half_width)
+                {
+                    let before = prefix_sums[n] - prefix_sums[n - half_width];
+                    let after = prefix_sums[n + half_width] - prefix_sums[n];
+                    *detail = (before - after) / (2 * half_width) as f64;
+                }
+            }
+
+            details
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    mod stationary_haar_details_tests {
+        use crate::wavelets::stationary_haar_details;
+
+        #[test]
+        fn returns_one_vector_per_level() {
+            let details = stationary_haar_details(&[0.0; 100], 4);
+
+            assert_eq!(4, details.len());
+            assert!(details.iter().all(|level| level.len() == 100));
+        }
+
+        #[test]
+        fn step_is_detected_at_every_level() {
+            let mut samples = vec![0.0; 50];
+            samples.extend(vec![1.0; 50]);
+
+            let details = stationary_haar_details(&samples, 4);
+
+            for level in details.iter() {
+                assert_eq!(-0.5, level[50]);
+                assert_eq!(0.0, level[30]);
+                assert_eq!(0.0, level[70]);
+            }
+        }
+
+        #[test]
+        fn level_one_coefficients_are_half_the_difference_of_adjacent_samples() {
+            let details = stationary_haar_details(&[1.0, 3.0, 2.0, 2.0], 1);
+
+            assert_eq!(vec![0.0, -1.0, 0.5, 0.0], details[0]);
+        }
+    }
+}
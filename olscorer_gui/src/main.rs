@@ -21,28 +21,92 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use olscorer_core::audio_utils::AudioData;
-use olscorer_core::transcription::Transcriber;
+use olscorer_core::audio_utils::{AudioData, AudioFormat, WindowFunction};
+use olscorer_core::transcription::{Transcriber, TranscriptionConfig};
+use serde::Serialize;
+use std::sync::mpsc;
+use std::thread;
 
 fn main() {
     tauri::Builder::default()
-        .invoke_handler(tauri::generate_handler![get_notes, get_audio_data])
+        .invoke_handler(tauri::generate_handler![
+            transcribe_audio,
+            get_audio_data,
+            get_audio_data_from_bytes,
+            get_spectrogram
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
+/// Transcribes the audio in the background, emitting a `note-detected` event
+/// with the name of each note as it is detected, a `transcription-progress`
+/// event with the fraction of the audio transcribed so far, and a
+/// `transcription-finished` event once all notes have been found
 #[tauri::command]
-fn get_notes(audio_data: AudioData) -> String {
-    Transcriber::get_notes(audio_data)
-        .iter()
-        .map(|note| note.name.to_string())
-        .collect::<Vec<String>>()
-        .join(", ")
+fn transcribe_audio(window: tauri::Window, audio_data: AudioData) {
+    thread::spawn(move || {
+        let (tx, rx) = mpsc::channel();
+        let progress_window = window.clone();
+        thread::spawn(move || {
+            Transcriber::transcribe_to_channel_with_progress(
+                audio_data,
+                TranscriptionConfig::default(),
+                tx,
+                |progress| {
+                    progress_window
+                        .emit("transcription-progress", progress)
+                        .expect("Error emitting progress");
+                },
+            )
+        });
+
+        for note in rx {
+            window
+                .emit("note-detected", note.name.to_string())
+                .expect("Error emitting note");
+        }
+
+        window
+            .emit("transcription-finished", ())
+            .expect("Error emitting transcription finished event");
+    });
 }
 
 #[tauri::command]
 fn get_audio_data(filepath: &str) -> AudioData {
     let mut path = std::path::PathBuf::new();
     path.push(filepath);
-    AudioData::read_wav_file(path).expect("Error reading wav file")
+    AudioData::read_audio_file(path).expect("Error reading audio file")
+}
+
+/// Decodes the contents of an audio file passed from the frontend, such as
+/// the bytes of a dropped file
+#[tauri::command]
+fn get_audio_data_from_bytes(bytes: Vec<u8>, format: AudioFormat) -> AudioData {
+    AudioData::from_bytes(&bytes, format).expect("Error reading audio data")
+}
+
+/// Magnitude spectrogram of the audio, stored as a flat array of `num_frames`
+/// spectra of `num_bins` values each
+#[derive(Serialize)]
+struct Spectrogram {
+    num_frames: usize,
+    num_bins: usize,
+    magnitudes: Vec<f32>,
+}
+
+#[tauri::command]
+fn get_spectrogram(audio_data: AudioData, frame_width: usize, hop_size: usize) -> Spectrogram {
+    let spectrogram = audio_data.compute_spectrogram(frame_width, hop_size, WindowFunction::Hann);
+
+    Spectrogram {
+        num_frames: spectrogram.len(),
+        num_bins: frame_width / 2 + 1,
+        magnitudes: spectrogram
+            .into_iter()
+            .flatten()
+            .map(|x| x as f32)
+            .collect(),
+    }
 }
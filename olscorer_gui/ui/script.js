@@ -28,6 +28,7 @@ const transcribeButton = document.getElementById("transcribeButton");
 const saveButton = document.getElementById("saveFileButton");
 const audioDataOutputText = document.getElementById("audioDataResultText");
 const notesOutput = document.getElementById("notesResult");
+const progressBar = document.getElementById("transcriptionProgress");
 
 async function openFile() {
     appWindow.setCursorIcon("wait");
@@ -36,8 +37,8 @@ async function openFile() {
         multiple: false,
         filters: [
             {
-                name: "wav",
-                extensions: ["wav"],
+                name: "Audio",
+                extensions: ["wav", "flac", "mp3", "ogg"],
             },
         ],
     });
@@ -74,14 +75,44 @@ async function transcribeAudio(audioData) {
     if (audioData === undefined) {
         await message("No audio to transcribe", { type: "error" });
     } else {
-        let returnValue;
         appWindow.setCursorIcon("wait");
-        await invoke("get_notes", { audioData: audioData }).then((response) => {
-            appWindow.setCursorIcon("default");
-            returnValue = response;
+
+        const notes = [];
+        notesOutput.innerHTML = "";
+        progressBar.value = 0;
+        progressBar.hidden = false;
+
+        // Display notes as they are detected
+        const unlistenNote = await appWindow.listen("note-detected", (event) => {
+            notes.push(event.payload);
+            notesOutput.innerHTML = notes.join(", ");
+        });
+
+        const unlistenProgress = await appWindow.listen(
+            "transcription-progress",
+            (event) => {
+                progressBar.value = event.payload;
+            }
+        );
+
+        let finishTranscription;
+        const finished = new Promise((resolve) => {
+            finishTranscription = resolve;
         });
+        const unlistenFinished = await appWindow.listen(
+            "transcription-finished",
+            () => finishTranscription()
+        );
+
+        await invoke("transcribe_audio", { audioData: audioData });
+        await finished;
+
+        unlistenNote();
+        unlistenProgress();
+        unlistenFinished();
+        progressBar.hidden = true;
         appWindow.setCursorIcon("default");
-        return returnValue;
+        return notes.join(", ");
     }
 }
 